@@ -1,3 +1,208 @@
-fn main() {
-    println!("Hello, world!");
+//! Derive macros for `catalyze`, re-exported by it with the `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{ext::IdentExt, parse_macro_input, Attribute, Data, DeriveInput, Fields, LitStr};
+
+/// Derives `catalyze::FromMessage` for a struct with named fields.
+///
+/// Each field is read from the option message field of the same name with
+/// `catalyze::FromValue::from_field`. A different name can be set with
+/// `#[option(name = "...")]`.
+#[proc_macro_derive(FromMessage, attributes(option))]
+pub fn derive_from_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_message(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `catalyze::CustomOption` for a struct which also implements
+/// `catalyze::FromMessage`.
+///
+/// The fully qualified name of the extension is set with
+/// `#[option(extension = "...")]`, e.g. `validate.rules`.
+#[proc_macro_derive(CustomOption, attributes(option))]
+pub fn derive_custom_option(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    custom_option(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn from_message(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromMessage can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromMessage can only be derived for structs with named fields",
+        ));
+    };
+    let fields = fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().expect("named field");
+            let name = match option_attr(&field.attrs, "name")? {
+                Some(name) => name.value(),
+                None => ident.unraw().to_string(),
+            };
+            Ok(quote! {
+                #ident: ::catalyze::FromValue::from_field(message, &field(#name)?)?
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::catalyze::FromMessage for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_message(
+                message: &dyn ::catalyze::protobuf::MessageDyn,
+            ) -> ::std::result::Result<Self, ::catalyze::Error> {
+                let descriptor = message.descriptor_dyn();
+                let field = |name: &str| {
+                    descriptor.field_by_name(name).ok_or_else(|| {
+                        ::catalyze::Error::OptionFieldNotFound {
+                            message: descriptor.full_name().to_string(),
+                            field: name.to_string(),
+                        }
+                    })
+                };
+                ::std::result::Result::Ok(Self { #(#fields,)* })
+            }
+        }
+    })
+}
+
+fn custom_option(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Some(extension) = option_attr(&input.attrs, "extension")? else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "CustomOption requires #[option(extension = \"...\")]",
+        ));
+    };
+    let extension = extension.value();
+    let extension = format!(
+        ".{}",
+        extension
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .trim_start_matches('.')
+    );
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::catalyze::CustomOption for #ident #ty_generics #where_clause {
+            const EXTENSION: &'static str = #extension;
+        }
+    })
+}
+
+/// Returns the value of `key` in the `#[option(...)]` attributes, if set.
+fn option_attr(attrs: &[Attribute], key: &str) -> syn::Result<Option<LitStr>> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("option")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) {
+                value = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(format!("expected `{key}`")))
+            }
+        })?;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_from_message() {
+        let input = parse_quote! {
+            struct Rules {
+                r#const: Option<u64>,
+                #[option(name = "max_len")]
+                max: u64,
+            }
+        };
+        let expected = quote! {
+            impl ::catalyze::FromMessage for Rules {
+                #[allow(unused_variables)]
+                fn from_message(
+                    message: &dyn ::catalyze::protobuf::MessageDyn,
+                ) -> ::std::result::Result<Self, ::catalyze::Error> {
+                    let descriptor = message.descriptor_dyn();
+                    let field = |name: &str| {
+                        descriptor.field_by_name(name).ok_or_else(|| {
+                            ::catalyze::Error::OptionFieldNotFound {
+                                message: descriptor.full_name().to_string(),
+                                field: name.to_string(),
+                            }
+                        })
+                    };
+                    ::std::result::Result::Ok(Self {
+                        r#const: ::catalyze::FromValue::from_field(message, &field("const")?)?,
+                        max: ::catalyze::FromValue::from_field(message, &field("max_len")?)?,
+                    })
+                }
+            }
+        };
+        assert_eq!(
+            from_message(input).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    fn test_from_message_errors() {
+        let err = |input| from_message(input).unwrap_err().to_string();
+        assert_eq!(
+            err(parse_quote! { enum Rules { A } }),
+            "FromMessage can only be derived for structs"
+        );
+        assert_eq!(
+            err(parse_quote! { struct Rules(u64); }),
+            "FromMessage can only be derived for structs with named fields"
+        );
+        assert_eq!(
+            err(parse_quote! { struct Rules { #[option(extension = "a.b")] a: u64 } }),
+            "expected `name`"
+        );
+    }
+
+    #[test]
+    fn test_custom_option() {
+        let expected = quote! {
+            impl<T> ::catalyze::CustomOption for Rules<T> {
+                const EXTENSION: &'static str = ".validate.rules";
+            }
+        };
+        for extension in ["validate.rules", ".validate.rules", "(validate.rules)"] {
+            let input = parse_quote! {
+                #[option(extension = #extension)]
+                struct Rules<T> { t: T }
+            };
+            assert_eq!(
+                custom_option(input).unwrap().to_string(),
+                expected.to_string()
+            );
+        }
+        let err = custom_option(parse_quote! { struct Rules {} }).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CustomOption requires #[option(extension = \"...\")]"
+        );
+    }
 }
@@ -1,6 +1,15 @@
-use std::{fs::Permissions, path::PathBuf};
+use std::{
+    env, fmt,
+    fs::{self, OpenOptions, Permissions},
+    io::{self, Write},
+    mem,
+    path::{Component, Path, PathBuf},
+};
 
 use proc_macro2::TokenStream;
+use protobuf::plugin::{code_generator_response, CodeGeneratorResponse};
+
+use crate::error::Error;
 
 pub enum Op {
     /// Creates a new file. If `overwrite` is `true` , any previous file content
@@ -32,3 +41,442 @@ pub struct Artifact {
     pub op: Op,
     pub tags: Vec<String>,
 }
+
+/// A problem with an [`Artifact`] which did not prevent it from being written,
+/// such as a `TokenStream` which could not be formatted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// An [`Artifact`] with a `TokenStream` rendered to a `String`. Unlike
+/// `Artifact`, it can be sent across threads.
+#[cfg(feature = "parallel")]
+pub(crate) struct Rendered {
+    path: PathBuf,
+    content: RenderedContent,
+    op: Op,
+    tags: Vec<String>,
+}
+
+#[cfg(feature = "parallel")]
+enum RenderedContent {
+    String(String),
+    Custom(Option<Permissions>, Vec<u8>),
+}
+
+#[cfg(feature = "parallel")]
+impl From<Rendered> for Artifact {
+    fn from(rendered: Rendered) -> Self {
+        Artifact {
+            path: rendered.path,
+            content: match rendered.content {
+                RenderedContent::String(s) => Content::String(s),
+                RenderedContent::Custom(permissions, bytes) => Content::Custom(permissions, bytes),
+            },
+            op: rendered.op,
+            tags: rendered.tags,
+        }
+    }
+}
+
+impl Content {
+    /// Consumes the `Content`, returning it as a `String`. A `TokenStream`
+    /// which has not been [formatted](Artifact::format) is written as is.
+    pub(crate) fn into_string(self) -> String {
+        match self {
+            Content::TokenStream(ts) => ts.to_string(),
+            Content::String(s) => s,
+            Content::Custom(_, bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        }
+    }
+
+    /// Consumes the `Content`, returning it as bytes.
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Content::Custom(_, bytes) => bytes,
+            content => content.into_string().into_bytes(),
+        }
+    }
+}
+
+impl Artifact {
+    /// Replaces [`Content::TokenStream`] content with the pretty-printed
+    /// `String`.
+    ///
+    /// With the `format` feature, the stream is parsed as a [`syn::File`] and
+    /// printed with `prettyplease`. If parsing fails, the stream is kept
+    /// unformatted and a [`Warning`] is returned. Without it, the stream is
+    /// never formatted.
+    pub(crate) fn format(&mut self) -> Option<Warning> {
+        match mem::replace(&mut self.content, Content::String(String::new())) {
+            Content::TokenStream(ts) => {
+                let (formatted, warning) = format_token_stream(ts, &self.path);
+                self.content = Content::String(formatted);
+                warning
+            }
+            content => {
+                self.content = content;
+                None
+            }
+        }
+    }
+
+    /// [Formats](Self::format) the `Artifact` so that it can be sent across
+    /// threads.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn render(mut self) -> (Rendered, Option<Warning>) {
+        let warning = self.format();
+        let content = match self.content {
+            Content::Custom(permissions, bytes) => RenderedContent::Custom(permissions, bytes),
+            content => RenderedContent::String(content.into_string()),
+        };
+        let rendered = Rendered {
+            path: self.path,
+            content,
+            op: self.op,
+            tags: self.tags,
+        };
+        (rendered, warning)
+    }
+
+    /// Adds the `Artifact` to `response` according to its [`Op`].
+    ///
+    /// - `Create` replaces the content of a file previously added to the
+    ///   response only if `overwrite` is `true`; otherwise the `Artifact` is
+    ///   skipped.
+    /// - `Append` extends the content of a file previously added to the
+    ///   response, or adds a new file if there is none.
+    /// - `Inject` adds a file with the `insertion_point` set, leaving the
+    ///   insertion to protoc.
+    pub(crate) fn add_to_response(self, response: &mut CodeGeneratorResponse) {
+        let name = self.path.to_string_lossy().into_owned();
+        let content = self.content.into_string();
+        let existing = response
+            .file
+            .iter_mut()
+            .find(|f| f.name() == name && f.insertion_point.is_none());
+        match (self.op, existing) {
+            (Op::Create { overwrite: true }, Some(file)) => file.set_content(content),
+            (Op::Create { overwrite: false }, Some(_)) => {}
+            (Op::Append, Some(file)) => file.mut_content().push_str(&content),
+            (Op::Create { .. } | Op::Append, None) => {
+                let mut file = code_generator_response::File::new();
+                file.set_name(name);
+                file.set_content(content);
+                response.file.push(file);
+            }
+            (Op::Inject(insertion_point), _) => {
+                let mut file = code_generator_response::File::new();
+                file.set_name(name);
+                file.set_insertion_point(insertion_point);
+                file.set_content(content);
+                response.file.push(file);
+            }
+        }
+    }
+
+    /// Returns `true` if the `Artifact` has [`Content::Custom`] content.
+    pub(crate) fn is_custom(&self) -> bool {
+        matches!(self.content, Content::Custom(..))
+    }
+
+    /// Returns an error unless the `Artifact`, resolved against `base`, is
+    /// located under one of the allowed `roots`.
+    ///
+    /// The `Artifact` need not exist yet: symlinks are followed in the part of
+    /// its path which exists, and the rest is resolved lexically, so neither
+    /// `..` components nor symlinks can escape a root.
+    pub(crate) fn check_allowed(&self, base: &Path, roots: &[PathBuf]) -> Result<(), Error> {
+        let path = base.join(&self.path);
+        let resolved = resolve(&path)?;
+        for root in roots {
+            if resolved.starts_with(resolve(root)?) {
+                return Ok(());
+            }
+        }
+        Err(Error::CustomArtifactNotAllowed { path })
+    }
+
+    /// Returns an error if the path of the `Artifact` is absolute or, once
+    /// resolved against `output_path` as by [`check_allowed`](Self::check_allowed),
+    /// is not located under it.
+    pub(crate) fn check_relative(&self, output_path: &Path) -> Result<(), Error> {
+        let outside = || Error::ArtifactOutsideOutputPath {
+            path: self.path.clone(),
+        };
+        let mut depth = 0usize;
+        for component in self.path.components() {
+            depth = match component {
+                Component::Normal(_) => depth + 1,
+                Component::CurDir => depth,
+                Component::ParentDir if depth > 0 => depth - 1,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(outside())
+                }
+            };
+        }
+        if !resolve(&output_path.join(&self.path))?.starts_with(resolve(output_path)?) {
+            return Err(outside());
+        }
+        Ok(())
+    }
+
+    /// Writes the `Artifact` to disk, relative to `output_path`, according to
+    /// its [`Op`].
+    ///
+    /// Permissions of [`Content::Custom`] artifacts are applied once written.
+    pub(crate) fn write(self, output_path: &Path) -> Result<(), Error> {
+        let path = output_path.join(&self.path);
+        let permissions = match &self.content {
+            Content::Custom(permissions, _) => permissions.clone(),
+            _ => None,
+        };
+        let content = self.content.into_bytes();
+        let result = match self.op {
+            Op::Create { overwrite } => {
+                if !overwrite && path.exists() {
+                    return Ok(());
+                }
+                create_parent_dir(&path).and_then(|_| fs::write(&path, content))
+            }
+            Op::Append => create_parent_dir(&path).and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)?
+                    .write_all(&content)
+            }),
+            Op::Inject(insertion_point) => {
+                let existing =
+                    fs::read_to_string(&path).map_err(|source| Error::WriteArtifact {
+                        path: path.clone(),
+                        source,
+                    })?;
+                let content = String::from_utf8_lossy(&content);
+                let injected = inject(&existing, &insertion_point, &content).ok_or_else(|| {
+                    Error::InsertionPointNotFound {
+                        path: path.clone(),
+                        insertion_point,
+                    }
+                })?;
+                fs::write(&path, injected)
+            }
+        };
+        result
+            .and_then(|_| match permissions {
+                Some(permissions) => fs::set_permissions(&path, permissions),
+                None => Ok(()),
+            })
+            .map_err(|source| Error::WriteArtifact { path, source })
+    }
+}
+
+#[cfg(feature = "format")]
+fn format_token_stream(ts: TokenStream, path: &Path) -> (String, Option<Warning>) {
+    match syn::parse2::<syn::File>(ts.clone()) {
+        Ok(file) => (prettyplease::unparse(&file), None),
+        Err(err) => {
+            let warning = Warning {
+                path: path.to_path_buf(),
+                message: format!("unable to format; writing unformatted output: {err}"),
+            };
+            (ts.to_string(), Some(warning))
+        }
+    }
+}
+
+#[cfg(not(feature = "format"))]
+fn format_token_stream(ts: TokenStream, _path: &Path) -> (String, Option<Warning>) {
+    (ts.to_string(), None)
+}
+
+/// Resolves `path` to an absolute path, against the current directory.
+///
+/// Symlinks are followed in the longest prefix of `path` which exists; the
+/// components which follow it are resolved lexically.
+fn resolve(path: &Path) -> Result<PathBuf, Error> {
+    let io_error = |source| Error::WriteArtifact {
+        path: path.to_path_buf(),
+        source,
+    };
+    let path = env::current_dir().map_err(io_error)?.join(path);
+    let components: Vec<_> = path.components().collect();
+    let mut existing = components.len();
+    while existing > 0
+        && fs::symlink_metadata(components[..existing].iter().collect::<PathBuf>()).is_err()
+    {
+        existing -= 1;
+    }
+    let prefix: PathBuf = components[..existing].iter().collect();
+    let rest: PathBuf = components[existing..].iter().collect();
+    Ok(normalize(
+        &prefix.canonicalize().map_err(io_error)?.join(rest),
+    ))
+}
+
+/// Lexically resolves `.` and `..` components of `path`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn create_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+/// Inserts `content` immediately above the line containing
+/// `@@protoc_insertion_point(insertion_point)`, indenting each inserted line
+/// to match the marker, as protoc does.
+///
+/// Returns `None` if the marker is not found.
+fn inject(existing: &str, insertion_point: &str, content: &str) -> Option<String> {
+    let marker = format!("@@protoc_insertion_point({insertion_point})");
+    let mut offset = 0;
+    let line = existing.split_inclusive('\n').find(|line| {
+        let found = line.contains(&marker);
+        if !found {
+            offset += line.len();
+        }
+        found
+    })?;
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let mut result = String::with_capacity(existing.len() + content.len());
+    result.push_str(&existing[..offset]);
+    for line in content.split_inclusive('\n') {
+        if line != "\n" {
+            result.push_str(indent);
+        }
+        result.push_str(line);
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push_str(&existing[offset..]);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject() {
+        let existing = "mod foo {\n    // @@protoc_insertion_point(foo)\n}\n";
+        let injected = inject(existing, "foo", "struct Foo;\n\nstruct Bar;").unwrap();
+        assert_eq!(
+            injected,
+            "mod foo {\n    struct Foo;\n\n    struct Bar;\n    // @@protoc_insertion_point(foo)\n}\n"
+        );
+        assert!(inject(existing, "bar", "struct Bar;").is_none());
+    }
+
+    #[cfg(feature = "format")]
+    #[test]
+    fn test_format_token_stream() {
+        let ts: TokenStream = "pub struct Foo { bar : u32 }".parse().unwrap();
+        assert_eq!(
+            format_token_stream(ts, Path::new("foo.rs")),
+            ("pub struct Foo {\n    bar: u32,\n}\n".to_string(), None)
+        );
+        let ts: TokenStream = "pub struct".parse().unwrap();
+        let (unformatted, warning) = format_token_stream(ts, Path::new("foo.rs"));
+        assert_eq!(unformatted, "pub struct");
+        let warning = warning.unwrap();
+        assert_eq!(warning.path, Path::new("foo.rs"));
+        assert!(warning.to_string().starts_with("foo.rs: unable to format"));
+    }
+
+    fn artifact(path: &str) -> Artifact {
+        Artifact {
+            path: path.into(),
+            content: Content::Custom(None, Vec::new()),
+            op: Op::Create { overwrite: true },
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_relative() {
+        let base = Path::new("out");
+        for path in [
+            "foo.rs",
+            "./foo/bar.rs",
+            "foo/../bar.rs",
+            "foo/./../foo/bar.rs",
+        ] {
+            assert!(artifact(path).check_relative(base).is_ok(), "{path}");
+        }
+        for path in ["/foo.rs", "../foo.rs", "foo/../../bar.rs", "./.."] {
+            assert!(
+                matches!(
+                    artifact(path).check_relative(base),
+                    Err(Error::ArtifactOutsideOutputPath { .. })
+                ),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_allowed() {
+        let base = Path::new("out");
+        let roots = [PathBuf::from("out/bin"), PathBuf::from("/etc/catalyze")];
+        for path in ["bin/run.sh", "bin/../bin/run.sh", "/etc/catalyze/config"] {
+            assert!(artifact(path).check_allowed(base, &roots).is_ok(), "{path}");
+        }
+        for path in ["run.sh", "bin/../run.sh", "../out/run.sh", "/etc/passwd"] {
+            assert!(
+                matches!(
+                    artifact(path).check_allowed(base, &roots),
+                    Err(Error::CustomArtifactNotAllowed { .. })
+                ),
+                "{path}"
+            );
+        }
+        assert!(artifact("bin/run.sh").check_allowed(base, &[]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_symlinks() {
+        let base = env::temp_dir().join("catalyze-test-check-symlinks");
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("out/bin")).unwrap();
+        std::os::unix::fs::symlink(&base, base.join("out/escape")).unwrap();
+        std::os::unix::fs::symlink(&base, base.join("out/bin/escape")).unwrap();
+        std::os::unix::fs::symlink(base.join("out/bin"), base.join("out/link")).unwrap();
+        let out = base.join("out");
+        let roots = [out.join("bin")];
+
+        assert!(artifact("link/run.sh").check_relative(&out).is_ok());
+        assert!(artifact("link/run.sh").check_allowed(&out, &roots).is_ok());
+        assert!(matches!(
+            artifact("escape/run.sh").check_relative(&out),
+            Err(Error::ArtifactOutsideOutputPath { .. })
+        ));
+        assert!(matches!(
+            artifact("bin/escape/run.sh").check_allowed(&out, &roots),
+            Err(Error::CustomArtifactNotAllowed { .. })
+        ));
+    }
+}
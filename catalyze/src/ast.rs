@@ -1,18 +1,16 @@
-use protobuf::descriptor::field_descriptor_proto::Type;
-use protobuf::reflect::FileDescriptor;
+use protobuf::reflect::{FileDescriptor, ReflectValueBox};
 
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::path::PathBuf;
-use std::rc::Rc;
-use std::slice;
+use std::path::{Path, PathBuf};
 
+use crate::custom_option;
 use crate::error::Error;
 use crate::extension::Extensions;
-use crate::field::Field;
+use crate::field::{Field, Type};
 use crate::file::File;
 use crate::iter::Iter;
+use crate::message::Message;
 use crate::method::Method;
 use crate::method::MethodIo;
 use crate::node::AllNodes;
@@ -20,6 +18,10 @@ use crate::node::Container;
 use crate::node::Kind;
 use crate::node::Node;
 use crate::package::Package;
+use crate::sync::{Rc, RefCell};
+use crate::util::topo;
+use crate::Input;
+use crate::Version;
 
 /// Ast encapsulates the entirety of the input CodeGeneratorRequest from protoc,
 /// parsed to build the Node graph used by catalyze.
@@ -61,29 +63,213 @@ impl Ast {
         self.0.node(key)
     }
 
+    /// Resolves a possibly partially qualified `name` (e.g. `Inner`,
+    /// `Outer.Inner`, `pkg.Type`) referenced from within `scope`, following the
+    /// same rules as protoc.
+    ///
+    /// Names starting with `.` are fully qualified. Otherwise, the first
+    /// segment of `name` is searched for from the innermost scope outward. If
+    /// it is found and `name` has more segments, the remainder is resolved
+    /// within what was found, and the search stops there. Messages, files and
+    /// packages are searched within; any other `scope` is searched from its
+    /// parent.
+    ///
+    /// Imports are not taken into account; any node in the `Ast` is visible.
+    pub fn resolve(&self, scope: impl Into<Node>, name: &str) -> Option<Node> {
+        if name.starts_with('.') {
+            return self.lookup(name).and_then(|(node, _)| node);
+        }
+        let scope = scope.into();
+        let mut scope = match scope.kind() {
+            Kind::Package | Kind::File | Kind::Message => scope.fully_qualified_name(),
+            _ => parent_scope(scope.fully_qualified_name()),
+        };
+        let first = name.split('.').next().unwrap_or(name);
+        loop {
+            if let Some((node, is_aggregate)) = self.lookup(&format!("{scope}.{first}")) {
+                if first == name {
+                    if node.is_some() {
+                        return node;
+                    }
+                } else if is_aggregate {
+                    return self
+                        .lookup(&format!("{scope}.{name}"))
+                        .and_then(|(node, _)| node);
+                }
+            }
+            if scope.is_empty() {
+                return None;
+            }
+            scope = parent_scope(scope);
+        }
+    }
+
+    /// Returns the value of the custom option named `name` set on `node`; see
+    /// [`Node::custom_option`].
+    ///
+    /// `name` is the fully qualified name of the option's extension, as it is
+    /// written in a proto file (e.g. `(validate.rules)`) or without the
+    /// parentheses. The leading `.` is optional.
+    pub fn custom_option(
+        &self,
+        node: impl Into<Node>,
+        name: &str,
+    ) -> Result<Option<ReflectValueBox>, Error> {
+        let fqn = custom_option::extension_name(name);
+        match self.node(&fqn) {
+            Some(Node::Extension(extension)) => node.into().custom_option(&extension),
+            Some(other) => Err(Error::invalid_node(Kind::Extension, other)),
+            None => Err(Error::NodeNotFound {
+                fully_qualified_name: fqn,
+            }),
+        }
+    }
+
+    /// Looks up the node with the fully qualified name `fqn`, returning it, if
+    /// any, along with whether it may contain other symbols. Partial package
+    /// names (e.g. `.foo` of `foo.bar`) are aggregates without a node.
+    fn lookup(&self, fqn: &str) -> Option<(Option<Node>, bool)> {
+        if let Some(node) = self.node(fqn) {
+            let is_aggregate = matches!(
+                node.kind(),
+                Kind::Message | Kind::Enum | Kind::Service | Kind::Package
+            );
+            return Some((Some(node), is_aggregate));
+        }
+        let name = fqn.trim_start_matches('.');
+        if let Some(package) = self.package(name) {
+            return Some((Some(Node::Package(package)), true));
+        }
+        let prefix = format!("{name}.");
+        self.packages()
+            .any(|p| p.name().starts_with(&prefix))
+            .then_some((None, true))
+    }
+
+    /// Returns every extension defined in the input.
+    #[inline]
+    pub fn defined_extensions(&self) -> Extensions {
+        self.0.defined_extensions.clone()
+    }
+
     #[inline]
     pub fn all_nodes(&self) -> AllNodes {
         AllNodes::from(self)
     }
+
+    /// Returns every file, ordered such that each file appears after the files
+    /// it imports.
+    pub fn files_in_dependency_order(&self) -> Vec<File> {
+        let files: Vec<File> = self.files().collect();
+        let index: HashMap<&str, usize> = files
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.name(), i))
+            .collect();
+        topo::dependency_order(files.len(), |i| {
+            files[i]
+                .imports()
+                .filter_map(|import| index.get(import.name()).copied())
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .map(|i| files[i].clone())
+        .collect()
+    }
+
+    /// Returns every message grouped into strongly connected components, where
+    /// a message depends on the messages its fields embed. Each component
+    /// appears after the components it depends on.
+    ///
+    /// A component with more than one message is a dependency cycle.
+    pub fn message_components(&self) -> Vec<Vec<Message>> {
+        let messages: Vec<Message> = self.files().flat_map(|f| f.all_messages()).collect();
+        let index: HashMap<&str, usize> = messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.fully_qualified_name(), i))
+            .collect();
+        topo::dependency_order(messages.len(), |i| {
+            messages[i]
+                .fields()
+                .filter_map(|field| field.embed())
+                .filter_map(|embed| index.get(embed.fully_qualified_name()).copied())
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .map(|component| component.into_iter().map(|i| messages[i].clone()).collect())
+        .collect()
+    }
+
+    /// Returns every message, ordered such that each message appears after
+    /// the messages its fields embed.
+    ///
+    /// A message which embeds itself is not considered a cycle. Returns
+    /// [`Error::MessageCycle`] if two or more messages depend on each other;
+    /// use [`message_components`](Self::message_components) to handle cycles.
+    pub fn messages_in_dependency_order(&self) -> Result<Vec<Message>, Error> {
+        let mut messages = Vec::new();
+        for mut component in self.message_components() {
+            if component.len() > 1 {
+                return Err(Error::MessageCycle {
+                    messages: component
+                        .iter()
+                        .map(|m| m.fully_qualified_name().to_string())
+                        .collect(),
+                });
+            }
+            messages.append(&mut component);
+        }
+        Ok(messages)
+    }
+
+    /// The version of protoc which produced the input, if known. This is
+    /// always `None` in standalone mode.
+    #[inline]
+    pub fn protoc_version(&self) -> Option<&Version> {
+        self.0.protoc_version.as_ref()
+    }
 }
 
 impl Ast {
-    // pub fn new(input: &'a Input) -> Result<Self, Error> {
-    //     let ast = AstDetail {
-    //         packages: HashMap::default(),
-    //         files: HashMap::default(),
-    //         file_list: Rc::new(RefCell::new(Vec::new())),
-    //         targets: HashMap::with_capacity(input.targets().len()),
-    //         defined_extensions: Extensions::new(),
-    //         nodes: HashMap::default(),
-    //         package_list: Rc::new(RefCell::new(Vec::new())),
-    //         target_files: Rc::new(RefCell::new(Vec::new())),
-    //         target_list: input.targets().iter().map(|v| v.as_str()).collect(),
-    //     }
-    //     .hydrate_files(input.files().iter())?;
-    //     let ast = Ast(Rc::new(ast));
-    //     Ok(ast)
-    // }
+    /// Builds the `Ast` from the `FileDescriptorProto`s of the given
+    /// [`Input`], marking the files listed in [`Input::targets`] as build
+    /// targets.
+    pub fn new<I: Input>(input: &I) -> Result<Self, Error> {
+        let descriptors = FileDescriptor::new_dynamic_fds(input.files().to_vec(), &[])
+            .map_err(|source| Error::InvalidFileDescriptor { source })?;
+        let ast = AstDetail {
+            packages: HashMap::default(),
+            files: HashMap::default(),
+            file_list: Rc::new(RefCell::new(Vec::new())),
+            targets: HashMap::with_capacity(input.targets().len()),
+            defined_extensions: Extensions::new(),
+            nodes: HashMap::default(),
+            package_list: Rc::new(RefCell::new(Vec::new())),
+            target_files: Rc::new(RefCell::new(Vec::new())),
+            target_list: input.targets().iter().cloned().collect(),
+            protoc_version: input.protoc_version(),
+        }
+        .hydrate_files(descriptors.into_iter())?;
+        let ast = Ast(Rc::new(ast));
+        #[cfg(feature = "sync")]
+        ast.freeze();
+        Ok(ast)
+    }
+
+    /// Freezes every node once hydration is complete, after which reads no
+    /// longer take a lock.
+    #[cfg(feature = "sync")]
+    fn freeze(&self) {
+        for node in self.all_nodes() {
+            node.freeze();
+        }
+        self.0.file_list.freeze();
+        self.0.target_files.freeze();
+        self.0.package_list.freeze();
+        self.0.defined_extensions.freeze();
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -97,13 +283,14 @@ pub(crate) struct AstDetail {
     package_list: Rc<RefCell<Vec<Package>>>,
     defined_extensions: Extensions,
     nodes: HashMap<String, Node>,
+    protoc_version: Option<Version>,
 }
 impl AstDetail {
     pub fn package(&self, name: &str) -> Option<Package> {
         self.packages.get(name).cloned()
     }
     pub fn file(&self, name: &str) -> Option<File> {
-        self.files.get(name).cloned()
+        self.files.get(Path::new(name)).cloned()
     }
 
     pub fn files(&self) -> Iter<File> {
@@ -122,7 +309,7 @@ impl AstDetail {
     fn connect_file_dep(&mut self, file: File, dep: &str) -> Result<(), Error> {
         let dep = self.file(dep).ok_or_else(|| Error::DependencyNotFound {
             dependency: dep.to_string(),
-            dependee: file,
+            dependee: file.clone(),
         })?;
 
         file.add_import(dep.clone());
@@ -132,11 +319,11 @@ impl AstDetail {
 
     fn hydrate_extensions(&self, container: Container) -> Result<(), Error> {
         for ext in container.defined_extensions() {
-            let extendee =
-                self.node(ext.descriptor().extendee())
-                    .ok_or_else(|| Error::ExtendeeNotFound {
-                        extendee: ext.descriptor().extendee().to_string(),
-                    })?;
+            let descriptor = ext.descriptor();
+            let extendee = descriptor.proto().extendee();
+            let extendee = self.node(extendee).ok_or_else(|| Error::ExtendeeNotFound {
+                extendee: extendee.to_string(),
+            })?;
 
             let Node::Message(m) = extendee else {
                 return Err(Error::InvalidNode {
@@ -149,15 +336,19 @@ impl AstDetail {
         Ok(())
     }
 
-    fn connect_file_deps(&mut self, file: File, deps: slice::Iter<String>) -> Result<(), Error> {
+    fn connect_file_deps<'a>(
+        &mut self,
+        file: File,
+        deps: impl Iterator<Item = &'a String>,
+    ) -> Result<(), Error> {
         for d in deps {
             self.connect_file_dep(file.clone(), d)?;
         }
         Ok(())
     }
-    fn load_pkg(&mut self, fd: FileDescriptor) -> Package {
+    fn load_pkg(&mut self, fd: &FileDescriptor) -> Package {
         self.packages
-            .entry(fd.name().to_string())
+            .entry(fd.package().to_string())
             .or_insert_with(|| {
                 let pkg = Package::new(fd.package());
                 self.package_list.borrow_mut().push(pkg.clone());
@@ -165,8 +356,8 @@ impl AstDetail {
             })
             .clone()
     }
-    fn is_build_target(&self, fd: FileDescriptor) -> bool {
-        self.target_list.contains(&fd.name())
+    fn is_build_target(&self, fd: &FileDescriptor) -> bool {
+        self.target_list.contains(fd.name())
     }
 
     fn hydrate_method(&self, method: Method) -> Result<(), Error> {
@@ -178,12 +369,12 @@ impl AstDetail {
                 });
             }
 
-            let node = self
-                .node(io.node_name())
-                .ok_or_else(|| Error::MissingMethod {
+            let Some(node) = self.node(io.node_name()) else {
+                return Err(Error::MissingMethod {
                     fully_qualified_name: method.fully_qualified_name().to_string(),
                     method_io: io,
-                })?;
+                });
+            };
 
             if let Node::Message(msg) = node {
                 match io {
@@ -196,17 +387,16 @@ impl AstDetail {
                 }
                 Ok(())
             } else {
-                return Err(Error::InvalidNode {
+                Err(Error::InvalidNode {
                     expected: Kind::Message,
                     node,
-                });
+                })
             }
         };
 
-        let (i, o) = method.io();
-
-        hydrate(i)?;
-        hydrate(o)?;
+        let io = method.io();
+        hydrate(MethodIo::Input(io.input.to_string()))?;
+        hydrate(MethodIo::Output(io.output.to_string()))?;
         Ok(())
     }
 
@@ -233,10 +423,13 @@ impl AstDetail {
         Ok(())
     }
     fn hydrate_embed_field(&self, idx: usize, field: Field, node: Node) -> Result<(), Error> {
-        let embed = node.try_into_message().map_err(|node| Error::InvalidNode {
-            expected: Kind::Message,
-            node,
-        })?;
+        let embed = node
+            .clone()
+            .try_into_message()
+            .map_err(|node| Error::InvalidNode {
+                expected: Kind::Message,
+                node,
+            })?;
         let msg = field.message();
         field.set_value(node.clone())?;
 
@@ -261,44 +454,51 @@ impl AstDetail {
     fn add_file(&mut self, file: File) {
         self.nodes
             .insert(file.name().to_string(), file.clone().into());
-        self.files.insert(file.name(), file.clone());
+        self.files.insert(file.path(), file.clone());
         self.file_list.borrow_mut().push(file.clone());
         if file.build_target() {
             self.targets.insert(file.name().to_string(), file.clone());
             self.target_files.borrow_mut().push(file.clone());
         }
     }
-    fn hydrate_files(
-        mut self,
-        files: slice::Iter<'_, protobuf::descriptor::FileDescriptorProto>,
-    ) -> Result<Self, Error> {
+    fn hydrate_files(mut self, files: impl Iterator<Item = FileDescriptor>) -> Result<Self, Error> {
         for fd in files {
-            let file = self.init_file(fd.into())?;
+            let name = fd.name().to_string();
+            let file = self.init_file(fd).map_err(|err| err.in_file(&name))?;
             self.add_file(file);
         }
         for file in self.files() {
-            self.hydrate_extensions(file.clone().into())?;
-            for msg in file.all_messages() {
-                self.hydrate_extensions(msg.clone().into())?;
-                for (idx, field) in msg.fields().enumerate() {
-                    self.hydrate_field(idx, field)?;
-                }
-            }
-            for svc in file.services() {
-                for meth in svc.methods() {
-                    self.hydrate_method(meth)?;
-                }
-            }
+            self.hydrate_file(&file)
+                .map_err(|err| err.in_file(file.name()))?;
         }
 
         Ok(self)
     }
 
+    fn hydrate_file(&mut self, file: &File) -> Result<(), Error> {
+        self.hydrate_extensions(file.clone().into())?;
+        for msg in file.all_messages() {
+            self.hydrate_extensions(msg.clone().into())?;
+            for (idx, field) in msg.fields().enumerate() {
+                self.hydrate_field(idx, field)?;
+            }
+            for oneof in msg.oneofs() {
+                oneof.update_imports();
+            }
+        }
+        for svc in file.services() {
+            for meth in svc.methods() {
+                self.hydrate_method(meth)?;
+            }
+        }
+        Ok(())
+    }
+
     fn init_file(&mut self, fd: FileDescriptor) -> Result<File, Error> {
-        let pkg = self.load_pkg(fd);
-        let build_target = self.is_build_target(fd);
-        let file = File::new(build_target, fd.to_owned(), pkg.clone())?;
-        self.connect_file_deps(file.clone(), fd.dependencies())?;
+        let pkg = self.load_pkg(&fd);
+        let build_target = self.is_build_target(&fd);
+        let file = File::new(build_target, fd.clone(), pkg.clone())?;
+        self.connect_file_deps(file.clone(), fd.proto().dependency.iter())?;
         for ext in file.defined_extensions() {
             self.defined_extensions.insert(ext)
         }
@@ -307,11 +507,73 @@ impl AstDetail {
             self.nodes
                 .insert(node.fully_qualified_name().to_string(), node.clone());
             if let Node::Message(msg) = node {
-                for (fqn, map) in msg.maps() {
-                    self.nodes.insert(fqn.to_string(), map.into());
+                for map in msg.maps() {
+                    self.nodes
+                        .insert(map.fully_qualified_name().to_string(), map.into());
                 }
             }
         }
         Ok(file)
     }
 }
+
+/// Returns `fqn` without its last segment.
+fn parent_scope(fqn: &str) -> &str {
+    fqn.rfind('.').map_or("", |i| &fqn[..i])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::kitchen;
+
+    #[test]
+    fn test_resolve() {
+        let ast = kitchen();
+        let resolve = |scope: &str, name: &str| {
+            let scope = ast.node(scope).unwrap();
+            ast.resolve(scope, name)
+                .map(|node| node.fully_qualified_name().to_string())
+        };
+        assert_eq!(
+            resolve(".kitchen.Color", "RGB").unwrap(),
+            ".kitchen.Color.RGB"
+        );
+        assert_eq!(
+            resolve(".kitchen.Kitchen", "Color.CMYK").unwrap(),
+            ".kitchen.Color.CMYK"
+        );
+        assert_eq!(
+            resolve(".kitchen.Color.RGB", "Style").unwrap(),
+            ".kitchen.Style"
+        );
+        assert_eq!(
+            resolve(".kitchen.Color.RGB", "kitchen.Style").unwrap(),
+            ".kitchen.Style"
+        );
+        assert_eq!(
+            resolve(".kitchen.Kitchen", ".kitchen.Style").unwrap(),
+            ".kitchen.Style"
+        );
+        assert!(resolve(".kitchen.Kitchen", "RGB").is_none());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_frozen() {
+        let ast = kitchen();
+        let count = ast.all_nodes().count();
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| s.spawn(|| ast.all_nodes().count()))
+                .collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), count);
+            }
+        });
+        let file = ast.file("kitchen/kitchen.proto").unwrap();
+        let mutated = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            file.set_comments(Default::default())
+        }));
+        assert!(mutated.is_err());
+    }
+}
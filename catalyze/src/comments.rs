@@ -1,8 +1,6 @@
-use std::rc::Rc;
-
 use protobuf::descriptor::source_code_info::Location;
 
-use crate::{file::File, iter::Iter, package::Package};
+use crate::{file::File, iter::Iter, package::Package, sync::Rc};
 
 #[derive(Debug, Default, Clone)]
 pub struct Comments {
@@ -10,11 +8,13 @@ pub struct Comments {
 }
 
 impl Comments {
-    pub fn location(&self) -> Location {
-        self.loc
+    pub fn location(&self) -> &Location {
+        &self.loc
     }
     pub fn is_empty(&self) -> bool {
-        !self.loc.has_comments()
+        !self.loc.has_leading_comments()
+            && !self.loc.has_trailing_comments()
+            && self.loc.leading_detached_comments.is_empty()
     }
     /// Returns any comment immediately preceding the node, without any
     /// whitespace between it and the comment.
@@ -30,7 +30,7 @@ impl Comments {
     /// Returns each comment block or line above the
     /// entity but separated by whitespace.a
     pub fn leading_detached(&self) -> std::slice::Iter<'_, String> {
-        self.loc.leading_detached_comments()
+        self.loc.leading_detached_comments.iter()
     }
 }
 #[derive(Debug, Clone)]
@@ -49,15 +49,14 @@ impl Iterator for PackageComments {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.files
-            .next()
-            .filter(|file| !file.package_comments().is_empty())
+            .find(|file| !file.package_comments().is_empty())
             .map(|file| (file.clone(), file.package_comments()))
     }
 }
 
 impl From<Location> for Comments {
     fn from(loc: Location) -> Self {
-        Comments { loc }
+        Comments { loc: Rc::new(loc) }
     }
 }
 
@@ -0,0 +1,424 @@
+//! Decoding of custom options.
+//!
+//! protoc resolves custom options, which are extensions of the
+//! `google.protobuf.*Options` messages, and encodes them into the options
+//! message. As the extensions are unknown to `protobuf`, their values are kept
+//! as unknown fields, from which they are decoded here using the
+//! [`Extension`]'s type.
+//!
+//! Decoded values can be mapped onto Rust types with [`FromValue`]. Structs
+//! which mirror an option message implement [`FromMessage`], and those which
+//! are set by an extension [`CustomOption`]; both can be derived with the
+//! `derive` feature.
+
+use protobuf::{
+    descriptor::field_descriptor_proto::Type,
+    reflect::{FieldDescriptor, ReflectValueBox, ReflectValueRef, RuntimeFieldType, RuntimeType},
+    CodedInputStream, MessageDyn, UnknownFields, UnknownValueRef,
+};
+
+use crate::{error::Error, extension::Extension};
+
+/// A struct which mirrors an option message, such as `validate.FieldRules`.
+///
+/// Each field of the struct is read from the message field of the same name
+/// with [`FromValue::from_field`].
+///
+/// # Example
+/// ```ignore
+/// use catalyze::{CustomOption, FromMessage};
+///
+/// #[derive(FromMessage, CustomOption)]
+/// #[option(extension = "google.api.http")]
+/// struct HttpRule {
+///     get: Option<String>,
+///     post: Option<String>,
+///     body: String,
+///     #[option(name = "additional_bindings")]
+///     bindings: Vec<HttpRule>,
+/// }
+///
+/// let rule = method.option::<HttpRule>()?;
+/// ```
+pub trait FromMessage: Sized {
+    fn from_message(message: &dyn MessageDyn) -> Result<Self, Error>;
+}
+
+/// An [option message](FromMessage) which is set by the extension
+/// [`EXTENSION`](Self::EXTENSION), read with `option::<T>()` on a node.
+pub trait CustomOption: FromMessage {
+    /// The fully qualified name of the extension, e.g. `.validate.rules`.
+    const EXTENSION: &'static str;
+}
+
+/// Converts a decoded option value, or a field of an option message, into
+/// `Self`.
+pub trait FromValue: Sized {
+    fn from_value(value: ReflectValueBox) -> Result<Self, Error>;
+
+    /// Reads `field` of `message`. By default, this is the value of a
+    /// singular field, or its default if it is not set.
+    fn from_field(message: &dyn MessageDyn, field: &FieldDescriptor) -> Result<Self, Error> {
+        Self::from_value(field.get_singular_field_or_default(message).to_box())
+    }
+}
+
+macro_rules! from_value {
+    ($($ty:ty => $expected:literal: $variant:ident),* $(,)?) => {$(
+        impl FromValue for $ty {
+            fn from_value(value: ReflectValueBox) -> Result<Self, Error> {
+                match value {
+                    ReflectValueBox::$variant(v) => Ok(v),
+                    value => Err(Error::option_value_type($expected, &value)),
+                }
+            }
+        }
+    )*};
+}
+
+from_value! {
+    bool => "bool": Bool,
+    i64 => "int64": I64,
+    u32 => "uint32": U32,
+    u64 => "uint64": U64,
+    f32 => "float": F32,
+    f64 => "double": F64,
+    String => "string": String,
+    Vec<u8> => "bytes": Bytes,
+}
+
+/// Enum values are converted into their number.
+impl FromValue for i32 {
+    fn from_value(value: ReflectValueBox) -> Result<Self, Error> {
+        match value {
+            ReflectValueBox::I32(v) | ReflectValueBox::Enum(_, v) => Ok(v),
+            value => Err(Error::option_value_type("int32 or enum", &value)),
+        }
+    }
+}
+
+impl<T: FromMessage> FromValue for T {
+    fn from_value(value: ReflectValueBox) -> Result<Self, Error> {
+        match value {
+            ReflectValueBox::Message(message) => T::from_message(&*message),
+            value => Err(Error::option_value_type("message", &value)),
+        }
+    }
+}
+
+/// `None` if the field is not set.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: ReflectValueBox) -> Result<Self, Error> {
+        T::from_value(value).map(Some)
+    }
+    fn from_field(message: &dyn MessageDyn, field: &FieldDescriptor) -> Result<Self, Error> {
+        field
+            .get_singular(message)
+            .map(|value| T::from_value(value.to_box()))
+            .transpose()
+    }
+}
+
+/// Reads each value of a repeated field.
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: ReflectValueBox) -> Result<Self, Error> {
+        T::from_value(value).map(|value| vec![value])
+    }
+    fn from_field(message: &dyn MessageDyn, field: &FieldDescriptor) -> Result<Self, Error> {
+        if !field.is_repeated() {
+            return Option::<T>::from_field(message, field).map(Vec::from_iter);
+        }
+        field
+            .get_repeated(message)
+            .into_iter()
+            .map(|value| T::from_value(value.to_box()))
+            .collect()
+    }
+}
+
+/// Returns the fully qualified name of the extension of the custom option
+/// `name`, which may be written as in a proto file (e.g. `(validate.rules)`),
+/// without the parentheses, and with or without the leading `.`.
+pub(crate) fn extension_name(name: &str) -> String {
+    let name = name.trim_start_matches('(').trim_end_matches(')');
+    if name.starts_with('.') {
+        name.to_string()
+    } else {
+        format!(".{name}")
+    }
+}
+
+/// Decodes each value of `extension` found in `unknown_fields`.
+///
+/// The records of a singular message option are merged into a single value;
+/// for other singular options, the last value is the one in effect.
+pub(crate) fn decode(
+    extension: &Extension,
+    unknown_fields: &UnknownFields,
+) -> Result<Vec<ReflectValueBox>, Error> {
+    let descriptor = extension.descriptor();
+    let proto = descriptor.proto();
+    let number = proto.number() as u32;
+    let (runtime_type, is_repeated) = match descriptor.runtime_field_type() {
+        RuntimeFieldType::Singular(t) => (t, false),
+        RuntimeFieldType::Repeated(t) => (t, true),
+        RuntimeFieldType::Map(..) => return Ok(Vec::new()),
+    };
+    let records = unknown_fields
+        .iter()
+        .filter(|(n, _)| *n == number)
+        .map(|(_, value)| value);
+    let option = || extension.fully_qualified_name().to_string();
+
+    if let RuntimeType::Message(message) = &runtime_type {
+        let mut messages: Vec<Box<dyn MessageDyn>> = Vec::new();
+        for record in records {
+            let UnknownValueRef::LengthDelimited(bytes) = record else {
+                return Err(Error::CustomOptionWireType { option: option() });
+            };
+            let value =
+                message
+                    .parse_from_bytes(bytes)
+                    .map_err(|source| Error::DecodeCustomOption {
+                        option: option(),
+                        source,
+                    })?;
+            match messages.last_mut() {
+                Some(merged) if !is_repeated => merge(&mut **merged, &*value),
+                _ => messages.push(value),
+            }
+        }
+        return Ok(messages.into_iter().map(ReflectValueBox::Message).collect());
+    }
+
+    let mut values = Vec::new();
+    for record in records {
+        match record {
+            UnknownValueRef::Varint(v) => values.push(varint(proto.type_(), &runtime_type, v)),
+            UnknownValueRef::Fixed32(v) => values.push(fixed32(proto.type_(), v)),
+            UnknownValueRef::Fixed64(v) => values.push(fixed64(proto.type_(), v)),
+            UnknownValueRef::LengthDelimited(bytes) => match proto.type_() {
+                Type::TYPE_STRING => values.push(Some(ReflectValueBox::String(
+                    String::from_utf8_lossy(bytes).into_owned(),
+                ))),
+                Type::TYPE_BYTES => values.push(Some(ReflectValueBox::Bytes(bytes.to_vec()))),
+                t => {
+                    let packed = packed(t, &runtime_type, bytes).map_err(|source| {
+                        Error::DecodeCustomOption {
+                            option: option(),
+                            source,
+                        }
+                    })?;
+                    values.extend(packed);
+                }
+            },
+        }
+    }
+    let values = values
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| Error::CustomOptionWireType { option: option() })?;
+    if is_repeated {
+        Ok(values)
+    } else {
+        Ok(values.into_iter().last().into_iter().collect())
+    }
+}
+
+/// Merges the fields set on `from` into `into`, as if both were parsed from a
+/// single buffer.
+///
+/// `protobuf` replaces, rather than merges, a message field of a oneof which
+/// appears more than once, so the records of an option are parsed separately
+/// and merged here instead.
+fn merge(into: &mut dyn MessageDyn, from: &dyn MessageDyn) {
+    for field in from.descriptor_dyn().fields() {
+        match field.runtime_field_type() {
+            RuntimeFieldType::Singular(_) => match field.get_singular(from) {
+                Some(ReflectValueRef::Message(value)) if field.has_field(into) => {
+                    merge(field.mut_message(into), &*value)
+                }
+                Some(value) => field.set_singular_field(into, value.to_box()),
+                None => {}
+            },
+            RuntimeFieldType::Repeated(_) => {
+                let mut repeated = field.mut_repeated(into);
+                for value in &field.get_repeated(from) {
+                    repeated.push(value.to_box());
+                }
+            }
+            RuntimeFieldType::Map(..) => {
+                let mut map = field.mut_map(into);
+                for (key, value) in &field.get_map(from) {
+                    map.insert(key.to_box(), value.to_box());
+                }
+            }
+        }
+    }
+}
+
+fn varint(t: Type, runtime_type: &RuntimeType, v: u64) -> Option<ReflectValueBox> {
+    Some(match t {
+        Type::TYPE_INT32 => ReflectValueBox::I32(v as i32),
+        Type::TYPE_INT64 => ReflectValueBox::I64(v as i64),
+        Type::TYPE_UINT32 => ReflectValueBox::U32(v as u32),
+        Type::TYPE_UINT64 => ReflectValueBox::U64(v),
+        Type::TYPE_SINT32 => ReflectValueBox::I32(((v as u32) >> 1) as i32 ^ -((v & 1) as i32)),
+        Type::TYPE_SINT64 => ReflectValueBox::I64((v >> 1) as i64 ^ -((v & 1) as i64)),
+        Type::TYPE_BOOL => ReflectValueBox::Bool(v != 0),
+        Type::TYPE_ENUM => match runtime_type {
+            RuntimeType::Enum(e) => ReflectValueBox::Enum(e.clone(), v as i32),
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+fn fixed32(t: Type, v: u32) -> Option<ReflectValueBox> {
+    Some(match t {
+        Type::TYPE_FIXED32 => ReflectValueBox::U32(v),
+        Type::TYPE_SFIXED32 => ReflectValueBox::I32(v as i32),
+        Type::TYPE_FLOAT => ReflectValueBox::F32(f32::from_bits(v)),
+        _ => return None,
+    })
+}
+
+fn fixed64(t: Type, v: u64) -> Option<ReflectValueBox> {
+    Some(match t {
+        Type::TYPE_FIXED64 => ReflectValueBox::U64(v),
+        Type::TYPE_SFIXED64 => ReflectValueBox::I64(v as i64),
+        Type::TYPE_DOUBLE => ReflectValueBox::F64(f64::from_bits(v)),
+        _ => return None,
+    })
+}
+
+/// Decodes the values of a packed repeated scalar option.
+fn packed(
+    t: Type,
+    runtime_type: &RuntimeType,
+    bytes: &[u8],
+) -> protobuf::Result<Vec<Option<ReflectValueBox>>> {
+    let mut input = CodedInputStream::from_bytes(bytes);
+    let mut values = Vec::new();
+    while !input.eof()? {
+        let value = match t {
+            Type::TYPE_FIXED32 | Type::TYPE_SFIXED32 | Type::TYPE_FLOAT => {
+                fixed32(t, input.read_fixed32()?)
+            }
+            Type::TYPE_FIXED64 | Type::TYPE_SFIXED64 | Type::TYPE_DOUBLE => {
+                fixed64(t, input.read_fixed64()?)
+            }
+            Type::TYPE_STRING | Type::TYPE_BYTES | Type::TYPE_MESSAGE | Type::TYPE_GROUP => None,
+            _ => varint(t, runtime_type, input.read_raw_varint64()?),
+        };
+        if value.is_none() {
+            break;
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        let t = RuntimeType::I32;
+        assert_eq!(
+            varint(Type::TYPE_SINT32, &t, 3),
+            Some(ReflectValueBox::I32(-2))
+        );
+        assert_eq!(
+            varint(Type::TYPE_SINT64, &t, 4),
+            Some(ReflectValueBox::I64(2))
+        );
+        assert_eq!(
+            varint(Type::TYPE_INT32, &t, u64::MAX),
+            Some(ReflectValueBox::I32(-1))
+        );
+        assert_eq!(varint(Type::TYPE_FLOAT, &t, 1), None);
+    }
+
+    #[test]
+    fn test_packed() {
+        let t = RuntimeType::U32;
+        let values = packed(Type::TYPE_UINT32, &t, &[1, 0x96, 0x01]).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Some(ReflectValueBox::U32(1)),
+                Some(ReflectValueBox::U32(150))
+            ]
+        );
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn test_decode() {
+        use crate::{test_util::contact, Node};
+
+        let ast = contact();
+        let rules = |name: &str| {
+            let node = ast.node(name).unwrap();
+            let Some(ReflectValueBox::Message(rules)) =
+                ast.custom_option(node, "(validate.rules)").unwrap()
+            else {
+                panic!("{name} has no rules");
+            };
+            rules
+        };
+        let get = |message: &dyn MessageDyn, path: &[&str]| {
+            let (last, path) = path.split_last().unwrap();
+            let mut message = message.clone_box();
+            for name in path {
+                let field = message.descriptor_dyn().field_by_name(name).unwrap();
+                let value = field.get_singular(&*message).unwrap().to_box();
+                let ReflectValueBox::Message(m) = value else {
+                    panic!("{name} is not a message");
+                };
+                message = m;
+            }
+            let field = message.descriptor_dyn().field_by_name(last).unwrap();
+            field.get_singular(&*message).map(|v| v.to_box())
+        };
+
+        // A scalar
+        let method = ast.node(".optionspb.Contact.method").unwrap();
+        assert_eq!(
+            ast.custom_option(method, "validate.required").unwrap(),
+            Some(ReflectValueBox::Bool(true))
+        );
+
+        // A message
+        assert_eq!(
+            get(&*rules(".optionspb.Contact.id"), &["uint64", "gt"]),
+            Some(ReflectValueBox::U64(999))
+        );
+
+        // An enum, within a message
+        let regex = get(
+            &*rules(".optionspb.Contact.phone"),
+            &["string", "well_known_regex"],
+        );
+        assert!(matches!(regex, Some(ReflectValueBox::Enum(e, 2)) if e.name() == "KnownRegex"));
+
+        // A singular message set by multiple records is merged
+        let Some(Node::Field(email)) = ast.node(".optionspb.Contact.email") else {
+            panic!("email is not a field");
+        };
+        let options = email.descriptor().proto().options.clone().unwrap();
+        let records = options.special_fields.unknown_fields().iter();
+        assert_eq!(records.filter(|(n, _)| *n == 1071).count(), 2);
+        let email = rules(".optionspb.Contact.email");
+        assert_eq!(
+            get(&*email, &["string", "email"]),
+            Some(ReflectValueBox::Bool(true))
+        );
+        assert_eq!(
+            get(&*email, &["string", "max_bytes"]),
+            Some(ReflectValueBox::U64(254))
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{file::File, node::Node, span::Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A warning or error reported against a [`Node`].
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    severity: Severity,
+    node: Node,
+    message: String,
+    notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, node: impl Into<Node>, message: impl fmt::Display) -> Self {
+        Self {
+            severity,
+            node: node.into(),
+            message: message.to_string(),
+            notes: Vec::new(),
+        }
+    }
+    pub fn warning(node: impl Into<Node>, message: impl fmt::Display) -> Self {
+        Self::new(Severity::Warning, node, message)
+    }
+    pub fn error(node: impl Into<Node>, message: impl fmt::Display) -> Self {
+        Self::new(Severity::Error, node, message)
+    }
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+    /// Adds a note, rendered beneath the source snippet.
+    pub fn note(mut self, note: impl fmt::Display) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+    /// The location of the node in its proto file, if known.
+    pub fn span(&self) -> Option<Span> {
+        self.node.span()
+    }
+
+    /// Renders the diagnostic in the style of rustc, underlining the node's
+    /// definition in the text of its proto file if it is present in
+    /// `sources`:
+    ///
+    /// ```text
+    /// error: field name must be snake_case
+    ///   --> kitchen/kitchen.proto:20:5
+    ///    |
+    /// 20 |     string brandName = 1;
+    ///    |     ^^^^^^^^^^^^^^^^^^^^^
+    ///    |
+    ///    = note: in .kitchen.Kitchen.brandName
+    /// ```
+    pub fn render(&self, sources: &Sources) -> String {
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+        let span = self.span();
+        let gutter = span
+            .as_ref()
+            .map_or(0, |span| (span.end_line() + 1).to_string().len());
+        let pad = " ".repeat(gutter);
+        match &span {
+            Some(span) => out.push_str(&format!("{pad}--> {span}\n")),
+            None => {
+                if let Some(file) = self.node.file() {
+                    out.push_str(&format!("{pad}--> {}\n", file.name()));
+                }
+            }
+        }
+        if let Some(span) = &span {
+            if let Some(text) = sources.get(span.file_path()) {
+                out.push_str(&format!("{pad} |\n"));
+                render_snippet(&mut out, text, span, gutter);
+            }
+        }
+        let mut notes = self.notes.iter().map(String::as_str).collect::<Vec<_>>();
+        let element = format!("in {}", self.node.fully_qualified_name());
+        notes.push(&element);
+        if span.is_some() {
+            out.push_str(&format!("{pad} |\n"));
+        }
+        for note in notes {
+            out.push_str(&format!("{pad} = note: {note}\n"));
+        }
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+        if let Some(file) = self.node.file() {
+            write!(f, "{}: ", file.name())?;
+        }
+        write!(f, "{}: {}", self.node.fully_qualified_name(), self.message)
+    }
+}
+
+/// `Diagnostics` collects the warnings and errors of a pass over the
+/// [`Ast`](crate::Ast) so that every problem can be reported at once, rather
+/// than stopping at the first.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+    pub fn warning(&mut self, node: impl Into<Node>, message: impl fmt::Display) {
+        self.push(Diagnostic::warning(node, message));
+    }
+    pub fn error(&mut self, node: impl Into<Node>, message: impl fmt::Display) {
+        self.push(Diagnostic::error(node, message));
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| d.is_error())
+    }
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| !d.is_error())
+    }
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+    /// Renders each diagnostic with [`Diagnostic::render`], followed by a
+    /// summary of the number of errors and warnings.
+    pub fn render(&self, sources: &Sources) -> String {
+        let mut out = String::new();
+        for diagnostic in &self.diagnostics {
+            out.push_str(&diagnostic.render(sources));
+            out.push('\n');
+        }
+        let errors = self.errors().count();
+        let warnings = self.warnings().count();
+        if errors > 0 || warnings > 0 {
+            out.push_str(&format!(
+                "{} error{}, {} warning{} emitted\n",
+                errors,
+                if errors == 1 { "" } else { "s" },
+                warnings,
+                if warnings == 1 { "" } else { "s" },
+            ));
+        }
+        out
+    }
+}
+
+impl Extend<Diagnostic> for Diagnostics {
+    fn extend<T: IntoIterator<Item = Diagnostic>>(&mut self, iter: T) {
+        self.diagnostics.extend(iter);
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.iter()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// The text of proto files, keyed by their path relative to the import root,
+/// used to render source snippets in diagnostics.
+///
+/// protoc does not pass source text to plugins, so files must either be
+/// inserted directly or read from the directories they were compiled from.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    files: HashMap<PathBuf, String>,
+}
+
+impl Sources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Reads each of `files` which can be found beneath one of `roots`,
+    /// searching the roots in order. Files which cannot be found or read are
+    /// skipped.
+    pub fn read<I, R>(files: impl IntoIterator<Item = File>, roots: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<Path>,
+    {
+        let roots: Vec<R> = roots.into_iter().collect();
+        let mut sources = Self::new();
+        for file in files {
+            let text = roots
+                .iter()
+                .find_map(|root| fs::read_to_string(root.as_ref().join(file.file_path())).ok());
+            if let Some(text) = text {
+                sources.insert(file.file_path(), text);
+            }
+        }
+        sources
+    }
+    pub fn insert(&mut self, path: impl AsRef<Path>, text: impl Into<String>) {
+        self.files.insert(path.as_ref().to_path_buf(), text.into());
+    }
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.files.get(path.as_ref()).map(String::as_str)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Writes the lines of `text` covered by `span`, each followed by a line
+/// underlining the spanned columns. Only the first and last lines of a
+/// multiline span are written.
+fn render_snippet(out: &mut String, text: &str, span: &Span, gutter: usize) {
+    let pad = " ".repeat(gutter);
+    let lines: Vec<&str> = text.lines().collect();
+    let last = span.end_line().min(lines.len().saturating_sub(1));
+    for (n, line) in lines
+        .iter()
+        .enumerate()
+        .take(last + 1)
+        .skip(span.start_line())
+    {
+        if n > span.start_line() && n < last {
+            if n == span.start_line() + 1 {
+                out.push_str("...\n");
+            }
+            continue;
+        }
+        let line = expand_tabs(line);
+        let start = if n == span.start_line() {
+            span.start_column()
+        } else {
+            line.len() - line.trim_start().len()
+        };
+        let end = if n == span.end_line() {
+            span.end_column()
+        } else {
+            line.trim_end().len()
+        };
+        let width = end.saturating_sub(start).max(1);
+        out.push_str(&format!("{:>gutter$} | {}\n", n + 1, line.trim_end()));
+        out.push_str(&format!(
+            "{pad} | {}{}\n",
+            " ".repeat(start),
+            "^".repeat(width)
+        ));
+    }
+}
+
+/// Expands tabs to the next multiple of 8 columns, matching the columns
+/// reported in `SourceCodeInfo`.
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    for c in line.chars() {
+        if c == '\t' {
+            let n = 8 - expanded.chars().count() % 8;
+            expanded.extend(std::iter::repeat_n(' ', n));
+        } else {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn test_render_snippet() {
+        let text = "message Foo {\n\tstring bar = 1;\n}\n";
+        let mut loc = protobuf::descriptor::source_code_info::Location::new();
+        loc.span = vec![1, 8, 23];
+        let span = Span::from_location("foo.proto", &loc).unwrap();
+        let mut out = String::new();
+        render_snippet(&mut out, text, &span, 1);
+        assert_eq!(
+            out,
+            "2 |         string bar = 1;\n  |         ^^^^^^^^^^^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let ast = test_util::kitchen();
+        let mut sources = Sources::new();
+        sources.insert(
+            "kitchen/kitchen.proto",
+            fs::read_to_string(test_util::fixture("proto/kitchen/kitchen.proto")).unwrap(),
+        );
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push(
+            Diagnostic::error(ast.node(".kitchen.Color.RGB.r").unwrap(), "too short")
+                .note("use `red`"),
+        );
+        diagnostics.warning(ast.node(".kitchen.Style").unwrap(), "unused enum");
+        diagnostics.warning(ast.node(".kitchen.Sink.brand").unwrap(), "deprecated");
+        let error = r#"error: too short
+  --> kitchen/kitchen.proto:39:9
+   |
+39 |         uint32 r = 1;
+   |         ^^^^^^^^^^^^^
+   |
+   = note: use `red`
+   = note: in .kitchen.Color.RGB.r
+"#;
+        assert_eq!(diagnostics.iter().next().unwrap().render(&sources), error);
+        assert_eq!(
+            diagnostics.render(&sources),
+            error.to_string()
+                + r#"
+warning: unused enum
+  --> kitchen/kitchen.proto:9:1
+   |
+ 9 | enum Style {
+   | ^^^^^^^^^^^^
+...
+16 | }
+   | ^
+   |
+   = note: in .kitchen.Style
+
+warning: deprecated
+ --> kitchen/sink.proto:9:5
+  |
+  = note: in .kitchen.Sink.brand
+
+1 error, 2 warnings emitted
+"#
+        );
+    }
+}
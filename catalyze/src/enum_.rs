@@ -1,13 +1,13 @@
-use std::{
-    cell::RefCell,
-    collections::VecDeque,
-    rc::{Rc, Weak},
-};
+use std::{collections::VecDeque, str::FromStr};
+
+use crate::sync::{Rc, RefCell, Weak};
 
 use protobuf::reflect::{EnumDescriptor, EnumValueDescriptor};
 
 use crate::{
     comments::Comments,
+    custom_option::CustomOption,
+    error::Error,
     file::{File, WeakFile},
     iter::Iter,
     message::{Dependents, Message, WeakMessage},
@@ -28,25 +28,29 @@ struct EnumValueDetail {
 
 impl EnumValueDetail {
     pub fn name(&self) -> &str {
-        &self.descriptor.name()
+        self.descriptor.name()
     }
     pub fn fully_qualified_name(&self) -> &str {
         &self.fqn
     }
     pub fn descriptor(&self) -> EnumValueDescriptor {
-        self.descriptor
+        self.descriptor.clone()
     }
 
-    pub fn into_enum(&self) -> Enum {
+    pub fn enum_(&self) -> Enum {
         self.e.clone().into()
     }
 
     pub fn comments(&self) -> Comments {
-        *self.comments.borrow()
+        self.comments.borrow().clone()
     }
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.comments.freeze();
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -69,9 +73,12 @@ impl EnumValue {
     pub fn descriptor(&self) -> EnumValueDescriptor {
         self.0.descriptor()
     }
+    pub fn options(&self) -> ValueOptions<'_> {
+        ValueOptions::from(self.0.descriptor.proto().options.as_ref())
+    }
     /// Returns the `Enum` that contains this value.
     pub fn enum_(&self) -> Enum {
-        self.0.into_enum()
+        self.0.enum_()
     }
     pub fn container(&self) -> Container {
         self.enum_().container()
@@ -85,7 +92,7 @@ impl EnumValue {
         self.enum_().package()
     }
     pub fn number(&self) -> i32 {
-        self.descriptor().number()
+        self.descriptor().value()
     }
     pub fn fully_qualified_name(&self) -> &str {
         self.0.fully_qualified_name()
@@ -93,9 +100,17 @@ impl EnumValue {
     pub fn comments(&self) -> Comments {
         self.0.comments()
     }
+    /// Returns the custom option `T` set on the enum value; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
+    }
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.freeze();
+    }
 
     pub(crate) fn node_at_path(&self, path: &[i32]) -> Option<Node> {
         if path.is_empty() {
@@ -136,32 +151,18 @@ impl PartialEq<EnumValue> for str {
 struct EnumDetail {
     fqn: String,
     comments: RefCell<Comments>,
-    values: RefCell<Vec<EnumValue>>,
+    values: Rc<RefCell<Vec<EnumValue>>>,
     container: WeakContainer,
-    dependents: RefCell<Vec<WeakMessage>>,
+    dependents: Rc<RefCell<Vec<WeakMessage>>>,
     descriptor: EnumDescriptor,
     wkt: Option<WellKnownEnum>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Options {
-    // message fields
-    ///  Set this option to true to allow mapping different tag names to the same
-    ///  value.
-    // @@protoc_insertion_point(field:google.protobuf.EnumOptions.allow_alias)
     allow_alias: Option<bool>,
-    ///  Is this enum deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the enum, or it will be completely ignored; in the very least, this
-    ///  is a formalization for deprecating enums.
-    // @@protoc_insertion_point(field:google.protobuf.EnumOptions.deprecated)
     deprecated: Option<bool>,
-    ///  The parser stores options it doesn't recognize here. See above.
-    // @@protoc_insertion_point(field:google.protobuf.EnumOptions.uninterpreted_option)
     uninterpreted_option: Vec<UninterpretedOption>,
-    // special fields
-    // @@protoc_insertion_point(special_field:google.protobuf.EnumOptions.special_fields)
-    special_fields: protobuf::SpecialFields,
 }
 impl Options {
     /// Is this enum deprecated?
@@ -169,27 +170,26 @@ impl Options {
     /// for the enum, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating enums.
     pub fn deprecated(&self) -> bool {
-        self.opts().deprecated()
+        self.deprecated.unwrap_or(false)
     }
     pub fn is_deprecated(&self) -> bool {
-        self.opts().deprecated()
+        self.deprecated()
     }
     /// Options not recognized by the parser.
     pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+        &self.uninterpreted_option
     }
     /// Allows mapping different tag names to the same value.
     pub fn allow_alias(&self) -> bool {
-        self.opts().allow_alias()
+        self.allow_alias.unwrap_or(false)
     }
 }
 
-impl From<Option<protobuf::descriptor::EnumOptions>> for Options {
+impl From<Option<&protobuf::descriptor::EnumOptions>> for Options {
     fn from(opts: Option<&protobuf::descriptor::EnumOptions>) -> Self {
         Self {
             allow_alias: opts.and_then(|o| o.allow_alias),
             deprecated: opts.and_then(|o| o.deprecated),
-            special_fields: opts.map(|o| o.special_fields).unwrap_or_default(),
             uninterpreted_option: opts
                 .map(|o| o.uninterpreted_option.iter().map(Into::into).collect())
                 .unwrap_or_default(),
@@ -199,13 +199,19 @@ impl From<Option<protobuf::descriptor::EnumOptions>> for Options {
 
 impl EnumDetail {
     pub fn comments(&self) -> Comments {
-        *self.comments.borrow()
+        self.comments.borrow().clone()
     }
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.comments.freeze();
+        self.values.freeze();
+        self.dependents.freeze();
+    }
     pub(crate) fn descriptor(&self) -> EnumDescriptor {
-        self.descriptor
+        self.descriptor.clone()
     }
     pub fn container(&self) -> Container {
         self.container.clone().into()
@@ -235,12 +241,12 @@ impl Enum {
     pub(crate) fn new(desc: EnumDescriptor, container: Container) -> Self {
         let fully_qualified_name = format!("{}.{}", container.fully_qualified_name(), desc.name());
         let wkt = if container.package().is_well_known_type() {
-            WellKnownType::from_str(desc.name()).ok()
+            WellKnownEnum::from_str(desc.name()).ok()
         } else {
             None
         };
         let e = Enum(Rc::new(EnumDetail {
-            values: Rc::new(RefCell::new(Vec::with_capacity(desc.values().len()))),
+            values: Rc::new(RefCell::new(Vec::new())),
             container: container.into(),
             dependents: Rc::new(RefCell::new(Vec::default())),
             fqn: fully_qualified_name,
@@ -249,12 +255,12 @@ impl Enum {
             wkt,
         }));
 
-        {
-            let mut values = e.0.values.borrow_mut();
-            for v in desc.values() {
-                values.push(EnumValue::new(v, e.clone()));
-            }
-        }
+        let values = e
+            .descriptor()
+            .values()
+            .map(|v| EnumValue::new(v, e.clone()))
+            .collect::<Vec<_>>();
+        e.0.values.borrow_mut().extend(values);
         e
     }
     pub fn has_value(&self, name: &str) -> bool {
@@ -279,6 +285,9 @@ impl Enum {
     pub fn descriptor(&self) -> EnumDescriptor {
         self.0.descriptor()
     }
+    pub fn options(&self) -> Options {
+        Options::from(self.0.descriptor.proto().options.as_ref())
+    }
     pub fn container(&self) -> Container {
         self.0.container.clone().into()
     }
@@ -286,7 +295,7 @@ impl Enum {
         self.0.file()
     }
     pub fn name(&self) -> &str {
-        &self.0.descriptor.name()
+        self.0.descriptor.name()
     }
     pub fn values(&self) -> Iter<EnumValue> {
         Iter::from(&self.0.values)
@@ -300,10 +309,18 @@ impl Enum {
     pub fn comments(&self) -> Comments {
         self.0.comments()
     }
+    /// Returns the custom option `T` set on the enum; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
+    }
 
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.freeze();
+    }
 
     pub(crate) fn weak_file(&self) -> WeakFile {
         self.0.container.weak_file()
@@ -330,7 +347,7 @@ impl Enum {
         EnumDescriptorPath::try_from(path[0])
             .ok()
             .and_then(|p| match p {
-                EnumDescriptorPath::Value => self.0.values.borrow().get(next).map(Into::into),
+                EnumDescriptorPath::Value => self.values().get(next).map(Into::into),
                 // _ => None,
             })
     }
@@ -414,7 +431,12 @@ impl Iterator for AllEnums {
 pub struct ValueOptions<'a> {
     opts: Option<&'a protobuf::descriptor::EnumValueOptions>,
 }
-impl ValueOptions<'_> {
+impl<'a> ValueOptions<'a> {
+    fn opts(&self) -> &'a protobuf::descriptor::EnumValueOptions {
+        self.opts.unwrap_or_else(|| {
+            <protobuf::descriptor::EnumValueOptions as protobuf::Message>::default_instance()
+        })
+    }
     /// Is this enum value deprecated?
     /// Depending on the target platform, this can emit Deprecated annotations
     /// for the enum value, or it will be completely ignored; in the very least,
@@ -426,8 +448,12 @@ impl ValueOptions<'_> {
         self.opts().deprecated()
     }
     /// Options not recognized by the parser.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.opts()
+            .uninterpreted_option
+            .iter()
+            .map(Into::into)
+            .collect()
     }
 }
 impl<'a> From<Option<&'a protobuf::descriptor::EnumValueOptions>> for ValueOptions<'a> {
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use snafu::Snafu;
 
 use crate::{
@@ -5,6 +7,7 @@ use crate::{
     file::{File, Syntax},
     method::MethodIo,
     node::{Kind, Node},
+    Version,
 };
 
 #[derive(Snafu, Debug)]
@@ -47,11 +50,99 @@ pub enum Error {
     #[snafu(display("Unknown syntax: {value:?}; expected either \"proto2\" or \"proto3\""))]
     UnknownSyntax { value: String },
 
-    #[snafu(display("Failed to execute, caused by:\n{source}"))]
+    #[snafu(display("{file}: {source}"))]
+    InFile { file: String, source: Box<Error> },
+
+    #[snafu(display("Failed to execute {module}, caused by:\n{source}"))]
     FailedToExecute {
+        module: String,
+        source: Box<dyn 'static + std::error::Error + Send + Sync>,
+    },
+
+    #[snafu(display("Invalid file descriptor: {source}"))]
+    InvalidFileDescriptor { source: protobuf::Error },
+
+    #[snafu(display("Failed to decode CodeGeneratorRequest: {source}"))]
+    DecodeRequest { source: protobuf::Error },
+
+    #[snafu(display("Failed to encode CodeGeneratorResponse: {source}"))]
+    EncodeResponse { source: protobuf::Error },
+
+    #[snafu(display("Failed to decode FileDescriptorSet: {source}"))]
+    DecodeFileDescriptorSet { source: protobuf::Error },
+
+    #[snafu(display("Failed to decode custom option {option}: {source}"))]
+    DecodeCustomOption {
+        option: String,
+        source: protobuf::Error,
+    },
+
+    #[snafu(display("Custom option {option} has an unexpected wire type"))]
+    CustomOptionWireType { option: String },
+
+    #[snafu(display("Expected {expected} option value, found {found}"))]
+    OptionValueType {
+        expected: &'static str,
+        found: String,
+    },
+
+    #[snafu(display("Field {field:?} not found in option message {message}"))]
+    OptionFieldNotFound { message: String, field: String },
+
+    #[snafu(display("Failed to parse proto files: {source}"))]
+    ParseProto {
+        source: Box<dyn 'static + std::error::Error + Send + Sync>,
+    },
+
+    #[snafu(display("Failed to write {}: {source}", path.display()))]
+    WriteArtifact {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display(
+        "Insertion point {insertion_point:?} not found in {}",
+        path.display()
+    ))]
+    InsertionPointNotFound {
+        path: PathBuf,
+        insertion_point: String,
+    },
+
+    #[snafu(display(
+        "Custom artifact {} is not under an allowed root",
+        path.display()
+    ))]
+    CustomArtifactNotAllowed { path: PathBuf },
+
+    #[snafu(display(
+        "Artifact {} is not relative to the output path",
+        path.display()
+    ))]
+    ArtifactOutsideOutputPath { path: PathBuf },
+
+    #[snafu(display("Missing required parameter {key:?}"))]
+    MissingParameter { key: String },
+
+    #[snafu(display("Unknown parameter {key:?}"))]
+    UnknownParameter { key: String },
+
+    #[snafu(display("Invalid value {value:?} for parameter {key:?}: {source}"))]
+    InvalidParameter {
+        key: String,
+        value: String,
         source: Box<dyn 'static + std::error::Error + Send + Sync>,
     },
 
+    #[snafu(display("protoc {version} is not supported; {minimum} or later is required"))]
+    UnsupportedProtocVersion {
+        version: Box<Version>,
+        minimum: Box<Version>,
+    },
+
+    #[snafu(display("Messages form a dependency cycle: {}", messages.join(", ")))]
+    MessageCycle { messages: Vec<String> },
+
     #[snafu(display("Invalid type: {value:?}"))]
     InvalidType { value: String },
 
@@ -77,8 +168,8 @@ pub enum Error {
     ))]
     InvalidIdempotencyLevel { value: i32 },
 
-    #[snafu(display("Not a well-known type: \"\""))]
-    NotWellKnownTyoe { value: String },
+    #[snafu(display("Not a well-known type: \"{value}\""))]
+    NotAWellKnownType { value: String },
 
     #[snafu(display("Unknown file descriptor path: {path}"))]
     UnknownFileDecriptorPath { path: i32 },
@@ -94,21 +185,32 @@ pub enum Error {
 }
 
 impl Error {
+    /// Wraps the error with the name of the proto file being processed when
+    /// it occurred.
+    pub(crate) fn in_file(self, file: impl AsRef<str>) -> Self {
+        Error::InFile {
+            file: file.as_ref().to_string(),
+            source: Box::new(self),
+        }
+    }
     pub(crate) fn invalid_node(expected: Kind, node: Node) -> Self {
         Error::InvalidNode { expected, node }
     }
+    pub(crate) fn option_value_type(
+        expected: &'static str,
+        found: &protobuf::reflect::ReflectValueBox,
+    ) -> Self {
+        Error::OptionValueType {
+            expected,
+            found: format!("{found:?}"),
+        }
+    }
     pub(crate) fn group_not_supported(fully_qualified_name: impl AsRef<str>) -> Self {
         Error::GroupNotSupported {
             fully_qualified_name: fully_qualified_name.as_ref().to_string(),
         }
     }
 
-    pub(crate) fn invalid_type(value: impl AsRef<str>) -> Self {
-        Error::InvalidType {
-            value: value.as_ref().to_string(),
-        }
-    }
-
     pub(crate) fn invalid_map_key_type(field: Field) -> Self {
         Error::MapKeyType { field }
     }
@@ -124,9 +226,6 @@ impl Error {
     pub(crate) fn invalid_optimize_mode(value: i32) -> Self {
         Error::InvalidOptimizeMode { value }
     }
-    pub(crate) fn invalid_idempotency_level(value: i32) -> Self {
-        Error::InvalidIdempotencyLevel { value }
-    }
     pub(crate) fn not_well_known_type(value: impl AsRef<str>) -> Self {
         Error::NotAWellKnownType {
             value: value.as_ref().to_string(),
@@ -176,12 +275,13 @@ impl Error {
     pub(crate) fn map_entry_missing_key(
         fully_qualified_name: impl AsRef<str>,
         name: impl AsRef<str>,
+        syntax: Syntax,
     ) -> Self {
         Self::invalid_map_entry(
             InvalidMapEntryReason::MissingKey,
             fully_qualified_name,
             name,
-            Syntax::Proto3,
+            syntax,
         )
     }
 }
@@ -204,14 +304,54 @@ impl std::fmt::Display for InvalidMapEntryReason {
     }
 }
 
-#[derive(Debug, Snafu)]
-pub(crate) enum InputErrorInner {
-    Standalone { source: protobuf::Error },
+/// An error attributed to a specific element of a proto file, intended to be
+/// returned by [`Generate`](crate::Generate) implementations.
+///
+/// In plugin mode, the error is reported to protoc as
+/// `"{file}: {element}: {source}"`.
+#[derive(Debug)]
+pub struct NodeError {
+    file: String,
+    element: String,
+    source: Box<dyn 'static + std::error::Error + Send + Sync>,
 }
 
-#[derive(Debug, Snafu)]
-#[snafu(display("Failed to parse input: {}", source))]
-pub struct InputError {
-    source: InputErrorInner,
-    backtrace: snafu::Backtrace,
+impl NodeError {
+    pub fn new(
+        node: impl Into<Node>,
+        source: impl Into<Box<dyn 'static + std::error::Error + Send + Sync>>,
+    ) -> Self {
+        let node = node.into();
+        Self {
+            file: node
+                .file()
+                .map(|file| file.name().to_string())
+                .unwrap_or_default(),
+            element: node.fully_qualified_name().to_string(),
+            source: source.into(),
+        }
+    }
+    /// The name of the proto file containing the element.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+    /// The fully qualified name of the element.
+    pub fn element(&self) -> &str {
+        &self.element
+    }
+}
+
+impl std::fmt::Display for NodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.file.is_empty() {
+            write!(f, "{}: ", self.file)?;
+        }
+        write!(f, "{}: {}", self.element, self.source)
+    }
+}
+
+impl std::error::Error for NodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
 }
@@ -1,13 +1,13 @@
-use std::{
-    cell::RefCell,
-    collections::HashMap,
-    rc::{Rc, Weak},
-};
+use std::{collections::HashMap, fmt};
+
+use crate::sync::{Rc, RefCell, Weak};
 
 use protobuf::reflect::FieldDescriptor;
 
 use crate::{
     comments::Comments,
+    custom_option::CustomOption,
+    error::Error,
     file::File,
     iter::Iter,
     message::{Message, WeakMessage},
@@ -15,7 +15,7 @@ use crate::{
     package::Package,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 struct ExtensionDetail {
     descriptor: FieldDescriptor,
     fqn: String,
@@ -24,20 +24,29 @@ struct ExtensionDetail {
     extendee: RefCell<WeakMessage>,
 }
 
+impl fmt::Debug for ExtensionDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtensionDetail")
+            .field("fqn", &self.fqn)
+            .field("container", &self.container)
+            .field("comments", &self.comments)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Extension(Rc<ExtensionDetail>);
 
 impl Extension {
     pub(crate) fn new(desc: FieldDescriptor, container: Container) -> Self {
         let fqn = format!("{}.{}", container.fully_qualified_name(), desc.name());
-        let ext = Self(Rc::new(ExtensionDetail {
+        Self(Rc::new(ExtensionDetail {
             fqn,
             descriptor: desc,
             container: container.into(),
             comments: RefCell::new(Comments::default()),
             extendee: RefCell::new(WeakMessage::new()),
-        }));
-        ext
+        }))
     }
 
     /// Returns the Container where the Extension is defined
@@ -49,13 +58,17 @@ impl Extension {
         self.0.extendee.borrow().clone().into()
     }
     pub fn name(&self) -> &str {
-        &self.0.descriptor.name()
+        self.0.descriptor.name()
     }
     pub fn fully_qualified_name(&self) -> &str {
         &self.0.fqn
     }
     pub fn comments(&self) -> Comments {
-        *self.0.comments.borrow()
+        self.0.comments.borrow().clone()
+    }
+    /// Returns the custom option `T` set on the extension; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
     }
     pub fn file(&self) -> File {
         self.0.container.file()
@@ -64,7 +77,7 @@ impl Extension {
         self.file().package()
     }
     pub fn descriptor(&self) -> FieldDescriptor {
-        self.0.descriptor
+        self.0.descriptor.clone()
     }
     pub fn node_at_path(&self, path: &[i32]) -> Option<Node> {
         if path.is_empty() {
@@ -79,6 +92,11 @@ impl Extension {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.comments.freeze();
+        self.0.extendee.freeze();
+    }
 
     fn downgrade(&self) -> WeakExtension {
         WeakExtension(Rc::downgrade(&self.0))
@@ -140,6 +158,11 @@ impl Extensions {
             .borrow_mut()
             .insert(ext.fully_qualified_name().to_string(), ext.clone());
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.ext_map.freeze();
+        self.ext_vec.freeze();
+    }
 
     pub fn new() -> Extensions {
         Self {
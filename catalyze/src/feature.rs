@@ -0,0 +1,90 @@
+use protobuf::{
+    plugin::{code_generator_response::Feature, CodeGeneratorResponse},
+    Message,
+};
+
+/// `CodeGeneratorResponse.Feature.FEATURE_SUPPORTS_EDITIONS`, which is not
+/// yet defined by `protobuf`.
+const FEATURE_SUPPORTS_EDITIONS: u64 = 2;
+/// Field number of `CodeGeneratorResponse.minimum_edition`.
+const MINIMUM_EDITION_FIELD: u32 = 3;
+/// Field number of `CodeGeneratorResponse.maximum_edition`.
+const MAXIMUM_EDITION_FIELD: u32 = 4;
+
+/// Protobuf editions, as defined by `google.protobuf.Edition`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(i32)]
+pub enum Edition {
+    Proto2 = 998,
+    Proto3 = 999,
+    Edition2023 = 1000,
+    Edition2024 = 1001,
+}
+
+/// Features a protoc plugin advertises through
+/// `CodeGeneratorResponse.supported_features`. protoc refuses to run a plugin
+/// against files which make use of a feature it does not declare.
+///
+/// By default, only proto3 `optional` is supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Features {
+    proto3_optional: bool,
+    editions: Option<(Edition, Edition)>,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            proto3_optional: true,
+            editions: None,
+        }
+    }
+}
+
+impl Features {
+    /// Returns `Features` with nothing supported.
+    pub fn none() -> Self {
+        Self {
+            proto3_optional: false,
+            editions: None,
+        }
+    }
+
+    /// Sets whether proto3 `optional` fields are supported.
+    pub fn proto3_optional(mut self, supported: bool) -> Self {
+        self.proto3_optional = supported;
+        self
+    }
+
+    /// Declares support for editions, from `minimum` through `maximum`
+    /// (inclusive).
+    pub fn editions(mut self, minimum: Edition, maximum: Edition) -> Self {
+        self.editions = Some((minimum, maximum));
+        self
+    }
+
+    pub fn supports_proto3_optional(&self) -> bool {
+        self.proto3_optional
+    }
+
+    /// The minimum and maximum supported editions, if editions are supported.
+    pub fn supported_editions(&self) -> Option<(Edition, Edition)> {
+        self.editions
+    }
+
+    /// Sets `supported_features` and, if editions are supported, the
+    /// `minimum_edition` and `maximum_edition` of `response`.
+    pub(crate) fn apply(&self, response: &mut CodeGeneratorResponse) {
+        let mut supported = Feature::FEATURE_NONE as u64;
+        if self.proto3_optional {
+            supported |= Feature::FEATURE_PROTO3_OPTIONAL as u64;
+        }
+        if let Some((minimum, maximum)) = self.editions {
+            supported |= FEATURE_SUPPORTS_EDITIONS;
+            let unknown_fields = response.mut_unknown_fields();
+            unknown_fields.add_varint(MINIMUM_EDITION_FIELD, minimum as i32 as u64);
+            unknown_fields.add_varint(MAXIMUM_EDITION_FIELD, maximum as i32 as u64);
+        }
+        response.set_supported_features(supported);
+    }
+}
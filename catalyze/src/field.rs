@@ -15,10 +15,13 @@ pub use repeated_field::*;
 pub use scalar_field::*;
 
 use ::std::option::Option;
-use std::{cell::RefCell, convert::From, fmt};
+use std::{convert::From, fmt};
+
+use crate::sync::RefCell;
 
 use crate::{
     comments::Comments,
+    custom_option::CustomOption,
     enum_::Enum,
     error::{Error, InvalidMapEntryReason},
     file::{File, FileRefs, Syntax},
@@ -63,9 +66,11 @@ impl fmt::Display for Type {
     }
 }
 
-use protobuf::descriptor::field_descriptor_proto::Type as FieldDescriptorType;
-impl From<FieldDescriptorType> for Type {
-    fn from(t: FieldDescriptorType) -> Self {
+use protobuf::descriptor::{
+    field_descriptor_proto::Type as FieldDescriptorType, FieldDescriptorProto,
+};
+impl From<&FieldDescriptorProto> for Type {
+    fn from(t: &FieldDescriptorProto) -> Self {
         match t.type_() {
             FieldDescriptorType::TYPE_DOUBLE => Type::Scalar(Scalar::Double),
             FieldDescriptorType::TYPE_FLOAT => Type::Scalar(Scalar::Float),
@@ -82,8 +87,8 @@ impl From<FieldDescriptorType> for Type {
             FieldDescriptorType::TYPE_SFIXED64 => Type::Scalar(Scalar::Sfixed64),
             FieldDescriptorType::TYPE_SINT32 => Type::Scalar(Scalar::Sint32),
             FieldDescriptorType::TYPE_SINT64 => Type::Scalar(Scalar::Sint64),
-            FieldDescriptorType::TYPE_ENUM => Type::Enum(t.type_name()),
-            FieldDescriptorType::TYPE_MESSAGE => Type::Message(t.type_name()),
+            FieldDescriptorType::TYPE_ENUM => Type::Enum(t.type_name().to_string()),
+            FieldDescriptorType::TYPE_MESSAGE => Type::Message(t.type_name().to_string()),
             FieldDescriptorType::TYPE_GROUP => Type::Group,
         }
     }
@@ -159,7 +164,7 @@ impl TryFrom<i32> for Scalar {
             16 => Ok(Scalar::Sfixed64),
             17 => Ok(Scalar::Sint32),
             18 => Ok(Scalar::Sint64),
-            v => Err(Self::Error::invalid_scalar(value)),
+            _ => Err(Self::Error::invalid_scalar(value)),
         }
     }
 }
@@ -195,75 +200,14 @@ impl TryFrom<i32> for CType {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Options {
-    // message fields
-    ///  The ctype option instructs the C++ code generator to use a different
-    ///  representation of the field than it normally would.  See the specific
-    ///  options below.  This option is not yet implemented in the open source
-    ///  release -- sorry, we'll try to include it in a future version!
-    // @@protoc_insertion_point(field:google.protobuf.FieldOptions.ctype)
-    pub ctype: Option<CType>,
-    ///  The packed option can be enabled for repeated primitive fields to enable
-    ///  a more efficient representation on the wire. Rather than repeatedly
-    ///  writing the tag and type for each element, the entire array is encoded as
-    ///  a single length-delimited blob. In proto3, only explicit setting it to
-    ///  false will avoid using packed encoding.
-    // @@protoc_insertion_point(field:google.protobuf.FieldOptions.packed)
-    pub packed: Option<bool>,
-    ///  The jstype option determines the JavaScript type used for values of the
-    ///  field.  The option is permitted only for 64 bit integral and fixed types
-    ///  (int64, uint64, sint64, fixed64, sfixed64).  A field with jstype JS_STRING
-    ///  is represented as JavaScript string, which avoids loss of precision that
-    ///  can happen when a large value is converted to a floating point JavaScript.
-    ///  Specifying JS_NUMBER for the jstype causes the generated JavaScript code to
-    ///  use the JavaScript "number" type.  The behavior of the default option
-    ///  JS_NORMAL is implementation dependent.
-    ///
-    ///  This option is an enum to permit additional types to be added, e.g.
-    ///  goog.math.Integer.
-    jstype: Option<protobuf::EnumOrUnknown<protobuf::descriptor::field_options::JSType>>,
-    ///  Should this field be parsed lazily?  Lazy applies only to message-type
-    ///  fields.  It means that when the outer message is initially parsed, the
-    ///  inner message's contents will not be parsed but instead stored in encoded
-    ///  form.  The inner message will actually be parsed when it is first accessed.
-    ///
-    ///  This is only a hint.  Implementations are free to choose whether to use
-    ///  eager or lazy parsing regardless of the value of this option.  However,
-    ///  setting this option true suggests that the protocol author believes that
-    ///  using lazy parsing on this field is worth the additional bookkeeping
-    ///  overhead typically needed to implement it.
-    ///
-    ///  This option does not affect the public interface of any generated code;
-    ///  all method signatures remain the same.  Furthermore, thread-safety of the
-    ///  interface is not affected by this option; const methods remain safe to
-    ///  call from multiple threads concurrently, while non-const methods continue
-    ///  to require exclusive access.
-    ///
-    ///
-    ///  Note that implementations may choose not to check required fields within
-    ///  a lazy sub-message.  That is, calling IsInitialized() on the outer message
-    ///  may return true even if the inner message has missing required fields.
-    ///  This is necessary because otherwise the inner message would have to be
-    ///  parsed in order to perform the check, defeating the purpose of lazy
-    ///  parsing.  An implementation which chooses not to check required fields
-    ///  must be consistent about it.  That is, for any particular sub-message, the
-    ///  implementation must either *always* check its required fields, or *never*
-    ///  check its required fields, regardless of whether or not the message has
-    ///  been parsed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Options {
+    ctype: Option<CType>,
+    packed: Option<bool>,
+    jstype: Option<JsType>,
     lazy: Option<bool>,
-    ///  Is this field deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for accessors, or it will be completely ignored; in the very least, this
-    ///  is a formalization for deprecating fields.
     deprecated: Option<bool>,
-    ///  For Google-internal migration only. Do not use.
-    weak: Option<bool>,
-    ///  The parser stores options it doesn't recognize here. See above.
-    uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
-    // special fields
-    // @@protoc_insertion_point(special_field:google.protobuf.FieldOptions.special_fields)
-    special_fields: protobuf::SpecialFields,
+    uninterpreted_option: Vec<UninterpretedOption>,
 }
 impl Options {
     pub(crate) fn new(opts: Option<&protobuf::descriptor::FieldOptions>) -> Self {
@@ -271,14 +215,18 @@ impl Options {
             return Self::default();
         };
         Self {
-            ctype: opts.ctype,
+            ctype: opts.ctype.map(|v| match v.enum_value() {
+                Ok(v) => v.into(),
+                Err(_) => CType::String,
+            }),
             deprecated: opts.deprecated,
-            jstype: opts.jstype,
+            jstype: opts.jstype.map(|v| match v.enum_value() {
+                Ok(v) => v.into(),
+                Err(v) => JsType::Unknown(v),
+            }),
             lazy: opts.lazy,
             packed: opts.packed,
-            special_fields: opts.special_fields,
-            uninterpreted_option: opts.uninterpreted_option.iter.map(Into::into),
-            weak: opts.weak,
+            uninterpreted_option: opts.uninterpreted_option.iter().map(Into::into).collect(),
         }
     }
     /// The ctype option instructs the C++ code generator to use a different
@@ -286,7 +234,7 @@ impl Options {
     /// options below.  This option is not yet implemented in the open source
     /// release -- sorry, we'll try to include it in a future version!
     pub fn ctype(&self) -> CType {
-        CType::from(self.opts().ctype())
+        self.ctype.unwrap_or(CType::String)
     }
     /// The packed option can be enabled for repeated primitive fields to enable
     /// a more efficient representation on the wire. Rather than repeatedly
@@ -294,7 +242,7 @@ impl Options {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn packed(&self) -> bool {
-        self.opts().packed()
+        self.packed.unwrap_or(false)
     }
     /// The jstype option determines the JavaScript type used for values of the
     /// field.  The option is permitted only for 64 bit integral and fixed types
@@ -308,7 +256,7 @@ impl Options {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.opts().jstype().into()
+        self.jstype.unwrap_or(JsType::Normal)
     }
     /// Should this field be parsed lazily?  Lazy applies only to message-type
     /// fields.  It means that when the outer message is initially parsed, the
@@ -339,23 +287,19 @@ impl Options {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn lazy(&self) -> bool {
-        self.opts().lazy()
+        self.lazy.unwrap_or(false)
     }
     /// Is this field deprecated?
     /// Depending on the target platform, this can emit Deprecated annotations
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn deprecated(&self) -> bool {
-        self.opts().deprecated()
-    }
-    /// For Google-internal migration only. Do not use.
-    pub fn weak(&self) -> bool {
-        self.opts().weak()
+        self.deprecated.unwrap_or(false)
     }
 
     /// Options the parser does not recognize.
     pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+        &self.uninterpreted_option
     }
 }
 
@@ -423,7 +367,7 @@ impl From<&MapField> for Field {
 impl Field {
     pub fn new(desc: FieldDescriptor, msg: Message, oneof: Option<Oneof>) -> Result<Field, Error> {
         let detail = FieldDetail::new(desc, msg, None);
-        if desc.type_().is_group() {
+        if detail.value_type().is_group() {
             return Err(Error::group_not_supported(detail.fully_qualified_name()));
         }
         if let Some(oneof) = oneof {
@@ -513,6 +457,10 @@ impl Field {
             Field::Scalar(f) => f.comments(),
         }
     }
+    /// Returns the custom option `T` set on the field; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
+    }
     pub fn has_import(&self) -> bool {
         match self {
             Field::Embed(f) => f.has_import(),
@@ -754,6 +702,17 @@ impl Field {
             Field::Scalar(f) => f.set_comments(comments),
         }
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        match self {
+            Field::Embed(f) => f.freeze(),
+            Field::Enum(f) => f.freeze(),
+            Field::Map(f) => f.freeze(),
+            Field::Oneof(f) => f.freeze(),
+            Field::Repeated(f) => f.freeze(),
+            Field::Scalar(f) => f.freeze(),
+        }
+    }
 
     pub(crate) fn set_value(&self, value: Node) -> Result<(), Error> {
         match self {
@@ -802,7 +761,7 @@ impl Field {
     /// options below.  This option is not yet implemented in the open source
     /// release -- sorry, we'll try to include it in a future version!
     pub fn ctype(&self) -> CType {
-        self.descriptor().options().ctype()
+        FieldDetail::options_of(&self.descriptor()).ctype()
     }
     /// The packed option can be enabled for repeated primitive fields to enable
     /// a more efficient representation on the wire. Rather than repeatedly
@@ -894,7 +853,7 @@ impl Field {
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
         match self {
             Field::Embed(f) => f.uninterpreted_options(),
             Field::Enum(f) => f.uninterpreted_options(),
@@ -937,7 +896,6 @@ impl Field {
         }
     }
 
-    #[must_use]
     pub fn try_into_embed(self) -> Result<EmbedField, Self> {
         if let Self::Embed(v) = self {
             Ok(v)
@@ -955,7 +913,6 @@ impl Field {
         }
     }
 
-    #[must_use]
     pub fn try_into_enum(self) -> Result<EnumField, Self> {
         if let Self::Enum(v) = self {
             Ok(v)
@@ -973,7 +930,6 @@ impl Field {
         }
     }
 
-    #[must_use]
     pub fn try_into_map(self) -> Result<MapField, Self> {
         if let Self::Map(v) = self {
             Ok(v)
@@ -999,7 +955,6 @@ impl Field {
         }
     }
 
-    #[must_use]
     pub fn try_into_oneof(self) -> Result<OneofField, Self> {
         if let Self::Oneof(v) = self {
             Ok(v)
@@ -1017,7 +972,6 @@ impl Field {
         }
     }
 
-    #[must_use]
     pub fn try_into_repeated(self) -> Result<RepeatedField, Self> {
         if let Self::Repeated(v) = self {
             Ok(v)
@@ -1035,7 +989,6 @@ impl Field {
         }
     }
 
-    #[must_use]
     pub fn try_into_scalar(self) -> Result<ScalarField, Self> {
         if let Self::Scalar(v) = self {
             Ok(v)
@@ -1045,7 +998,7 @@ impl Field {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub(crate) struct FieldDetail {
     msg: WeakMessage,
     fqn: String,
@@ -1055,6 +1008,16 @@ pub(crate) struct FieldDetail {
     map_entry: Option<WeakMessage>,
 }
 
+impl fmt::Debug for FieldDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldDetail")
+            .field("fqn", &self.fqn)
+            .field("syntax", &self.syntax)
+            .field("comments", &self.comments)
+            .finish_non_exhaustive()
+    }
+}
+
 impl FieldDetail {
     pub fn new(desc: FieldDescriptor, msg: Message, map_entry: Option<Message>) -> Self {
         let fqn = format!("{}.{}", msg.fully_qualified_name(), desc.name());
@@ -1086,7 +1049,13 @@ impl FieldDetail {
         self.syntax
     }
     pub fn descriptor(&self) -> FieldDescriptor {
-        self.descriptor
+        self.descriptor.clone()
+    }
+    pub(crate) fn options(&self) -> Options {
+        Self::options_of(&self.descriptor)
+    }
+    pub(crate) fn options_of(descriptor: &FieldDescriptor) -> Options {
+        Options::new(descriptor.proto().options.as_ref())
     }
     pub fn is_map(&self) -> bool {
         self.map_entry.is_some()
@@ -1094,21 +1063,31 @@ impl FieldDetail {
     pub fn is_repeated(&self) -> bool {
         self.descriptor.is_repeated()
     }
+    /// Returns `true` if the field is labeled `optional`; in proto3, this
+    /// is only the case for fields declared with the `optional` keyword.
     pub fn is_marked_optional(&self) -> bool {
-        self.descriptor.is_marked_optional(self.syntax)
+        let proto = self.descriptor.proto();
+        match self.syntax {
+            Syntax::Proto2 => proto.label() == Label::Optional.into(),
+            Syntax::Proto3 => proto.proto3_optional(),
+        }
     }
     pub fn is_marked_required(&self) -> bool {
-        self.descriptor.is_marked_required(self.syntax)
+        self.syntax.is_proto2() && self.descriptor.proto().label() == Label::Required.into()
     }
     pub fn value_type(&self) -> Type {
-        self.descriptor.type_()
+        Type::from(self.descriptor.proto())
     }
 
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.comments.freeze();
+    }
     pub fn comments(&self) -> Comments {
-        *self.comments.borrow()
+        self.comments.borrow().clone()
     }
     pub fn file(&self) -> File {
         self.msg.file()
@@ -1182,6 +1161,16 @@ impl From<protobuf::descriptor::field_descriptor_proto::Label> for Label {
     }
 }
 
+impl From<Label> for protobuf::descriptor::field_descriptor_proto::Label {
+    fn from(value: Label) -> Self {
+        match value {
+            Label::Required => Self::LABEL_REQUIRED,
+            Label::Optional => Self::LABEL_OPTIONAL,
+            Label::Repeated => Self::LABEL_REPEATED,
+        }
+    }
+}
+
 impl TryFrom<i32> for Label {
     type Error = crate::error::Error;
     fn try_from(value: i32) -> Result<Self, Self::Error> {
@@ -1,6 +1,6 @@
 #![allow(clippy::new_ret_no_self)]
 
-use std::{cell::RefCell, rc::Rc};
+use crate::sync::{Rc, RefCell};
 
 use protobuf::reflect::FieldDescriptor;
 
@@ -13,10 +13,10 @@ use crate::{
     node::{Kind, Node},
     package::Package,
     uninterpreted_option::UninterpretedOption,
-    well_known::{self, WellKnownMessage, WellKnownType},
+    well_known::{WellKnownMessage, WellKnownType},
 };
 
-use super::{FieldDetail, Type};
+use super::{FieldDetail, Options, Type};
 
 #[derive(Debug, Clone)]
 pub(crate) struct Detail {
@@ -84,7 +84,7 @@ pub struct EmbedField(Rc<Detail>);
 
 impl EmbedField {
     pub fn name(&self) -> &str {
-        self.0.descriptor().name()
+        self.0.name()
     }
     pub fn comments(&self) -> Comments {
         self.0.detail.comments()
@@ -92,6 +92,11 @@ impl EmbedField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+        self.0.embed.freeze();
+    }
 
     pub fn embed(&self) -> Message {
         self.0.embed()
@@ -106,7 +111,7 @@ impl EmbedField {
         self.0.detail.package()
     }
     pub fn fully_qualified_name(&self) -> &str {
-        &self.0.detail.fqn
+        self.0.detail.fully_qualified_name()
     }
 
     /// Indicates whether or not the field is labeled as a required field. This
@@ -144,7 +149,10 @@ impl EmbedField {
     }
 
     pub fn descriptor(&self) -> FieldDescriptor {
-        self.0.descriptor()
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
     }
 
     pub fn syntax(&self) -> Syntax {
@@ -164,7 +172,7 @@ impl EmbedField {
     }
 
     pub fn value_type(&self) -> Type {
-        self.0.descriptor().type_()
+        self.0.detail.value_type()
     }
 
     pub(crate) fn new(detail: FieldDetail) -> Field {
@@ -187,7 +195,7 @@ impl EmbedField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
     /// The packed option can be enabled for repeated primitive fields to enable
     /// a more efficient representation on the wire. Rather than repeatedly
@@ -195,7 +203,7 @@ impl EmbedField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
     /// Should this field be parsed lazily?  Lazy applies only to message-type
     /// fields.  It means that when the outer message is initially parsed, the
@@ -226,19 +234,19 @@ impl EmbedField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
     /// Is this field deprecated?
     /// Depending on the target platform, this can emit Deprecated annotations
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -1,6 +1,6 @@
 #![allow(clippy::new_ret_no_self)]
 
-use std::{cell::RefCell, rc::Rc};
+use crate::sync::{Rc, RefCell};
 
 use crate::{
     comments::Comments,
@@ -15,7 +15,9 @@ use crate::{
     well_known::{WellKnownEnum, WellKnownType},
 };
 
-use super::JsType;
+use protobuf::reflect::FieldDescriptor;
+
+use super::{JsType, Options};
 
 #[derive(Debug, Clone)]
 pub(crate) struct Detail {
@@ -40,6 +42,11 @@ impl Detail {
     pub fn set_comments(&self, comments: Comments) {
         self.detail.set_comments(comments)
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.detail.freeze();
+        self.enum_.freeze();
+    }
     pub fn package(&self) -> Package {
         self.detail.package()
     }
@@ -150,19 +157,26 @@ impl EnumField {
     pub fn set_comments(&self, comments: Comments) {
         self.0.set_comments(comments)
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.freeze()
+    }
     pub fn package(&self) -> Package {
         self.0.package()
     }
 
     pub fn has_presence(&self) -> bool {
-        self.syntax() == Syntax::Proto2 || self.descriptor().is_marked_optional(self.syntax())
+        self.syntax() == Syntax::Proto2 || self.is_marked_optional()
     }
     pub fn syntax(&self) -> Syntax {
         self.0.syntax()
     }
-    // pub fn descriptor(&self) -> FieldDescriptor {
-    //     self.0.descriptor()
-    // }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
 
     pub fn file(&self) -> File {
         self.0.detail.file()
@@ -196,7 +210,7 @@ impl EnumField {
     }
 
     pub fn value_type(&self) -> Type {
-        self.descriptor().type_()
+        Type::from(self.descriptor().proto())
     }
     /// The jstype option determines the JavaScript type used for values of the
     /// field.  The option is permitted only for 64 bit integral and fixed types
@@ -210,7 +224,7 @@ impl EnumField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
     /// The packed option can be enabled for repeated primitive fields to enable
     /// a more efficient representation on the wire. Rather than repeatedly
@@ -218,7 +232,7 @@ impl EnumField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -250,7 +264,7 @@ impl EnumField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -258,12 +272,12 @@ impl EnumField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -1,13 +1,13 @@
 #![allow(clippy::new_ret_no_self)]
-use std::{cell::RefCell, rc::Rc};
+use crate::sync::{Rc, RefCell};
 
 use protobuf::reflect::FieldDescriptor;
 
-use super::FieldDetail;
+use super::{FieldDetail, Options};
 use crate::{
     comments::Comments,
     enum_::{Enum, WeakEnum},
-    error::{Error, InvalidMapEntryReason},
+    error::Error,
     field::{Field, JsType, Scalar, Type},
     file::{File, FileRefs, Syntax},
     message::{Message, WeakMessage},
@@ -127,9 +127,11 @@ impl MapField {
             _ => None,
         }
     }
-    /// alias for `enum_`
     pub fn enum_(&self) -> Option<Enum> {
-        self.enum_()
+        match self {
+            MapField::Enum(f) => Some(f.enum_()),
+            _ => None,
+        }
     }
 
     pub fn comments(&self) -> Comments {
@@ -170,6 +172,14 @@ impl MapField {
             MapField::Embed(f) => f.set_comments(comments),
         }
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        match self {
+            MapField::Scalar(f) => f.freeze(),
+            MapField::Enum(f) => f.freeze(),
+            MapField::Embed(f) => f.freeze(),
+        }
+    }
 
     pub fn has_import(&self) -> bool {
         match self {
@@ -278,11 +288,12 @@ impl MapField {
         };
 
         let fields = map_entry.fields();
-        let key = fields.get(0).ok_or_else(|| Error::InvalidMapEntry {
-            reason: InvalidMapEntryReason::MissingKey,
-            fully_qualified_name: detail.fully_qualified_name().to_string(),
-            name: detail.name().to_string(),
-            syntax: detail.syntax,
+        let key = fields.get(0).ok_or_else(|| {
+            Error::map_entry_missing_key(
+                detail.fully_qualified_name(),
+                detail.name(),
+                detail.syntax(),
+            )
         })?;
         let value = fields.get(1).ok_or_else(|| {
             Error::map_entry_missing_value(
@@ -295,7 +306,7 @@ impl MapField {
         let key = key
             .value_type()
             .try_into()
-            .map_err(|err| Error::invalid_map_key_type(key))?;
+            .map_err(|_| Error::invalid_map_key_type(key))?;
 
         let fd = Detail { key, detail };
         match value.value_type() {
@@ -312,7 +323,7 @@ impl MapField {
         }
     }
 
-    pub fn as_field(self) -> Field {
+    pub fn into_field(self) -> Field {
         Field::Map(self)
     }
 
@@ -384,7 +395,7 @@ impl MapField {
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
         match self {
             MapField::Scalar(f) => f.uninterpreted_options(),
             MapField::Enum(f) => f.uninterpreted_options(),
@@ -500,12 +511,19 @@ impl Detail {
     pub fn descriptor(&self) -> FieldDescriptor {
         self.detail.descriptor()
     }
+    fn options(&self) -> Options {
+        self.detail.options()
+    }
     pub fn comments(&self) -> Comments {
         self.detail.comments()
     }
-    pub fn set_comments(&self, comments: Comments) {
+    pub(crate) fn set_comments(&self, comments: Comments) {
         self.detail.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.detail.comments.freeze();
+    }
 
     pub fn file(&self) -> File {
         self.detail.file()
@@ -561,6 +579,9 @@ impl MappedScalarField {
     pub fn descriptor(&self) -> FieldDescriptor {
         self.0.detail.descriptor()
     }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn comments(&self) -> Comments {
         self.0.detail.comments()
     }
@@ -575,6 +596,10 @@ impl MappedScalarField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+    }
 
     pub fn build_target(&self) -> bool {
         self.0.detail.build_target()
@@ -607,7 +632,7 @@ impl MappedScalarField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
     /// The packed option can be enabled for repeated primitive fields to enable
     /// a more efficient representation on the wire. Rather than repeatedly
@@ -615,7 +640,7 @@ impl MappedScalarField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -647,7 +672,7 @@ impl MappedScalarField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -655,12 +680,12 @@ impl MappedScalarField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -717,6 +742,9 @@ impl MappedEmbedField {
     pub fn descriptor(&self) -> FieldDescriptor {
         self.0.detail.descriptor()
     }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     /// Returns the embedded message.
     pub fn embed(&self) -> Message {
         self.0.embed().into()
@@ -738,6 +766,11 @@ impl MappedEmbedField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+        self.0.embed.freeze();
+    }
 
     pub fn has_import(&self) -> bool {
         self.file() != self.0.embed().file()
@@ -785,7 +818,7 @@ impl MappedEmbedField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     pub(crate) fn set_value(&self, node: Node) -> Result<(), Error> {
@@ -808,7 +841,7 @@ impl MappedEmbedField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -840,7 +873,7 @@ impl MappedEmbedField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -848,12 +881,12 @@ impl MappedEmbedField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -910,6 +943,9 @@ impl MappedEnumField {
     pub fn descriptor(&self) -> FieldDescriptor {
         self.0.detail.descriptor()
     }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn file(&self) -> File {
         self.0.detail.file()
     }
@@ -936,9 +972,14 @@ impl MappedEnumField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+        self.0.enum_.freeze();
+    }
 
     pub fn has_import(&self) -> bool {
-        return self.enum_().file() != self.file();
+        self.enum_().file() != self.file()
     }
     pub fn imports(&self) -> FileRefs {
         if self.has_import() {
@@ -975,7 +1016,7 @@ impl MappedEnumField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
     fn set_value(&self, node: Node) -> Result<(), Error> {
         match node {
@@ -997,7 +1038,7 @@ impl MappedEnumField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -1029,7 +1070,7 @@ impl MappedEnumField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -1037,12 +1078,12 @@ impl MappedEnumField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
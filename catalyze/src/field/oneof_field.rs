@@ -1,7 +1,7 @@
 #![allow(clippy::new_ret_no_self)]
 
+use crate::sync::{Rc, RefCell};
 use core::panic;
-use std::{cell::RefCell, rc::Rc};
 
 use crate::{
     comments::Comments,
@@ -18,7 +18,9 @@ use crate::{
     well_known::WellKnownType,
 };
 
-use super::FieldDetail;
+use protobuf::reflect::FieldDescriptor;
+
+use super::{FieldDetail, Options};
 #[derive(Debug, Clone)]
 pub(crate) struct OneofFieldDetail {
     pub detail: FieldDetail,
@@ -28,6 +30,12 @@ impl OneofFieldDetail {
     pub fn name(&self) -> &str {
         self.detail.name()
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.detail.options()
+    }
 
     pub fn fully_qualified_name(&self) -> &str {
         self.detail.fully_qualified_name()
@@ -53,6 +61,10 @@ impl OneofFieldDetail {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.detail.freeze();
+    }
 
     pub fn is_marked_required(&self) -> bool {
         self.detail.is_marked_required()
@@ -81,6 +93,13 @@ impl OneofField {
             OneofField::Embed(f) => f.name(),
         }
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        match self {
+            OneofField::Scalar(f) => f.descriptor(),
+            OneofField::Enum(f) => f.descriptor(),
+            OneofField::Embed(f) => f.descriptor(),
+        }
+    }
     pub fn fully_qualified_name(&self) -> &str {
         match self {
             OneofField::Scalar(f) => f.fully_qualified_name(),
@@ -168,6 +187,14 @@ impl OneofField {
             OneofField::Embed(f) => f.set_comments(comments),
         }
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        match self {
+            OneofField::Scalar(f) => f.freeze(),
+            OneofField::Enum(f) => f.freeze(),
+            OneofField::Embed(f) => f.freeze(),
+        }
+    }
 
     pub fn syntax(&self) -> Syntax {
         match self {
@@ -242,7 +269,7 @@ impl OneofField {
     }
 
     pub fn value_type(&self) -> Type {
-        self.descriptor().type_()
+        Type::from(self.descriptor().proto())
     }
 
     pub(crate) fn new(detail: FieldDetail, oneof: Oneof) -> Result<Field, Error> {
@@ -357,8 +384,10 @@ impl OneofField {
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        FieldDetail::options_of(&self.descriptor())
+            .uninterpreted_options()
+            .to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -382,6 +411,12 @@ impl OneofEnumField {
     pub fn name(&self) -> &str {
         self.0.detail.name()
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
 
     pub fn fully_qualified_name(&self) -> &str {
         self.0.detail.fully_qualified_name()
@@ -405,6 +440,11 @@ impl OneofEnumField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+        self.0.enum_.freeze();
+    }
     pub fn imports(&self) -> FileRefs {
         if self.has_import() {
             FileRefs::from(self.0.enum_.borrow().weak_file())
@@ -473,7 +513,7 @@ impl OneofEnumField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     /// The packed option can be enabled for repeated primitive fields to enable
@@ -482,7 +522,7 @@ impl OneofEnumField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -514,7 +554,7 @@ impl OneofEnumField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -522,7 +562,7 @@ impl OneofEnumField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     pub fn number(&self) -> i32 {
@@ -543,6 +583,12 @@ impl OneofScalarField {
     pub fn name(&self) -> &str {
         self.0.detail.name()
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn oneof(&self) -> Oneof {
         self.0.detail.oneof()
     }
@@ -570,6 +616,10 @@ impl OneofScalarField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+    }
 
     pub fn build_target(&self) -> bool {
         self.file().build_target()
@@ -607,7 +657,7 @@ impl OneofScalarField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     /// The packed option can be enabled for repeated primitive fields to enable
@@ -616,7 +666,7 @@ impl OneofScalarField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -648,7 +698,7 @@ impl OneofScalarField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -656,7 +706,7 @@ impl OneofScalarField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     pub fn number(&self) -> i32 {
@@ -676,6 +726,12 @@ impl OneofEmbedField {
     pub fn name(&self) -> &str {
         self.0.detail.name()
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn fully_qualified_name(&self) -> &str {
         self.0.detail.fully_qualified_name()
     }
@@ -713,6 +769,11 @@ impl OneofEmbedField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+        self.0.embed.freeze();
+    }
 
     pub fn syntax(&self) -> Syntax {
         self.0.detail.syntax()
@@ -765,7 +826,7 @@ impl OneofEmbedField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     /// The packed option can be enabled for repeated primitive fields to enable
@@ -774,7 +835,7 @@ impl OneofEmbedField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -806,7 +867,7 @@ impl OneofEmbedField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -814,7 +875,7 @@ impl OneofEmbedField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     pub fn number(&self) -> i32 {
@@ -1,5 +1,9 @@
 #![allow(clippy::new_ret_no_self)]
-use std::{cell::RefCell, rc::Rc};
+use protobuf::reflect::FieldDescriptor;
+
+use crate::sync::{Rc, RefCell};
+
+use super::Options;
 
 use crate::{
     comments::Comments,
@@ -145,6 +149,14 @@ impl RepeatedField {
             RepeatedField::Embed(f) => f.set_comments(comments),
         }
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        match self {
+            RepeatedField::Scalar(f) => f.freeze(),
+            RepeatedField::Enum(f) => f.freeze(),
+            RepeatedField::Embed(f) => f.freeze(),
+        }
+    }
 
     pub fn is_marked_required(&self) -> bool {
         match self {
@@ -186,8 +198,16 @@ impl RepeatedField {
         }
     }
 
+    pub fn descriptor(&self) -> FieldDescriptor {
+        match self {
+            RepeatedField::Scalar(f) => f.descriptor(),
+            RepeatedField::Enum(f) => f.descriptor(),
+            RepeatedField::Embed(f) => f.descriptor(),
+        }
+    }
+
     pub fn value_type(&self) -> Type {
-        self.descriptor().type_()
+        Type::from(self.descriptor().proto())
     }
 
     pub(crate) fn new(detail: FieldDetail) -> Result<Field, Error> {
@@ -208,8 +228,7 @@ impl RepeatedField {
                     detail,
                     embed: RefCell::new(WeakMessage::new()),
                 }),
-            )))
-            .into()),
+            )))),
             Type::Group => Err(Error::group_not_supported(detail.fully_qualified_name())),
         }
     }
@@ -293,7 +312,7 @@ impl RepeatedField {
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
         match self {
             RepeatedField::Scalar(f) => f.uninterpreted_options(),
             RepeatedField::Enum(f) => f.uninterpreted_options(),
@@ -353,6 +372,12 @@ impl RepeatedEmbedField {
     pub fn name(&self) -> &str {
         self.0.detail.name()
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn fully_qualified_name(&self) -> &str {
         self.0.detail.fully_qualified_name()
     }
@@ -382,6 +407,11 @@ impl RepeatedEmbedField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.detail.freeze();
+        self.0.embed.freeze();
+    }
 
     pub fn has_import(&self) -> bool {
         self.file() != self.0.embed().file()
@@ -432,7 +462,7 @@ impl RepeatedEmbedField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     /// The packed option can be enabled for repeated primitive fields to enable
@@ -441,7 +471,7 @@ impl RepeatedEmbedField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -473,7 +503,7 @@ impl RepeatedEmbedField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -481,12 +511,12 @@ impl RepeatedEmbedField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -501,6 +531,12 @@ impl RepeatedEnumField {
     pub fn name(&self) -> &str {
         self.0.detail.name()
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn fully_qualified_name(&self) -> &str {
         self.0.detail.fully_qualified_name()
     }
@@ -536,6 +572,10 @@ impl RepeatedEnumField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.freeze();
+    }
 
     pub fn has_import(&self) -> bool {
         self.0.has_import()
@@ -577,7 +617,7 @@ impl RepeatedEnumField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     /// The packed option can be enabled for repeated primitive fields to enable
@@ -586,7 +626,7 @@ impl RepeatedEnumField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -618,7 +658,7 @@ impl RepeatedEnumField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -626,12 +666,12 @@ impl RepeatedEnumField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -646,6 +686,12 @@ impl RepeatedScalarField {
     pub fn name(&self) -> &str {
         self.0.name()
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn fully_qualified_name(&self) -> &str {
         self.0.fully_qualified_name()
     }
@@ -676,6 +722,10 @@ impl RepeatedScalarField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.freeze();
+    }
 
     pub fn scalar(&self) -> Scalar {
         self.0.scalar()
@@ -700,7 +750,7 @@ impl RepeatedScalarField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     /// The packed option can be enabled for repeated primitive fields to enable
@@ -709,7 +759,7 @@ impl RepeatedScalarField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -741,7 +791,7 @@ impl RepeatedScalarField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -749,12 +799,12 @@ impl RepeatedScalarField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().is_deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn number(&self) -> i32 {
@@ -1,7 +1,9 @@
 #![allow(clippy::new_ret_no_self)]
-use std::rc::Rc;
+use crate::sync::Rc;
 
-use super::FieldDetail;
+use protobuf::reflect::FieldDescriptor;
+
+use super::{FieldDetail, Options};
 use crate::{
     comments::Comments,
     field::{Field, JsType, Scalar, Syntax, Type},
@@ -13,8 +15,8 @@ use crate::{
 
 #[derive(Debug, Clone)]
 pub(crate) struct Detail {
-    detail: FieldDetail,
-    scalar: Scalar,
+    pub detail: FieldDetail,
+    pub scalar: Scalar,
 }
 
 impl Detail {
@@ -43,8 +45,6 @@ impl Detail {
     pub fn message(&self) -> Message {
         self.detail.message()
     }
-    /// Returns `Rc<U>`
-
     pub fn syntax(&self) -> Syntax {
         self.detail.syntax()
     }
@@ -62,6 +62,10 @@ impl Detail {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.detail.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.detail.freeze();
+    }
 
     pub fn is_marked_required(&self) -> bool {
         self.detail.is_marked_required()
@@ -89,6 +93,12 @@ impl ScalarField {
     pub fn scalar(&self) -> Scalar {
         self.0.scalar
     }
+    pub fn descriptor(&self) -> FieldDescriptor {
+        self.0.detail.descriptor()
+    }
+    fn options(&self) -> Options {
+        self.0.detail.options()
+    }
     pub fn name(&self) -> &str {
         self.0.name()
     }
@@ -110,6 +120,10 @@ impl ScalarField {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.set_comments(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.freeze();
+    }
 
     pub fn build_target(&self) -> bool {
         self.0.build_target()
@@ -128,7 +142,7 @@ impl ScalarField {
     }
 
     pub fn value_type(&self) -> Type {
-        self.descriptor().type_()
+        Type::from(self.descriptor().proto())
     }
 
     /// The jstype option determines the JavaScript type used for values of the
@@ -143,7 +157,7 @@ impl ScalarField {
     /// This option is an enum to permit additional types to be added, e.g.
     /// goog.math.Integer.
     pub fn jstype(&self) -> JsType {
-        self.descriptor().options().jstype()
+        self.options().jstype()
     }
 
     /// The packed option can be enabled for repeated primitive fields to enable
@@ -152,7 +166,7 @@ impl ScalarField {
     /// a single length-delimited blob. In proto3, only explicit setting it to
     /// false will avoid using packed encoding.
     pub fn is_packed(&self) -> bool {
-        self.descriptor().options().packed()
+        self.options().packed()
     }
 
     /// Should this field be parsed lazily?  Lazy applies only to message-type
@@ -184,7 +198,7 @@ impl ScalarField {
     /// check its required fields, regardless of whether or not the message has
     /// been parsed.
     pub fn is_lazy(&self) -> bool {
-        self.descriptor().options().is_lazy()
+        self.options().lazy()
     }
 
     /// Is this field deprecated?
@@ -192,12 +206,12 @@ impl ScalarField {
     /// for accessors, or it will be completely ignored; in the very least, this
     /// is a formalization for deprecating fields.
     pub fn is_deprecated(&self) -> bool {
-        self.descriptor().options().deprecated()
+        self.options().deprecated()
     }
 
     /// Options the parser does not recognize.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        self.descriptor().options().uninterpreted_options()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.options().uninterpreted_options().to_vec()
     }
 
     pub fn message(&self) -> Message {
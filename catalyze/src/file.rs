@@ -1,6 +1,7 @@
 use protobuf::reflect::FileDescriptor;
 
 use crate::comments::Comments;
+use crate::custom_option::CustomOption;
 use crate::enum_::{AllEnums, Enum};
 use crate::error::Error;
 use crate::extension::Extension;
@@ -10,13 +11,13 @@ use crate::node::{AllNodes, Container, Node, Nodes};
 use crate::package::{Package, WeakPackage};
 
 use crate::service::Service;
+use crate::sync::{OnceCell, Rc, RefCell, Weak};
 use crate::uninterpreted_option::UninterpretedOption;
 use crate::*;
-use std::cell::RefCell;
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::path::PathBuf;
-use std::rc::{Rc, Weak};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Syntax {
@@ -58,13 +59,21 @@ impl TryFrom<String> for Syntax {
     }
 }
 
-impl ToString for Syntax {
-    fn to_string(&self) -> String {
+impl fmt::Display for Syntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Syntax::Proto2 => "proto2",
             Syntax::Proto3 => "proto3",
         }
-        .to_string()
+        .fmt(f)
+    }
+}
+impl From<protobuf::reflect::Syntax> for Syntax {
+    fn from(v: protobuf::reflect::Syntax) -> Self {
+        match v {
+            protobuf::reflect::Syntax::Proto2 => Syntax::Proto2,
+            protobuf::reflect::Syntax::Proto3 => Syntax::Proto3,
+        }
     }
 }
 impl From<&str> for Syntax {
@@ -117,12 +126,17 @@ impl<'a> From<Option<&'a protobuf::descriptor::FileOptions>> for Options<'a> {
 }
 
 impl<'a> Options<'a> {
+    fn opts(&self) -> &'a protobuf::descriptor::FileOptions {
+        self.opts.unwrap_or_else(|| {
+            <protobuf::descriptor::FileOptions as protobuf::Message>::default_instance()
+        })
+    }
     /// Java package where classes generated from this .proto will be
     /// placed.  By default, the proto package is used, but this is often
     /// inappropriate because proto packages do not normally start with backwards
     /// domain names.
     pub fn java_package(&self) -> Option<&str> {
-        self.opts.and_then(|opts| opts.java_package.as_ref())
+        self.opts.and_then(|opts| opts.java_package.as_deref())
     }
     /// If set, all the classes from the .proto file are wrapped in a single
     /// outer class with the given name.  This applies to both Proto1
@@ -131,7 +145,7 @@ impl<'a> Options<'a> {
     /// explicitly choose the class name).
     pub fn java_outer_classname(&self) -> Option<&str> {
         self.opts
-            .and_then(|opts| opts.java_outer_classname.as_ref())
+            .and_then(|opts| opts.java_outer_classname.as_deref())
     }
 
     /// If set true, then the Java code generator will generate a separate .java
@@ -257,8 +271,12 @@ impl<'a> Options<'a> {
     }
     /// The parser stores options it doesn't recognize here.
     /// See the documentation for the "Options" section above.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.opts()
+            .uninterpreted_option
+            .iter()
+            .map(UninterpretedOption::from)
+            .collect()
     }
 }
 
@@ -270,18 +288,20 @@ struct Detail {
     descriptor: FileDescriptor,
     file_path: PathBuf,
     fqn: String,
-    messages: RefCell<Vec<Message>>,
-    enums: RefCell<Vec<Enum>>,
-    services: RefCell<Vec<Service>>,
-    defined_extensions: RefCell<Vec<Extension>>,
+    messages: Rc<RefCell<Vec<Message>>>,
+    enums: Rc<RefCell<Vec<Enum>>>,
+    services: Rc<RefCell<Vec<Service>>>,
+    defined_extensions: Rc<RefCell<Vec<Extension>>>,
     build_target: bool,
     pkg_comments: RefCell<Comments>,
     comments: RefCell<Comments>,
 
     pkg: WeakPackage,
-    dependents: RefCell<Vec<WeakFile>>,
-    imports: RefCell<Vec<WeakFile>>,
+    dependents: Rc<RefCell<Vec<WeakFile>>>,
+    imports: Rc<RefCell<Vec<WeakFile>>>,
     used_imports: Rc<RefCell<HashSet<String>>>,
+    visible_extensions: OnceCell<HashMap<String, Extension>>,
+    visible_symbols: OnceCell<HashSet<String>>,
     syntax: Syntax,
 }
 
@@ -291,30 +311,25 @@ impl Detail {
             "" => String::default(),
             p => format!(".{}", p),
         };
+        let proto = descriptor.proto();
         Rc::new(Self {
-            descriptor,
             pkg: pkg.into(),
             build_target,
             fqn,
-            syntax: descriptor.syntax(),
+            syntax: descriptor.syntax().into(),
             file_path: PathBuf::from(descriptor.name()),
             dependents: Rc::new(RefCell::new(Vec::new())),
-            imports: Rc::new(RefCell::new(Vec::with_capacity(
-                descriptor.dependencies().len(),
-            ))),
-            defined_extensions: Rc::new(RefCell::new(Vec::with_capacity(
-                descriptor.extensions().len(),
-            ))),
-            messages: Rc::new(RefCell::new(Vec::with_capacity(
-                descriptor.messages().len(),
-            ))),
-            enums: Rc::new(RefCell::new(Vec::with_capacity(descriptor.enums().len()))),
-            services: Rc::new(RefCell::new(Vec::with_capacity(
-                descriptor.services().len(),
-            ))),
+            imports: Rc::new(RefCell::new(Vec::with_capacity(proto.dependency.len()))),
+            defined_extensions: Rc::new(RefCell::new(Vec::with_capacity(proto.extension.len()))),
+            messages: Rc::new(RefCell::new(Vec::with_capacity(proto.message_type.len()))),
+            enums: Rc::new(RefCell::new(Vec::with_capacity(proto.enum_type.len()))),
+            services: Rc::new(RefCell::new(Vec::with_capacity(proto.service.len()))),
             pkg_comments: RefCell::new(Comments::default()),
             comments: RefCell::new(Comments::default()),
             used_imports: Rc::new(RefCell::new(HashSet::new())),
+            visible_extensions: OnceCell::new(),
+            visible_symbols: OnceCell::new(),
+            descriptor,
         })
     }
 }
@@ -336,18 +351,26 @@ impl File {
 
     fn assign_comments(self) -> Self {
         {
-            for loc in self.descriptor().source_code_info() {
-                match loc.file_descriptor_path() {
-                    Ok(p) => match p {
-                        FileDescriptorPath::Package => self.set_package_comments(loc.into()),
-                        FileDescriptorPath::Syntax => self.set_comments(loc.into()),
-                        _ => {
-                            let n = self.node_at_path(loc.path());
-                            if let Some(n) = n {
-                                n.set_comments(loc.into())
-                            }
+            let descriptor = self.descriptor();
+            let Some(info) = descriptor.proto().source_code_info.as_ref() else {
+                return self;
+            };
+            for loc in &info.location {
+                let Some(&first) = loc.path.first() else {
+                    continue;
+                };
+                match FileDescriptorPath::try_from(first) {
+                    Ok(FileDescriptorPath::Package) if loc.path.len() == 1 => {
+                        self.set_package_comments(loc.clone().into())
+                    }
+                    Ok(FileDescriptorPath::Syntax) if loc.path.len() == 1 => {
+                        self.set_comments(loc.clone().into())
+                    }
+                    Ok(_) => {
+                        if let Some(n) = self.node_at_path(&loc.path) {
+                            n.set_comments(loc.clone().into())
                         }
-                    },
+                    }
                     Err(_) => continue,
                 }
             }
@@ -357,45 +380,48 @@ impl File {
 
     fn hydrate_extensions(self) -> Self {
         {
-            let mut exts = self.0.defined_extensions.borrow_mut();
             let container = self.as_container();
-            for ed in self.descriptor().extensions() {
-                let ext = Extension::new(ed, container.clone());
-                exts.push(ext);
-            }
+            let exts: Vec<_> = self
+                .descriptor()
+                .extensions()
+                .map(|ed| Extension::new(ed, container.clone()))
+                .collect();
+            self.0.defined_extensions.borrow_mut().extend(exts);
         }
         self
     }
     fn hydrate_messages(self) -> Result<Self, Error> {
         {
-            let container = self.clone().as_container();
-            let mut msgs = self.0.messages.borrow_mut();
-            for md in self.descriptor().messages() {
-                let msg = Message::new(md, container.clone())?;
-                msgs.push(msg);
-            }
+            let container = self.as_container();
+            let msgs = self
+                .descriptor()
+                .messages()
+                .map(|md| Message::new(md, container.clone()))
+                .collect::<Result<Vec<_>, _>>()?;
+            self.0.messages.borrow_mut().extend(msgs);
         }
         Ok(self)
     }
     fn hydrate_enums(self) -> Self {
         {
             let container = self.as_container();
-
-            let mut enums = self.0.enums.borrow_mut();
-            for ed in self.descriptor().enums() {
-                let e = Enum::new(ed, container.clone());
-                enums.push(e);
-            }
+            let enums: Vec<_> = self
+                .descriptor()
+                .enums()
+                .map(|ed| Enum::new(ed, container.clone()))
+                .collect();
+            self.0.enums.borrow_mut().extend(enums);
         }
         self
     }
     fn hydrate_services(self) -> Self {
         {
-            let mut services = self.0.services.borrow_mut();
-            for sd in self.descriptor().services() {
-                let svc = Service::new(sd, self.clone());
-                services.push(svc);
-            }
+            let services: Vec<_> = self
+                .descriptor()
+                .services()
+                .map(|sd| Service::new(sd, self.clone()))
+                .collect();
+            self.0.services.borrow_mut().extend(services);
         }
         self
     }
@@ -406,7 +432,7 @@ impl File {
         self.clone().into()
     }
     pub fn name(&self) -> &str {
-        &self.0.descriptor.name()
+        self.0.descriptor.name()
     }
     pub fn package(&self) -> Package {
         self.0.pkg.clone().into()
@@ -415,17 +441,24 @@ impl File {
         self.0.build_target
     }
     pub fn comments(&self) -> Comments {
-        *self.0.comments.borrow()
+        self.0.comments.borrow().clone()
+    }
+    /// Returns the custom option `T` set on the file; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
+    }
+    pub fn options(&self) -> Options<'_> {
+        self.0.descriptor.proto().options.as_ref().into()
     }
     pub fn file_path(&self) -> &PathBuf {
         &self.0.file_path
     }
     /// Returns comments attached to the package in this File if any exist.
     pub fn package_comments(&self) -> Comments {
-        *self.0.pkg_comments.borrow()
+        self.0.pkg_comments.borrow().clone()
     }
     pub fn descriptor(&self) -> FileDescriptor {
-        self.0.descriptor
+        self.0.descriptor.clone()
     }
     pub(crate) fn set_comments(&self, comments: Comments) {
         *self.0.comments.borrow_mut() = comments;
@@ -433,6 +466,19 @@ impl File {
     pub(crate) fn set_package_comments(&self, comments: Comments) {
         *self.0.pkg_comments.borrow_mut() = comments;
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        let d = &self.0;
+        d.messages.freeze();
+        d.enums.freeze();
+        d.services.freeze();
+        d.defined_extensions.freeze();
+        d.pkg_comments.freeze();
+        d.comments.freeze();
+        d.dependents.freeze();
+        d.imports.freeze();
+        d.used_imports.freeze();
+    }
     pub fn message(&self, name: &str) -> Option<Message> {
         let name = name.to_lowercase();
         self.all_messages().find(|m| {
@@ -484,6 +530,68 @@ impl File {
         self.0.file_path.clone()
     }
 
+    /// This file followed by its transitive imports.
+    fn visible_files(&self) -> impl Iterator<Item = File> {
+        std::iter::once(self.clone()).chain(self.transitive_imports())
+    }
+
+    /// Returns the extension with the fully qualified name `fqn` if it is
+    /// defined in this file or one of its transitive imports.
+    ///
+    /// The extensions are collected on first use; the file's imports must be
+    /// hydrated by then.
+    pub(crate) fn visible_extension(&self, fqn: &str) -> Option<Extension> {
+        self.0
+            .visible_extensions
+            .get_or_init(|| {
+                self.visible_files()
+                    .flat_map(|file| {
+                        let nested = file.all_messages().flat_map(|m| m.defined_extensions());
+                        file.defined_extensions().chain(nested)
+                    })
+                    .map(|ext| (ext.fully_qualified_name().to_string(), ext))
+                    .collect()
+            })
+            .get(fqn)
+            .cloned()
+    }
+
+    /// Returns `true` if `fqn` names a symbol defined in this file or one of
+    /// its transitive imports, or one of their packages or parent packages.
+    ///
+    /// Enum values are also symbols of the scope enclosing their enum, as in
+    /// protoc. The symbols are collected on first use.
+    pub(crate) fn is_visible_symbol(&self, fqn: &str) -> bool {
+        self.0
+            .visible_symbols
+            .get_or_init(|| {
+                let mut symbols = HashSet::new();
+                for file in self.visible_files() {
+                    let mut package = String::new();
+                    for part in file
+                        .descriptor()
+                        .package()
+                        .split('.')
+                        .filter(|p| !p.is_empty())
+                    {
+                        package = format!("{package}.{part}");
+                        symbols.insert(package.clone());
+                    }
+                    for node in file.all_nodes() {
+                        if let Node::EnumValue(value) = &node {
+                            let enum_ = value.enum_();
+                            let fqn = enum_.fully_qualified_name();
+                            let scope = fqn.rsplit_once('.').map_or("", |(s, _)| s);
+                            symbols.insert(format!("{scope}.{}", value.name()));
+                        }
+                        symbols.insert(node.fully_qualified_name().to_string());
+                    }
+                }
+                symbols
+            })
+            .contains(fqn)
+    }
+
     pub(crate) fn add_import(&self, file: File) {
         self.0.imports.borrow_mut().push(file.into());
     }
@@ -492,7 +600,7 @@ impl File {
         self.0
             .used_imports
             .borrow_mut()
-            .insert(file.fully_qualified_name().to_string());
+            .insert(file.name().to_string());
     }
 
     pub(crate) fn add_dependent(&self, file: File) {
@@ -516,9 +624,9 @@ impl File {
     pub fn unused_imports(&self) -> Vec<File> {
         let used_imports = self.0.used_imports.borrow();
         let mut unused_imports = Vec::new();
-        for file in self.0.imports.borrow().iter() {
-            if !used_imports.contains(&file.fully_qualified_name()) {
-                unused_imports.push(file.into());
+        for file in self.0.imports.borrow().iter().map(File::from) {
+            if !used_imports.contains(file.name()) {
+                unused_imports.push(file);
             }
         }
         unused_imports
@@ -555,6 +663,13 @@ impl File {
                     .get(next)
                     .cloned()
                     .map(Node::Service),
+                FileDescriptorPath::Extension => self
+                    .0
+                    .defined_extensions
+                    .borrow()
+                    .get(next)
+                    .cloned()
+                    .map(Node::Extension),
                 _ => None,
             }
             .and_then(|n| n.node_at_path(&path[2..]))
@@ -593,9 +708,6 @@ impl PartialEq<WeakFile> for File {
 pub(crate) struct WeakFile(Weak<Detail>);
 
 impl WeakFile {
-    pub fn fully_qualified_name(&self) -> &str {
-        self.upgrade().fully_qualified_name()
-    }
     pub fn package(&self) -> Package {
         self.upgrade().package()
     }
@@ -635,7 +747,7 @@ pub struct TransitiveImports {
     processed: HashSet<String>,
 }
 impl TransitiveImports {
-    pub(crate) fn new(files: RefCell<Vec<WeakFile>>) -> Self {
+    pub(crate) fn new(files: Rc<RefCell<Vec<WeakFile>>>) -> Self {
         Self {
             queue: VecDeque::from_iter(files.borrow().iter().map(|f| f.into())),
             processed: HashSet::new(),
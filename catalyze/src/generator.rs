@@ -1,422 +1,1200 @@
-use crate::Ast;
-use crate::{Artifact, File};
+use crate::error::Error;
+#[cfg(feature = "parallel")]
+use crate::GenerateFile;
+use crate::{
+    Artifact, Ast, Features, File, FromParameters, Generate, Generator, Input, ProtocPlugin,
+    Standalone, Version, Warning,
+};
+use protobuf::plugin::{CodeGeneratorRequest, CodeGeneratorResponse};
 use protobuf::Message;
 use std::collections::HashMap;
-use std::{
-    fs,
-    io::{self},
-    io::{BufReader, BufWriter, Read, Write},
-    path::Path,
-    path::PathBuf,
-};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Object-safe counterpart of [`Generate`], allowing a `Generator` to hold
+/// any number of implementations regardless of their `Error` type.
+///
+/// The returned artifacts are [formatted](Artifact::format), with any
+/// resulting warnings pushed onto `warnings`.
+pub(crate) trait DynGenerate {
+    fn name(&self) -> &str;
+    fn generate(
+        self: Box<Self>,
+        targets: HashMap<String, File>,
+        ast: Ast,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<Artifact>, Error>;
+}
+
+impl<G> DynGenerate for G
+where
+    G: Generate,
+    G::Error: 'static + std::error::Error + Send + Sync,
+{
+    fn name(&self) -> &str {
+        Generate::name(self)
+    }
+    fn generate(
+        self: Box<Self>,
+        targets: HashMap<String, File>,
+        ast: Ast,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<Artifact>, Error> {
+        let module = Generate::name(&*self).to_string();
+        let mut artifacts =
+            Generate::generate(*self, targets, ast).map_err(|err| Error::FailedToExecute {
+                module,
+                source: Box::new(err),
+            })?;
+        warnings.extend(artifacts.iter_mut().filter_map(Artifact::format));
+        Ok(artifacts)
+    }
+}
+
+/// A module registered under an explicit name, which replaces the name of the
+/// wrapped implementation for tagging and error attribution.
+struct Module {
+    name: String,
+    generator: Box<dyn DynGenerate>,
+}
+
+impl DynGenerate for Module {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn generate(
+        self: Box<Self>,
+        targets: HashMap<String, File>,
+        ast: Ast,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<Artifact>, Error> {
+        let Module { name, generator } = *self;
+        generator
+            .generate(targets, ast, warnings)
+            .map_err(|err| match err {
+                Error::FailedToExecute { source, .. } => Error::FailedToExecute {
+                    module: name,
+                    source,
+                },
+                err => err,
+            })
+    }
+}
+
+/// Adapts a [`GenerateFile`] to [`DynGenerate`], generating each target file
+/// on the rayon thread pool. Artifacts are returned in the order of
+/// [`Ast::target_files`], regardless of the order in which files complete. If
+/// more than one file fails, the error of the earliest file is returned.
+#[cfg(feature = "parallel")]
+struct PerFile<G>(G);
+
+#[cfg(feature = "parallel")]
+impl<G: GenerateFile> DynGenerate for PerFile<G> {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+    fn generate(
+        self: Box<Self>,
+        _targets: HashMap<String, File>,
+        ast: Ast,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<Artifact>, Error> {
+        use rayon::prelude::*;
+
+        let generator = &self.0;
+        let files: Vec<File> = ast.target_files().collect();
+        let rendered: Vec<Result<Vec<_>, G::Error>> = files
+            .into_par_iter()
+            .map(|file| {
+                generator
+                    .generate_file(file, &ast)
+                    .map(|artifacts| artifacts.into_iter().map(Artifact::render).collect())
+            })
+            .collect();
+        let rendered = rendered
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::FailedToExecute {
+                module: generator.name().to_string(),
+                source: Box::new(err),
+            })?;
+        Ok(rendered
+            .into_iter()
+            .flatten()
+            .map(|(rendered, warning)| {
+                warnings.extend(warning);
+                Artifact::from(rendered)
+            })
+            .collect())
+    }
+}
+
+impl<I: Input> Generator<I> {
+    pub fn new(input: I) -> Self {
+        Self {
+            input,
+            generators: Vec::new(),
+            custom_roots: Vec::new(),
+            minimum_protoc_version: None,
+            features: Features::default(),
+        }
+    }
+
+    /// Registers a [`Generate`] implementation as a module. Modules are
+    /// executed in the order they are registered, each against the same
+    /// [`Ast`], and every [`Artifact`] a module produces is tagged with its
+    /// [`name`](Generate::name).
+    pub fn generator<G>(mut self, generator: G) -> Self
+    where
+        G: 'static + Generate,
+        G::Error: 'static + std::error::Error + Send + Sync,
+    {
+        self.generators.push(Box::new(generator));
+        self
+    }
+
+    /// Registers a [`Generate`] implementation as a module named `name`,
+    /// which is used in place of [`Generate::name`] to tag its artifacts and
+    /// attribute its errors.
+    ///
+    /// ```no_run
+    /// # use catalyze::{Artifact, Ast, File, Generate, Generator};
+    /// # use std::{collections::HashMap, convert::Infallible};
+    /// # struct Messages;
+    /// # struct Grpc;
+    /// # impl Generate for Messages {
+    /// #     type Error = Infallible;
+    /// #     fn generate(self, _: HashMap<String, File>, _: Ast) -> Result<Vec<Artifact>, Infallible> { Ok(vec![]) }
+    /// # }
+    /// # impl Generate for Grpc {
+    /// #     type Error = Infallible;
+    /// #     fn generate(self, _: HashMap<String, File>, _: Ast) -> Result<Vec<Artifact>, Infallible> { Ok(vec![]) }
+    /// # }
+    /// Generator::new_protoc_plugin(std::io::stdin().lock())?
+    ///     .module("messages", Messages)
+    ///     .module("grpc", Grpc)
+    ///     .render(std::io::stdout().lock())?;
+    /// # Ok::<(), catalyze::Error>(())
+    /// ```
+    pub fn module<G>(mut self, name: impl Into<String>, generator: G) -> Self
+    where
+        G: 'static + Generate,
+        G::Error: 'static + std::error::Error + Send + Sync,
+    {
+        self.generators.push(Box::new(Module {
+            name: name.into(),
+            generator: Box::new(generator),
+        }));
+        self
+    }
+
+    /// Registers a [`GenerateFile`] implementation as a module. The module is
+    /// executed in registration order alongside other modules, but each of its
+    /// target files is generated concurrently. The resulting artifacts are
+    /// ordered by target file, as in [`Ast::target_files`].
+    #[cfg(feature = "parallel")]
+    pub fn file_generator<G>(mut self, generator: G) -> Self
+    where
+        G: 'static + GenerateFile,
+    {
+        self.generators.push(Box::new(PerFile(generator)));
+        self
+    }
+
+    /// Registers a [`GenerateFile`] implementation as a module named `name`;
+    /// see [`module`](Self::module) and [`file_generator`](Self::file_generator).
+    #[cfg(feature = "parallel")]
+    pub fn file_module<G>(mut self, name: impl Into<String>, generator: G) -> Self
+    where
+        G: 'static + GenerateFile,
+    {
+        self.generators.push(Box::new(Module {
+            name: name.into(),
+            generator: Box::new(PerFile(generator)),
+        }));
+        self
+    }
+
+    /// Allows [`Content::Custom`](crate::Content::Custom) artifacts to be
+    /// written under `root`. Custom artifacts are written directly to disk,
+    /// bypassing protoc, and are rejected unless they resolve to a path under
+    /// an allowed root.
+    pub fn allow_custom_root(mut self, root: impl AsRef<Path>) -> Self {
+        self.custom_roots.push(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Refuses to execute if the input was produced by a version of protoc
+    /// earlier than `minimum`. Inputs without a version, such as
+    /// [`Standalone`], are not checked.
+    pub fn require_protoc_version(mut self, minimum: Version) -> Self {
+        self.minimum_protoc_version = Some(minimum);
+        self
+    }
+
+    /// Sets the [`Features`] advertised to protoc in plugin mode. Defaults to
+    /// supporting proto3 `optional`.
+    pub fn features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn input(&self) -> &I {
+        &self.input
+    }
+
+    pub fn parameter(&self) -> &I::Parameter {
+        self.input.parameter()
+    }
+
+    /// Builds the [`Ast`] and executes each generator against it, returning
+    /// the accumulated [`Artifact`]s tagged with the name of the generator
+    /// which produced them, along with any [`Warning`]s.
+    fn execute(&mut self) -> Result<(Vec<Artifact>, Vec<Warning>), Error> {
+        if let (Some(minimum), Some(version)) =
+            (&self.minimum_protoc_version, self.input.protoc_version())
+        {
+            if !version.at_least(minimum) {
+                return Err(Error::UnsupportedProtocVersion {
+                    version: Box::new(version),
+                    minimum: Box::new(minimum.clone()),
+                });
+            }
+        }
+        let ast = Ast::new(&self.input)?;
+        let mut artifacts = Vec::new();
+        let mut warnings = Vec::new();
+        for generator in self.generators.drain(..) {
+            let name = generator.name().to_string();
+            let generated =
+                generator.generate(ast.target_file_map(), ast.clone(), &mut warnings)?;
+            for mut artifact in generated {
+                if !artifact.tags.contains(&name) {
+                    artifact.tags.push(name.clone());
+                }
+                artifacts.push(artifact);
+            }
+        }
+        Ok((artifacts, warnings))
+    }
+}
+
+impl Generator<ProtocPlugin> {
+    /// Returns a new `Generator` for a protoc plugin, decoding the
+    /// `CodeGeneratorRequest` from `input`.
+    ///
+    /// ```no_run
+    /// let generator = catalyze::Generator::new_protoc_plugin(std::io::stdin().lock())?;
+    /// generator.render(std::io::stdout().lock())?;
+    /// # Ok::<(), catalyze::Error>(())
+    /// ```
+    pub fn new_protoc_plugin<R: Read>(input: R) -> Result<Self, Error> {
+        Self::new_protoc_plugin_with_parameter(input)
+    }
+}
+
+impl<P: FromParameters> Generator<ProtocPlugin<P>> {
+    /// Returns a new `Generator` for a protoc plugin, decoding the
+    /// `CodeGeneratorRequest` from `input` and converting its parameter into
+    /// `P`.
+    ///
+    /// ```no_run
+    /// use catalyze::{Error, FromParameters, Generator, Parameters, ProtocPlugin};
+    ///
+    /// struct Params {
+    ///     out_dir: Option<String>,
+    ///     serde: bool,
+    /// }
+    ///
+    /// impl FromParameters for Params {
+    ///     fn from_parameters(params: &Parameters) -> Result<Self, Error> {
+    ///         params.deny_unknown(&["out_dir", "serde"])?;
+    ///         Ok(Self {
+    ///             out_dir: params.value("out_dir")?,
+    ///             serde: params.flag("serde")?,
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let generator: Generator<ProtocPlugin<Params>> =
+    ///     Generator::new_protoc_plugin_with_parameter(std::io::stdin().lock())?;
+    /// generator.render(std::io::stdout().lock())?;
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// Errors decoding the request or converting its parameter are returned
+    /// rather than reported to protoc; see [`run`](Self::run).
+    pub fn new_protoc_plugin_with_parameter<R: Read>(mut input: R) -> Result<Self, Error> {
+        let request = CodeGeneratorRequest::parse_from_reader(&mut input)
+            .map_err(|source| Error::DecodeRequest { source })?;
+        Ok(Self::new(ProtocPlugin::new(request)?))
+    }
+
+    /// Runs a protoc plugin: decodes the `CodeGeneratorRequest` from `input`,
+    /// configures the `Generator` with `setup` and [renders](Self::render) the
+    /// `CodeGeneratorResponse` to `output`.
+    ///
+    /// Unlike [`new_protoc_plugin_with_parameter`](Self::new_protoc_plugin_with_parameter),
+    /// errors decoding the request or converting its parameter into `P` are
+    /// reported to protoc through the `error` field of the response.
+    ///
+    /// ```no_run
+    /// use catalyze::{Generator, Parameters, ProtocPlugin};
+    ///
+    /// Generator::<ProtocPlugin<Parameters>>::run(
+    ///     std::io::stdin().lock(),
+    ///     std::io::stdout().lock(),
+    ///     |generator| generator,
+    /// )?;
+    /// # Ok::<(), catalyze::Error>(())
+    /// ```
+    pub fn run<R, W, F>(input: R, output: W, setup: F) -> Result<Vec<Warning>, Error>
+    where
+        R: Read,
+        W: Write,
+        F: FnOnce(Self) -> Self,
+    {
+        match Self::new_protoc_plugin_with_parameter(input) {
+            Ok(generator) => setup(generator).render(output),
+            Err(err) => {
+                let mut response = CodeGeneratorResponse::new();
+                response.set_error(response_error(err));
+                write_response(&response, output)?;
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+impl<P> Generator<ProtocPlugin<P>> {
+    /// Executes the generators and writes the resulting
+    /// `CodeGeneratorResponse` to `output`.
+    ///
+    /// Errors encountered while building the [`Ast`] or executing the
+    /// generators are reported to protoc through the `error` field of the
+    /// response, in which case `render` still returns `Ok`. Only failures to
+    /// write the response are returned.
+    ///
+    /// Returns the [`Warning`]s raised while rendering the artifacts, which
+    /// protoc has no means of reporting; a plugin typically writes them to
+    /// stderr.
+    ///
+    /// [`Content::Custom`](crate::Content::Custom) artifacts are not part of
+    /// the response; they are written directly to disk, relative to the
+    /// current directory. None are written unless every one of them resolves
+    /// to a path under a root allowed by
+    /// [`allow_custom_root`](Self::allow_custom_root).
+    pub fn render<W: Write>(mut self, output: W) -> Result<Vec<Warning>, Error> {
+        let mut response = CodeGeneratorResponse::new();
+        self.features.apply(&mut response);
+        let warnings = match self.build_response(&mut response) {
+            Ok(warnings) => warnings,
+            Err(err) => {
+                response.file.clear();
+                response.set_error(response_error(err));
+                Vec::new()
+            }
+        };
+        write_response(&response, output)?;
+        Ok(warnings)
+    }
+
+    fn build_response(
+        &mut self,
+        response: &mut CodeGeneratorResponse,
+    ) -> Result<Vec<Warning>, Error> {
+        let (artifacts, warnings) = self.execute()?;
+        let (custom, artifacts): (Vec<_>, Vec<_>) =
+            artifacts.into_iter().partition(Artifact::is_custom);
+        for artifact in &custom {
+            artifact.check_allowed(Path::new(""), &self.custom_roots)?;
+        }
+        for artifact in custom {
+            artifact.write(Path::new(""))?;
+        }
+        for artifact in artifacts {
+            artifact.add_to_response(response);
+        }
+        Ok(warnings)
+    }
+}
+
+fn write_response<W: Write>(response: &CodeGeneratorResponse, mut output: W) -> Result<(), Error> {
+    response
+        .write_to_writer(&mut output)
+        .map_err(|source| Error::EncodeResponse { source })?;
+    output.flush().map_err(|source| Error::EncodeResponse {
+        source: source.into(),
+    })
+}
+
+/// Formats `err` for `CodeGeneratorResponse.error`. Errors returned from a
+/// generator are prefixed with the name of the generator.
+fn response_error(err: Error) -> String {
+    match err {
+        Error::FailedToExecute { module, source } => format!("{module}: {source}"),
+        err => err.to_string(),
+    }
+}
+
+impl Generator<Standalone> {
+    /// Returns a new `Generator` which decodes a `FileDescriptorSet` from
+    /// `input` and writes the generated artifacts to `output_path`.
+    ///
+    /// `targets` are the names of the proto files, as they appear in the
+    /// `FileDescriptorSet`, to generate code for.
+    pub fn new_standalone<R, T, O>(input: R, targets: T, output_path: O) -> Result<Self, Error>
+    where
+        R: Read,
+        T: IntoIterator,
+        T::Item: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Ok(Self::new(Standalone::new(input, targets, output_path, ())?))
+    }
+
+    /// Creates a `Generator` which parses the proto files `inputs`, resolving
+    /// imports from `includes`, without `protoc`. See [`Standalone::parse`].
+    #[cfg(feature = "parse")]
+    pub fn new_parsed<I, T, O>(includes: I, inputs: T, output_path: O) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+        T: IntoIterator,
+        T::Item: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Ok(Self::new(Standalone::parse(
+            includes,
+            inputs,
+            output_path,
+            (),
+        )?))
+    }
+}
+
+impl<P> Generator<Standalone<P>> {
+    /// Executes the generators and writes each resulting [`Artifact`] to
+    /// disk, relative to the output path.
+    ///
+    /// [`Content::Custom`](crate::Content::Custom) artifacts must resolve to a
+    /// path under a root allowed by [`allow_custom_root`](Self::allow_custom_root);
+    /// all others must stay within the output path. Nothing is written unless
+    /// every artifact passes.
+    ///
+    /// Returns the [`Warning`]s raised while rendering the artifacts.
+    pub fn render(mut self) -> Result<Vec<Warning>, Error> {
+        let (artifacts, warnings) = self.execute()?;
+        let output_path = self.input.output_path();
+        for artifact in &artifacts {
+            if artifact.is_custom() {
+                artifact.check_allowed(output_path, &self.custom_roots)?;
+            } else {
+                artifact.check_relative(output_path)?;
+            }
+        }
+        for artifact in artifacts {
+            artifact.write(output_path)?;
+        }
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::field::{Field, Key, MapField, RepeatedField, Scalar};
+    use crate::node::Node;
+    use crate::well_known::WellKnownMessage;
+    use crate::*;
+    use protobuf::plugin::{CodeGeneratorRequest, CodeGeneratorResponse};
+    use protobuf::{Message, UnknownValueRef};
+    use std::{collections::HashMap, convert::Infallible, env, fs, io::Cursor, path::PathBuf};
+
+    struct Mod;
+    impl Generate for Mod {
+        type Error = Infallible;
+
+        fn generate(
+            self,
+            targets: HashMap<String, File>,
+            ast: Ast,
+        ) -> Result<Vec<Artifact>, Self::Error> {
+            assert_eq!(targets.len(), 1);
+            assert!(targets.contains_key("kitchen/kitchen.proto"));
+            let sink = ast.node(".kitchen.Sink").unwrap();
+            assert!(matches!(sink, Node::Message(_)));
+            let sink_proto = ast.file("kitchen/sink.proto").unwrap();
+
+            let kitchen_proto = ast.file("kitchen/kitchen.proto").unwrap();
+            let kitchen = kitchen_proto.message("Kitchen").expect("Kitchen not found");
+            let color = kitchen_proto.message("Color").expect("Color not found");
+
+            let style_field = kitchen.field("style").expect("style not found");
+            assert_eq!(style_field.name(), "style");
+            match style_field {
+                Field::Enum(e) => {
+                    let style = e.enum_();
+                    assert_eq!(style.name(), "Style");
+                }
+                _ => panic!("style should be an enum"),
+            }
+
+            let sink_field = kitchen.field("sink").expect("sink not found");
+            assert_eq!(sink_field.name(), "sink");
+            assert!(!sink_field.is_well_known_type());
+            assert!(sink_field.is_embed());
+
+            let dish_counts_field = kitchen.field("dish_counts").expect("dish_counts not found");
+            assert!(dish_counts_field.is_map());
+            assert_eq!(dish_counts_field.name(), "dish_counts");
+
+            let utensils_field = kitchen.field("utensils").expect("utensils not found");
+            assert_eq!("utensils", utensils_field.name());
+            assert!(utensils_field.is_repeated());
+            assert!(utensils_field.is_scalar());
+
+            match utensils_field {
+                Field::Repeated(utensils_field) => {
+                    assert_eq!(utensils_field.name(), "utensils");
+                    assert!(utensils_field.is_scalar());
+                    match utensils_field {
+                        RepeatedField::Scalar(utensils_field) => {
+                            assert_eq!(utensils_field.scalar(), Scalar::String);
+                        }
+                        _ => panic!("utensils should be a repeated scalar field"),
+                    }
+                }
+                _ => panic!("utensils should be a repeated field"),
+            }
+
+            match dish_counts_field {
+                Field::Map(dish_counts) => match dish_counts {
+                    MapField::Scalar(scf) => {
+                        assert_eq!(scf.name(), "dish_counts");
+                        assert_eq!(scf.key(), Key::String);
+                        assert_eq!(scf.scalar(), Scalar::Uint32);
+                    }
+                    _ => panic!("map field should be scalar"),
+                },
+                _ => panic!("dish_counts should be a map"),
+            }
+
+            let wall_colors_field = kitchen.field("wall_colors").expect("wall_colors not found");
+            match wall_colors_field {
+                Field::Repeated(wall_colors_field) => match wall_colors_field {
+                    RepeatedField::Embed(wall_colors_field) => {
+                        let color_embed = wall_colors_field.embed();
+                        assert_eq!(color_embed, color);
+                        assert_eq!(color_embed.name(), "Color");
+                    }
+                    _ => panic!("wall_colors should be a repeated embed field"),
+                },
+                _ => panic!("wall_colors should be repeated"),
+            }
+            let appliance_colors = kitchen
+                .field("appliance_colors")
+                .expect("appliance_colors not found");
+
+            match appliance_colors {
+                Field::Map(appliance_colors) => match appliance_colors {
+                    MapField::Embed(appliance_colors) => {
+                        let color_embed = appliance_colors.embed();
+                        assert_eq!(color_embed, color);
+                        assert_eq!(color_embed.name(), "Color");
+                    }
+                    _ => panic!("appliance_colors should be a mapped embed field"),
+                },
+                _ => panic!("appliance_colors should be a map"),
+            }
+
+            let sink = sink_proto.message("Sink").expect("Sink not found");
+            let brand = sink.enum_("Brand").expect("Brand not found");
+            let brand_field = sink.field("brand").expect("brand not found");
+
+            assert!(brand_field.is_enum());
+            assert_eq!(brand_field.name(), "brand");
+            match brand_field {
+                Field::Enum(brand_field) => {
+                    assert_eq!(brand, brand_field.enum_());
+                    assert_eq!(brand_field.name(), "brand");
+                    assert_eq!(brand, brand_field.enum_());
+                }
+                _ => panic!("brand should be an enum"),
+            }
+
+            let material_field = sink.field("material").expect("material not found");
+
+            assert!(material_field.is_embed());
+            assert_eq!(material_field.name(), "material");
+
+            let material = sink_proto.message("Material").expect("Material not found");
+
+            match material_field {
+                Field::Embed(material_field) => {
+                    let material_embed = material_field.embed();
+                    assert_eq!(material_embed, material);
+                    assert_eq!(material_embed.name(), "Material");
+                }
+                _ => panic!("material should be an embed field"),
+            }
+
+            let model_field = sink.field("model").expect("model not found");
+            assert_eq!(model_field.name(), "model");
+            assert!(model_field.is_scalar());
+            match model_field {
+                Field::Scalar(model_field) => {
+                    assert_eq!(model_field.scalar(), Scalar::String);
+                }
+                _ => panic!("model should be a scalar field"),
+            }
+            let basin_count_field = sink.field("basin_count").expect("basin_count not found");
+            assert_eq!(basin_count_field.name(), "basin_count");
+            assert!(basin_count_field.is_scalar());
+            assert_eq!(basin_count_field.number(), 4);
+            match basin_count_field {
+                Field::Scalar(basin_count_field) => {
+                    assert_eq!(basin_count_field.scalar(), Scalar::Uint32);
+                }
+                _ => panic!("basin_count should be a scalar field"),
+            }
+
+            let installed_field = sink.field("installed").expect("installed not found");
+
+            assert_eq!(installed_field.name(), "installed");
+
+            assert!(
+                installed_field.is_embed(),
+                "installed_field's google.protobuf.Timestamp should be an embedded message"
+            );
+            match installed_field {
+                Field::Embed(installed_field) => {
+                    // println!("{:#?}", installed_field.embed().package());
+
+                    assert!(
+                        installed_field.is_well_known_type(),
+                        "installed_field is a google.protobuf.Timestamp which should be a well-known-type"
+                    );
+
+                    let wkt = installed_field
+                        .embed()
+                        .well_known_message()
+                        .expect("google.protobuf.Timestamp should be well-known");
+                    assert_eq!(wkt, WellKnownMessage::Timestamp);
+                }
+                _ => panic!("installed should be an embed field"),
+            }
+
+            let brand = sink.enum_("Brand").expect("Brand not found");
+            assert_eq!(brand.value("KRAUS").expect("KRAUS not found"), 0);
+            assert_eq!(brand.value("SWANSTONE").expect("SWANSTONE not found"), 1);
+            assert_eq!(brand.value("HOUZER").expect("HOUZER not found"), 2);
+            assert_eq!(brand.value("BLANCO").expect("BLANCO not found"), 3);
+            assert_eq!(brand.value("KOHLER").expect("KOHLER not found"), 4);
+
+            let cooking_service = kitchen_proto.service("Cooking").expect("Cooking not found");
+            let saute_method = cooking_service.method("Saute").expect("Saute not found");
+            let saute_request = kitchen_proto
+                .message("SauteRequest")
+                .expect("SauteRequest not found");
+            let saute_response = kitchen_proto
+                .message("SauteResponse")
+                .expect("SauteResponse not found");
+            assert_eq!(saute_method.name(), "Saute");
+            assert_eq!(saute_method.input(), saute_request);
+            assert_eq!(saute_method.output(), saute_response);
+            assert!(
+                !saute_method.is_bidirectional_streaming(),
+                "Saute should not be bidirectional streaming"
+            );
+            assert!(
+                !saute_method.is_client_streaming(),
+                "Saute should not be client streaming"
+            );
+            assert!(
+                !saute_method.is_server_streaming(),
+                "Saute should not be server streaming"
+            );
+            let ice_request = kitchen_proto
+                .message("IceRequest")
+                .expect("IceRequest not found");
+            let ice_response = kitchen_proto
+                .message("IceResponse")
+                .expect("IceResponse not found");
+
+            let dispense_ice_method = cooking_service
+                .method("DispenseIce")
+                .expect("DispenseIce not found");
+
+            assert_eq!(dispense_ice_method.input(), ice_request);
+            assert_eq!(dispense_ice_method.output(), ice_response);
+            assert!(
+                !dispense_ice_method.is_client_streaming(),
+                "DispenseIce should not be client streaming"
+            );
+            assert!(
+                dispense_ice_method.is_server_streaming(),
+                "DispenseIce should be server streaming"
+            );
+            assert!(
+                !dispense_ice_method.is_bidirectional_streaming(),
+                "DispenseIce should not be bidirectional streaming"
+            );
+
+            let load_fridge_method = cooking_service
+                .method("LoadFridge")
+                .expect("LoadFridge not found");
+
+            assert!(
+                load_fridge_method.is_client_streaming(),
+                "LoadFridge should be client streaming"
+            );
+            assert!(
+                !load_fridge_method.is_server_streaming(),
+                "LoadFridge should not be server streaming"
+            );
+
+            let order_drinks_method = cooking_service
+                .method("OrderDrinks")
+                .expect("OrderDrinks not found");
+            assert!(
+                order_drinks_method.is_bidirectional_streaming(),
+                "OrderDrinks should be bi-directional streaming"
+            );
+
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn test_new_protoc_plugin_generator() {
+        let mut output = Vec::new();
+        Generator::new_protoc_plugin(Cursor::new(test_util::kitchen_request()))
+            .unwrap()
+            .generator(Mod {})
+            .render(&mut output)
+            .unwrap();
+        let response = CodeGeneratorResponse::parse_from_bytes(&output).unwrap();
+        assert!(response.error.is_none());
+        assert_eq!(response.supported_features(), 1);
+    }
+
+    #[test]
+    fn test_plugin_features() {
+        let render = |features| {
+            let mut output = Vec::new();
+            Generator::new_protoc_plugin(Cursor::new(test_util::kitchen_request()))
+                .unwrap()
+                .features(features)
+                .generator(Mod)
+                .render(&mut output)
+                .unwrap();
+            CodeGeneratorResponse::parse_from_bytes(&output).unwrap()
+        };
+        let edition = |response: &CodeGeneratorResponse, number| match response
+            .unknown_fields()
+            .get(number)
+        {
+            Some(UnknownValueRef::Varint(value)) => Some(value),
+            None => None,
+            Some(value) => panic!("unexpected value for field {number}: {value:?}"),
+        };
+
+        let response = render(Features::default().editions(Edition::Proto2, Edition::Edition2023));
+        assert_eq!(response.supported_features(), 0b11);
+        assert_eq!(edition(&response, 3), Some(Edition::Proto2 as u64));
+        assert_eq!(edition(&response, 4), Some(Edition::Edition2023 as u64));
+
+        let response =
+            render(Features::none().editions(Edition::Edition2023, Edition::Edition2024));
+        assert_eq!(response.supported_features(), 0b10);
+        assert_eq!(edition(&response, 3), Some(Edition::Edition2023 as u64));
+        assert_eq!(edition(&response, 4), Some(Edition::Edition2024 as u64));
+
+        let response = render(Features::none());
+        assert_eq!(response.supported_features(), 0);
+        assert_eq!(edition(&response, 3), None);
+        assert_eq!(edition(&response, 4), None);
+    }
+
+    #[test]
+    fn test_new_standalone_generator() {
+        let output_path = env::temp_dir().join("catalyze-test-standalone");
+        Generator::new_standalone(
+            test_util::kitchen_bin(),
+            ["kitchen/kitchen.proto"],
+            &output_path,
+        )
+        .unwrap()
+        .generator(Mod {})
+        .render()
+        .unwrap();
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn test_new_parsed_generator() {
+        let root = test_util::fixture("proto");
+        let generator = Generator::new_parsed(
+            [&root],
+            [root.join("kitchen/kitchen.proto")],
+            env::temp_dir().join("catalyze-test-parsed"),
+        )
+        .unwrap();
+        assert_eq!(generator.input().targets(), ["kitchen/kitchen.proto"]);
+        let ast = Ast::new(generator.input()).unwrap();
+        assert!(ast.file("kitchen/kitchen.proto").unwrap().build_target());
+    }
+
+    struct Failing;
+    impl Generate for Failing {
+        type Error = NodeError;
+        fn generate(
+            self,
+            _targets: HashMap<String, File>,
+            ast: Ast,
+        ) -> Result<Vec<Artifact>, Self::Error> {
+            let file = ast.target_files().next().unwrap();
+            Err(NodeError::new(file, "unsupported"))
+        }
+    }
+
+    #[test]
+    fn test_unsupported_protoc_version() {
+        let render = |suffix: &str| {
+            let mut request =
+                CodeGeneratorRequest::parse_from_bytes(&test_util::kitchen_request()).unwrap();
+            let version = request.compiler_version.mut_or_insert_default();
+            version.set_major(3);
+            version.set_minor(15);
+            version.set_patch(0);
+            version.set_suffix(suffix.to_string());
+            let input = Cursor::new(request.write_to_bytes().unwrap());
+            let mut output = Vec::new();
+            Generator::new_protoc_plugin(input)
+                .unwrap()
+                .require_protoc_version(Version::new(3, 15, None))
+                .generator(Mod)
+                .render(&mut output)
+                .unwrap();
+            CodeGeneratorResponse::parse_from_bytes(&output).unwrap()
+        };
+        let response = render("rc2");
+        assert!(response.file.is_empty());
+        assert_eq!(
+            response.error(),
+            "protoc 3.15.0-rc2 is not supported; 3.15 or later is required"
+        );
+        assert!(render("").error.is_none());
+    }
+
+    struct Strict;
+    impl FromParameters for Strict {
+        fn from_parameters(params: &Parameters) -> Result<Self, Error> {
+            params.deny_unknown(&["serde"])?;
+            Ok(Strict)
+        }
+    }
+
+    #[test]
+    fn test_run_parameter_error() {
+        let run = |parameter: &str| {
+            let mut request =
+                CodeGeneratorRequest::parse_from_bytes(&test_util::kitchen_request()).unwrap();
+            request.set_parameter(parameter.to_string());
+            let input = Cursor::new(request.write_to_bytes().unwrap());
+            let mut output = Vec::new();
+            Generator::<ProtocPlugin<Strict>>::run(input, &mut output, |g| g.generator(Mod))
+                .unwrap();
+            CodeGeneratorResponse::parse_from_bytes(&output).unwrap()
+        };
+        let response = run("serde,out_dir=src");
+        assert!(response.file.is_empty());
+        assert_eq!(
+            response.error(),
+            Error::UnknownParameter {
+                key: "out_dir".to_string()
+            }
+            .to_string()
+        );
+        assert!(run("serde").error.is_none());
+    }
+
+    #[test]
+    fn test_protoc_plugin_generator_error() {
+        let mut output = Vec::new();
+        Generator::new_protoc_plugin(Cursor::new(test_util::kitchen_request()))
+            .unwrap()
+            .generator(Failing)
+            .render(&mut output)
+            .unwrap();
+        let response = CodeGeneratorResponse::parse_from_bytes(&output).unwrap();
+        assert!(response.file.is_empty());
+        assert!(response.error().starts_with("Failing: "));
+        assert!(response.error().ends_with(": unsupported"));
+    }
+
+    /// Returns the artifacts it was created with.
+    struct Emit(Vec<Artifact>);
+    impl Generate for Emit {
+        type Error = Infallible;
+        fn generate(
+            self,
+            _targets: HashMap<String, File>,
+            _ast: Ast,
+        ) -> Result<Vec<Artifact>, Self::Error> {
+            Ok(self.0)
+        }
+    }
+
+    fn artifact(path: &str, content: Content) -> Artifact {
+        Artifact {
+            path: path.into(),
+            content,
+            op: Op::Create { overwrite: true },
+            tags: Vec::new(),
+        }
+    }
+
+    fn render(name: &str, artifacts: Vec<Artifact>) -> (PathBuf, Result<Vec<Warning>, Error>) {
+        let output_path = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&output_path);
+        let result = Generator::new_standalone(
+            test_util::kitchen_bin(),
+            ["kitchen/kitchen.proto"],
+            &output_path,
+        )
+        .unwrap()
+        .allow_custom_root(output_path.join("bin"))
+        .generator(Emit(artifacts))
+        .render();
+        (output_path, result)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_render_custom_artifact() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let permissions = fs::Permissions::from_mode(0o750);
+        let (output_path, result) = render(
+            "catalyze-test-custom-artifact",
+            vec![
+                artifact("gen/lib.rs", Content::String("mod a;".into())),
+                artifact(
+                    "bin/run.sh",
+                    Content::Custom(Some(permissions), b"#!/bin/sh".to_vec()),
+                ),
+            ],
+        );
+        result.unwrap();
+        let script = output_path.join("bin/run.sh");
+        assert_eq!(fs::read(&script).unwrap(), b"#!/bin/sh");
+        let mode = fs::metadata(&script).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        assert_eq!(
+            fs::read_to_string(output_path.join("gen/lib.rs")).unwrap(),
+            "mod a;"
+        );
+    }
+
+    #[test]
+    fn test_protoc_plugin_custom_artifacts() {
+        let root = env::temp_dir().join("catalyze-test-plugin-custom");
+        let _ = fs::remove_dir_all(&root);
+        let allowed = root.join("bin/run.sh");
+        let denied = root.join("run.sh");
+        let mut output = Vec::new();
+        Generator::new_protoc_plugin(Cursor::new(test_util::kitchen_request()))
+            .unwrap()
+            .allow_custom_root(root.join("bin"))
+            .generator(Emit(vec![
+                artifact(
+                    allowed.to_str().unwrap(),
+                    Content::Custom(None, b"a".to_vec()),
+                ),
+                artifact(
+                    denied.to_str().unwrap(),
+                    Content::Custom(None, b"b".to_vec()),
+                ),
+            ]))
+            .render(&mut output)
+            .unwrap();
+        let response = CodeGeneratorResponse::parse_from_bytes(&output).unwrap();
+        assert!(response.error().contains("run.sh"));
+        assert!(!allowed.exists());
+        assert!(!denied.exists());
+    }
+
+    #[cfg(feature = "format")]
+    #[test]
+    fn test_render_warnings() {
+        let tokens = |ts: &str| Content::TokenStream(ts.parse().unwrap());
+        let (output_path, result) = render(
+            "catalyze-test-warnings",
+            vec![
+                artifact("ok.rs", tokens("struct A ;")),
+                artifact("bad.rs", tokens("struct")),
+            ],
+        );
+        let warnings = result.unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].path, PathBuf::from("bad.rs"));
+        assert_eq!(
+            fs::read_to_string(output_path.join("ok.rs")).unwrap(),
+            "struct A;\n"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("bad.rs")).unwrap(),
+            "struct"
+        );
+    }
+
+    #[test]
+    fn test_module_tags_and_order() {
+        let string = |s: &str| Content::String(s.into());
+        let mut tagged = artifact("grpc/b.rs", string("b"));
+        tagged.tags.push("service".to_string());
+        let mut generator = Generator::new_standalone(
+            test_util::kitchen_bin(),
+            ["kitchen/kitchen.proto"],
+            env::temp_dir().join("catalyze-test-modules"),
+        )
+        .unwrap()
+        .module("messages", Emit(vec![artifact("messages.rs", string("m"))]))
+        .module(
+            "grpc",
+            Emit(vec![artifact("grpc/a.rs", string("a")), tagged]),
+        )
+        .generator(Emit(vec![artifact("validate.rs", string("v"))]));
+        let (artifacts, warnings) = generator.execute().unwrap();
+        assert!(warnings.is_empty());
+        let tagged: Vec<_> = artifacts
+            .iter()
+            .map(|a| (a.path.to_str().unwrap(), a.tags.clone()))
+            .collect();
+        assert_eq!(
+            tagged,
+            [
+                ("messages.rs", vec!["messages".to_string()]),
+                ("grpc/a.rs", vec!["grpc".to_string()]),
+                ("grpc/b.rs", vec!["service".to_string(), "grpc".to_string()]),
+                ("validate.rs", vec!["Emit".to_string()]),
+            ]
+        );
+    }
+
+    /// Generates an artifact named after each target file, finishing the
+    /// earlier files last, and fails for files whose name contains `fail`.
+    #[cfg(feature = "parallel")]
+    struct PerFileEmit {
+        fail: &'static str,
+    }
+
+    #[cfg(feature = "parallel")]
+    impl GenerateFile for PerFileEmit {
+        type Error = NodeError;
+        fn generate_file(&self, file: File, ast: &Ast) -> Result<Vec<Artifact>, Self::Error> {
+            let index = ast.target_files().position(|f| f == file).unwrap();
+            let delay = 20 * (ast.target_files().len() - index) as u64;
+            std::thread::sleep(std::time::Duration::from_millis(delay));
+            if !self.fail.is_empty() && file.name().contains(self.fail) {
+                return Err(NodeError::new(file, "unsupported"));
+            }
+            let path = format!("{}.rs", file.name());
+            Ok(vec![
+                artifact(&path, Content::String(file.name().into())),
+                artifact(&format!("{path}.meta"), Content::String(String::new())),
+            ])
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_file_generator_order() {
+        let generator = |fail| {
+            Generator::new_standalone(
+                test_util::kitchen_bin(),
+                [
+                    "kitchen/kitchen.proto",
+                    "google/protobuf/timestamp.proto",
+                    "kitchen/sink.proto",
+                ],
+                env::temp_dir().join("catalyze-test-file-generator"),
+            )
+            .unwrap()
+            .module(
+                "before",
+                Emit(vec![artifact("before.rs", Content::String("".into()))]),
+            )
+            .file_module("files", PerFileEmit { fail })
+        };
+        let ast = Ast::new(generator("").input()).unwrap();
+        let mut expected = vec!["before.rs".to_string()];
+        for file in ast.target_files() {
+            expected.push(format!("{}.rs", file.name()));
+            expected.push(format!("{}.rs.meta", file.name()));
+        }
+        for _ in 0..3 {
+            let (artifacts, _) = generator("").execute().unwrap();
+            let paths: Vec<_> = artifacts
+                .iter()
+                .map(|a| a.path.to_str().unwrap().to_string())
+                .collect();
+            assert_eq!(paths, expected);
+            assert!(artifacts[1..].iter().all(|a| a.tags == ["files"]));
+        }
+
+        let first = ast
+            .target_files()
+            .find(|f| f.name().contains("kitchen/"))
+            .unwrap();
+        match generator("kitchen/").execute() {
+            Err(Error::FailedToExecute { module, source }) => {
+                assert_eq!(module, "files");
+                assert!(source.to_string().starts_with(first.name()));
+            }
+            _ => panic!("expected the generator to fail"),
+        }
+    }
+
+    #[test]
+    fn test_module_error() {
+        let mut output = Vec::new();
+        Generator::new_protoc_plugin(Cursor::new(test_util::kitchen_request()))
+            .unwrap()
+            .module("grpc", Failing)
+            .render(&mut output)
+            .unwrap();
+        let response = CodeGeneratorResponse::parse_from_bytes(&output).unwrap();
+        assert!(response.error().starts_with("grpc: "));
+    }
+
+    #[test]
+    fn test_render_rejects_artifact() {
+        let ok = || artifact("ok.rs", Content::String(String::new()));
+        let (output_path, result) = render(
+            "catalyze-test-outside-artifact",
+            vec![
+                ok(),
+                artifact("../escape.rs", Content::String(String::new())),
+            ],
+        );
+        assert!(matches!(
+            result,
+            Err(Error::ArtifactOutsideOutputPath { .. })
+        ));
+        assert!(!output_path.join("ok.rs").exists());
+        assert!(!output_path.join("../escape.rs").exists());
 
-// impl Workflow for Standalone {
-//     fn decode_source<I: Read>(input: &mut BufReader<I>) -> Result<Source, io::Error> {
-//         let mut buf = Vec::new();
-//         input.read_to_end(&mut buf)?;
-//         let fds = protobuf::descriptor::FileDescriptorSet::parse_from_bytes(&buf)?;
-//         Ok(Source::FileDescriptorSet(fds))
-//     }
-// }
-
-// impl Workflow for ProtocPlugin {
-//     fn decode_source<I: Read>(input: &mut BufReader<I>) -> Result<Source, io::Error> {
-//         let mut buf = Vec::new();
-//         input.read_to_end(&mut buf)?;
-//         let cgr = protobuf::plugin::CodeGeneratorRequest::parse_from_bytes(&buf)?;
-//         Ok(Source::CodeGeneratorRequest(cgr))
-//     }
-// }
-
-// impl<I, O> Generator<ProtocPlugin, I, O>
-// where
-//     I: Read,
-//     O: Write,
-// {
-//     pub fn input<R: Read>(self, input: R) -> Generator<ProtocPlugin, R, O> {
-//         Generator {
-//             input: BufReader::new(input),
-//             modules: self.modules,
-//             targets: self.targets,
-//             workflow: self.workflow,
-//             output_path: self.output_path,
-//             output: self.output,
-//             parsed_input: self.parsed_input,
-//             param_mutators: self.param_mutators,
-//         }
-//     }
-//     pub fn output<W: Write>(self, output: W) -> Generator<ProtocPlugin, I, W> {
-//         Generator {
-//             input: self.input,
-//             modules: self.modules,
-//             targets: self.targets,
-//             workflow: self.workflow,
-//             output_path: self.output_path,
-//             output: Some(BufWriter::new(output)),
-//             parsed_input: self.parsed_input,
-//             param_mutators: self.param_mutators,
-//         }
-//     }
-// }
-
-// impl<I> Generator<Standalone, I>
-// where
-//     I: Read,
-// {
-//     /// Returns a new `Generator` with the `Standalone` workflow.
-//     pub fn new_standalone<'a, T, O>(
-//         input: I,
-//         target_protos: T,
-//         output_path: O,
-//     ) -> Generator<Standalone, I, fs::File>
-//     where
-//         T: IntoIterator,
-//         T::Item: AsRef<Path>,
-//         O: AsRef<Path>,
-//     {
-//         Generator {
-//             targets: target_protos
-//                 .into_iter()
-//                 .map(|p| p.as_ref().to_path_buf())
-//                 .collect(),
-//             input: BufReader::new(input),
-//             modules: Vec::new(),
-//             workflow: Standalone,
-//             output_path: Some(output_path.as_ref().to_path_buf()),
-//             output: None,
-//             parsed_input: Default::default(),
-//             param_mutators: Vec::new(),
-//         }
-//     }
-// }
-
-// impl<'a, W, I, O> Generator<W, I, O>
-// where
-//     W: Workflow,
-//     I: Read,
-//     O: Write,
-// {
-//     pub fn render(mut self) -> Result<(), crate::error::Error> {
-//         let input = Self::parse_input(
-//             &mut self.input,
-//             self.output_path.clone(),
-//             self.targets.clone(),
-//             self.param_mutators.as_slice(),
-//         )?;
-//         self.parsed_input = input;
-//         let path = self
-//             .output_path
-//             .as_ref()
-//             .map_or(Path::new("."), Path::new)
-//             .to_owned();
-//         let ast = Ast::new(&self.parsed_input)?;
-//         let mut artifacts = vec![];
-
-//         for m in self.modules.iter_mut() {
-//             let mut res = m.execute(ast.target_file_map(), ast.clone())?;
-//             artifacts.append(&mut res);
-//         }
-//         Ok(())
-//     }
-//     fn parse_input(
-//         buf: &mut BufReader<I>,
-//         output_path: Option<String>,
-//         targets: Vec<String>,
-//     ) -> Result<Input, io::Error> {
-//         let source = W::decode_source(buf)?;
-//         let mut input = Input::new(source, output_path, targets);
-//         input.mutate(mutators);
-//         Ok(input)
-//     }
-//     pub fn module(&mut self, module: impl Module + 'static) -> &mut Self {
-//         self.modules.push(Box::new(module));
-//         self
-//     }
-// }
-
-// #[cfg(test)]
-// mod tests {
-
-//     use crate::*;
-//     use std::{collections::HashMap, env, fs, io::Cursor};
-
-//     struct Mod;
-//     impl Module for Mod {
-//         fn name(&self) -> &'static str {
-//             "mod"
-//         }
-
-//         fn execute(
-//             &mut self,
-//             targets: HashMap<String, File>,
-//             ast: Ast,
-//         ) -> Result<Vec<Artifact>, Box<dyn std::error::Error + Send + Sync + 'static>> {
-//             assert_eq!(targets.len(), 1);
-//             assert!(targets.contains_key("kitchen/kitchen.proto"));
-//             let sink = ast.node(".kitchen.Sink").unwrap();
-//             assert!(matches!(sink, Node::Message(_)));
-//             let sink_proto = ast.file("kitchen/sink.proto").unwrap();
-
-//             let kitchen_proto = ast.file("kitchen/kitchen.proto").unwrap();
-//             let kitchen = kitchen_proto.message("Kitchen").expect("Kitchen not found");
-//             let color = kitchen_proto.message("Color").expect("Color not found");
-
-//             let style_field = kitchen.field("style").expect("style not found");
-//             assert_eq!(style_field.name(), "style");
-//             match style_field {
-//                 Field::Enum(e) => {
-//                     let style = e.enum_();
-//                     assert_eq!(style.name(), "Style");
-//                 }
-//                 _ => panic!("style should be an enum"),
-//             }
-
-//             let sink_field = kitchen.field("sink").expect("sink not found");
-//             assert_eq!(sink_field.name(), "sink");
-//             assert!(!sink_field.is_well_known_type());
-//             assert!(sink_field.is_embed());
-
-//             let dish_counts_field = kitchen.field("dish_counts").expect("dish_counts not found");
-//             assert!(dish_counts_field.is_map());
-//             assert_eq!(dish_counts_field.name(), "dish_counts");
-
-//             let utensils_field = kitchen.field("utensils").expect("utensils not found");
-//             assert_eq!("utensils", utensils_field.name());
-//             assert!(utensils_field.is_repeated());
-//             assert!(utensils_field.is_scalar());
-
-//             match utensils_field {
-//                 Field::Repeated(utensils_field) => {
-//                     assert_eq!(utensils_field.name(), "utensils");
-//                     assert!(utensils_field.is_scalar());
-//                     match utensils_field {
-//                         RepeatedField::Scalar(utensils_field) => {
-//                             assert_eq!(utensils_field.scalar(), Scalar::String);
-//                         }
-//                         _ => panic!("utensils should be a repeated scalar field"),
-//                     }
-//                 }
-//                 _ => panic!("utensils should be a repeated field"),
-//             }
-
-//             match dish_counts_field {
-//                 Field::Map(dish_counts) => match dish_counts {
-//                     MapField::Scalar(scf) => {
-//                         assert_eq!(scf.name(), "dish_counts");
-//                         assert_eq!(scf.key(), Key::String);
-//                         assert_eq!(scf.scalar(), Scalar::Uint32);
-//                     }
-//                     _ => panic!("map field should be scalar"),
-//                 },
-//                 _ => panic!("dish_counts should be a map"),
-//             }
-
-//             let wall_colors_field = kitchen.field("wall_colors").expect("wall_colors not found");
-//             match wall_colors_field {
-//                 Field::Repeated(wall_colors_field) => match wall_colors_field {
-//                     RepeatedField::Embed(wall_colors_field) => {
-//                         let color_embed = wall_colors_field.embed();
-//                         assert_eq!(color_embed, color);
-//                         assert_eq!(color_embed.name(), "Color");
-//                     }
-//                     _ => panic!("wall_colors should be a repeated embed field"),
-//                 },
-//                 _ => panic!("wall_colors should be repeated"),
-//             }
-//             let appliance_colors = kitchen
-//                 .field("appliance_colors")
-//                 .expect("appliance_colors not found");
-
-//             match appliance_colors {
-//                 Field::Map(appliance_colors) => match appliance_colors {
-//                     MapField::Embed(appliance_colors) => {
-//                         let color_embed = appliance_colors.embed();
-//                         assert_eq!(color_embed, color);
-//                         assert_eq!(color_embed.name(), "Color");
-//                     }
-//                     _ => panic!("appliance_colors should be a mapped embed field"),
-//                 },
-//                 _ => panic!("appliance_colors should be a map"),
-//             }
-
-//             let sink = sink_proto.message("Sink").expect("Sink not found");
-//             let brand = sink.enum_("Brand").expect("Brand not found");
-//             let brand_field = sink.field("brand").expect("brand not found");
-
-//             assert!(brand_field.is_enum());
-//             assert_eq!(brand_field.name(), "brand");
-//             match brand_field {
-//                 Field::Enum(brand_field) => {
-//                     assert_eq!(brand, brand_field.enum_());
-//                     assert_eq!(brand_field.name(), "brand");
-//                     assert_eq!(brand, brand_field.enum_());
-//                 }
-//                 _ => panic!("brand should be an enum"),
-//             }
-
-//             let material_field = sink.field("material").expect("material not found");
-
-//             assert!(material_field.is_embed());
-//             assert_eq!(material_field.name(), "material");
-
-//             let material = sink_proto.message("Material").expect("Material not found");
-
-//             match material_field {
-//                 Field::Embed(material_field) => {
-//                     let material_embed = material_field.embed();
-//                     assert_eq!(material_embed, material);
-//                     assert_eq!(material_embed.name(), "Material");
-//                 }
-//                 _ => panic!("material should be an embed field"),
-//             }
-
-//             let model_field = sink.field("model").expect("model not found");
-//             assert_eq!(model_field.name(), "model");
-//             assert!(model_field.is_scalar());
-//             match model_field {
-//                 Field::Scalar(model_field) => {
-//                     assert_eq!(model_field.scalar(), Scalar::String);
-//                 }
-//                 _ => panic!("model should be a scalar field"),
-//             }
-//             let basin_count_field = sink.field("basin_count").expect("basin_count not found");
-//             assert_eq!(basin_count_field.name(), "basin_count");
-//             assert!(basin_count_field.is_scalar());
-//             assert_eq!(basin_count_field.number(), 4);
-//             match basin_count_field {
-//                 Field::Scalar(basin_count_field) => {
-//                     assert_eq!(basin_count_field.scalar(), Scalar::Uint32);
-//                 }
-//                 _ => panic!("basin_count should be a scalar field"),
-//             }
-
-//             let installed_field = sink.field("installed").expect("installed not found");
-
-//             assert_eq!(installed_field.name(), "installed");
-
-//             assert!(
-//                 installed_field.is_embed(),
-//                 "installed_field's google.protobuf.Timestamp should be an embedded message"
-//             );
-//             match installed_field {
-//                 Field::Embed(installed_field) => {
-//                     // println!("{:#?}", installed_field.embed().package());
-
-//                     assert!(
-//                         installed_field.is_well_known_type(),
-//                         "installed_field is a google.protobuf.Timestamp which should be a well-known-type"
-//                     );
-
-//                     let wkt = installed_field
-//                         .embed()
-//                         .well_known_message()
-//                         .expect("google.protobuf.Timestamp should be well-known");
-//                     assert_eq!(wkt, WellKnownMessage::Timestamp);
-//                 }
-//                 _ => panic!("installed should be an embed field"),
-//             }
-
-//             let brand = sink.enum_("Brand").expect("Brand not found");
-//             assert_eq!(brand.value("KRAUS").expect("KRAUS not found"), 0);
-//             assert_eq!(brand.value("SWANSTONE").expect("SWANSTONE not found"), 1);
-//             assert_eq!(brand.value("HOUZER").expect("HOUZER not found"), 2);
-//             assert_eq!(brand.value("BLANCO").expect("BLANCO not found"), 3);
-//             assert_eq!(brand.value("KOHLER").expect("KOHLER not found"), 4);
-
-//             let cooking_service = kitchen_proto.service("Cooking").expect("Cooking not found");
-//             let saute_method = cooking_service.method("Saute").expect("Saute not found");
-//             let saute_request = kitchen_proto
-//                 .message("SauteRequest")
-//                 .expect("SauteRequest not found");
-//             let saute_response = kitchen_proto
-//                 .message("SauteResponse")
-//                 .expect("SauteResponse not found");
-//             assert_eq!(saute_method.name(), "Saute");
-//             assert_eq!(saute_method.input(), saute_request);
-//             assert_eq!(saute_method.output(), saute_response);
-//             assert!(
-//                 !saute_method.is_bidirectional_streaming(),
-//                 "Saute should not be bidirectional streaming"
-//             );
-//             assert!(
-//                 !saute_method.is_client_streaming(),
-//                 "Saute should not be client streaming"
-//             );
-//             assert!(
-//                 !saute_method.is_server_streaming(),
-//                 "Saute should not be server streaming"
-//             );
-//             let ice_request = kitchen_proto
-//                 .message("IceRequest")
-//                 .expect("IceRequest not found");
-//             let ice_response = kitchen_proto
-//                 .message("IceResponse")
-//                 .expect("IceResponse not found");
-
-//             let dispense_ice_method = cooking_service
-//                 .method("DispenseIce")
-//                 .expect("DispenseIce not found");
-
-//             assert_eq!(dispense_ice_method.input(), ice_request);
-//             assert_eq!(dispense_ice_method.output(), ice_response);
-//             assert!(
-//                 !dispense_ice_method.is_client_streaming(),
-//                 "DispenseIce should not be client streaming"
-//             );
-//             assert!(
-//                 dispense_ice_method.is_server_streaming(),
-//                 "DispenseIce should be server streaming"
-//             );
-//             assert!(
-//                 !dispense_ice_method.is_bidirectional_streaming(),
-//                 "DispenseIce should not be bidirectional streaming"
-//             );
-
-//             let load_fridge_method = cooking_service
-//                 .method("LoadFridge")
-//                 .expect("LoadFridge not found");
-
-//             assert!(
-//                 load_fridge_method.is_client_streaming(),
-//                 "LoadFridge should be client streaming"
-//             );
-//             assert!(
-//                 !load_fridge_method.is_server_streaming(),
-//                 "LoadFridge should not be server streaming"
-//             );
-
-//             let order_drinks_method = cooking_service
-//                 .method("OrderDrinks")
-//                 .expect("OrderDrinks not found");
-//             assert!(
-//                 order_drinks_method.is_bidirectional_streaming(),
-//                 "OrderDrinks should be bi-directional streaming"
-//             );
-
-//             Ok(vec![])
-//         }
-//     }
-
-//     #[test]
-//     fn test_new_standalone_generator() {
-//         let input = fs::File::open(
-//             env::current_dir()
-//                 .unwrap()
-//                 .join("tests/proto_op/kitchen.bin"),
-//         )
-//         .unwrap();
-//         let mut gen = Generator::new_standalone(input, &["kitchen/kitchen.proto"], "").unwrap();
-//         gen.module(Mod {}).render().unwrap();
-//     }
-
-//     #[test]
-//     fn test_new_protoc_plugin_generator() {
-//         use std::io::prelude::*;
-//         let mut input_file = std::fs::File::open("tests/code-generator-requests/kitchen").unwrap();
-//         let mut input = Vec::new();
-//         input_file.read_to_end(&mut input).unwrap();
-//         let mut input = Cursor::new(input);
-//         let mut gen = Generator::new_protoc_plugin().input(&mut input);
-//         gen.module(Mod {}).render().unwrap();
-//     }
-// }
+        let (output_path, result) = render(
+            "catalyze-test-disallowed-artifact",
+            vec![ok(), artifact("run.sh", Content::Custom(None, Vec::new()))],
+        );
+        assert!(matches!(
+            result,
+            Err(Error::CustomArtifactNotAllowed { .. })
+        ));
+        assert!(!output_path.join("ok.rs").exists());
+    }
+}
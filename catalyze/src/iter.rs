@@ -1,4 +1,6 @@
-use std::{cell::RefCell, fmt::Debug, rc::Rc};
+use std::fmt::Debug;
+
+use crate::sync::{Rc, RefCell};
 
 #[derive(Clone)]
 pub struct Iter<T> {
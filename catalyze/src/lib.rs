@@ -1,10 +1,12 @@
 mod artifact;
 mod ast;
 mod comments;
-mod config;
+mod custom_option;
+mod diagnostic;
 mod enum_;
 mod error;
 mod extension;
+mod feature;
 mod field;
 mod file;
 mod generator;
@@ -14,29 +16,69 @@ mod method;
 mod node;
 mod oneof;
 mod package;
-mod proto;
+mod parameter;
+mod printer;
 pub mod range;
-pub mod reflect_value;
+mod relative;
+#[cfg(feature = "serde")]
+pub mod schema;
 pub mod service;
+mod span;
+mod sync;
+#[cfg(test)]
+mod test_util;
 pub mod uninterpreted_option;
 pub mod unknown;
+mod util;
+pub mod visit;
 pub mod well_known;
 
-use std::{collections::HashMap, convert::Infallible, fmt, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    io::Read,
+    path::{Path, PathBuf},
+};
 
-use artifact::Artifact;
-pub use ast::Ast;
-use error::Error;
-use file::File;
+use itertools::{EitherOrBoth, Itertools};
+use protobuf::Message as _;
 
-// #[derive(Clone, Debug)]
-pub enum Source {
-    CodeGeneratorRequest(protobuf::plugin::CodeGeneratorRequest),
-    FileDescriptorSet(protobuf::descriptor::FileDescriptorSet),
-}
+pub use protobuf;
+
+pub use artifact::{Artifact, Content, Op, Warning};
+pub use ast::Ast;
+#[cfg(feature = "derive")]
+pub use catalyze_macros::{CustomOption, FromMessage};
+pub use comments::{Comments, PackageComments};
+pub use custom_option::{CustomOption, FromMessage, FromValue};
+pub use diagnostic::{Diagnostic, Diagnostics, Severity, Sources};
+pub use enum_::{Enum, EnumValue};
+pub use error::{Error, NodeError};
+pub use extension::{Extension, Extensions};
+pub use feature::{Edition, Features};
+pub use field::Field;
+pub use file::{File, Syntax};
+pub use message::Message;
+pub use method::Method;
+pub use node::{Container, Kind, Node};
+pub use oneof::Oneof;
+pub use package::Package;
+pub use parameter::{FromParameters, Parameters};
+pub use printer::Printer;
+pub use relative::RelativeName;
+pub use service::Service;
+pub use span::Span;
+#[cfg(feature = "message-graph")]
+pub use util::MessageGraph;
 
 pub trait Generate {
     type Error;
+    /// The name of the module, used to tag each [`Artifact`] it produces and
+    /// to attribute errors. Defaults to the name of the implementing type.
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
     fn generate(
         self,
         targets: HashMap<String, File>,
@@ -44,6 +86,26 @@ pub trait Generate {
     ) -> Result<Vec<Artifact>, Self::Error>;
 }
 
+/// A module which generates [`Artifact`]s for each target file independently,
+/// allowing the files to be processed concurrently.
+#[cfg(feature = "parallel")]
+pub trait GenerateFile: Sync {
+    type Error: 'static + std::error::Error + Send + Sync;
+    /// The name of the module, used to tag each [`Artifact`] it produces and
+    /// to attribute errors. Defaults to the name of the implementing type.
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+    fn generate_file(&self, file: File, ast: &Ast) -> Result<Vec<Artifact>, Self::Error>;
+}
+
+/// The unqualified name of `T`, without generic parameters.
+fn type_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
 #[derive(Clone, Debug)]
 pub struct Version {
     pub major: u32,
@@ -54,61 +116,304 @@ pub struct Version {
     pub prefix: Option<String>,
 }
 
+impl Version {
+    pub fn new(major: u32, minor: u32, patch: Option<u32>) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            prerelease: None,
+            build_metadata: None,
+            prefix: None,
+        }
+    }
+
+    /// Compares the precedence of two `Version`s. A missing patch is treated
+    /// as `0` and a prerelease precedes its release (e.g. `3.15.0-rc1` is
+    /// less than `3.15.0`). `build_metadata` and `prefix` are ignored.
+    ///
+    /// Prereleases are compared by their dot-separated identifiers, in which
+    /// runs of digits are compared numerically, so `rc2` precedes `rc10`.
+    pub fn cmp_precedence(&self, other: &Version) -> Ordering {
+        (self.major, self.minor, self.patch.unwrap_or(0))
+            .cmp(&(other.major, other.minor, other.patch.unwrap_or(0)))
+            .then_with(|| match (&self.prerelease, &other.prerelease) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => cmp_prerelease(a, b),
+            })
+    }
+
+    /// Returns `true` if this `Version` is equal to or later than `minimum`.
+    pub fn at_least(&self, minimum: &Version) -> bool {
+        self.cmp_precedence(minimum) != Ordering::Less
+    }
+}
+
+/// Compares prereleases by their dot-separated identifiers, a shorter list of
+/// otherwise equal identifiers preceding a longer one, as in semver.
+fn cmp_prerelease(a: &str, b: &str) -> Ordering {
+    cmp_by(a.split('.'), b.split('.'), cmp_identifier)
+}
+
+/// Compares identifiers run by run, comparing runs of digits numerically.
+/// Numeric runs precede other runs.
+fn cmp_identifier(a: &str, b: &str) -> Ordering {
+    fn runs(s: &str) -> impl Iterator<Item = &str> {
+        let mut rest = s;
+        std::iter::from_fn(move || {
+            let first = rest.chars().next()?;
+            let len = rest
+                .find(|c: char| c.is_ascii_digit() != first.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (run, tail) = rest.split_at(len);
+            rest = tail;
+            Some(run)
+        })
+    }
+    let is_numeric = |run: &str| run.starts_with(|c: char| c.is_ascii_digit());
+    cmp_by(runs(a), runs(b), |a, b| {
+        match (is_numeric(a), is_numeric(b)) {
+            (true, true) => {
+                let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+                a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+            }
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => a.cmp(b),
+        }
+    })
+}
+
+/// Lexicographically compares `a` and `b` with `cmp`.
+fn cmp_by<T>(
+    a: impl Iterator<Item = T>,
+    b: impl Iterator<Item = T>,
+    cmp: impl Fn(T, T) -> Ordering,
+) -> Ordering {
+    a.zip_longest(b)
+        .map(|pair| match pair {
+            EitherOrBoth::Both(a, b) => cmp(a, b),
+            EitherOrBoth::Left(_) => Ordering::Greater,
+            EitherOrBoth::Right(_) => Ordering::Less,
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(prefix) = &self.prefix {
+            write!(f, "{prefix}")?;
+        }
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if let Some(patch) = self.patch {
+            write!(f, ".{patch}")?;
+        }
+        if let Some(prerelease) = &self.prerelease {
+            write!(f, "-{prerelease}")?;
+        }
+        if let Some(build_metadata) = &self.build_metadata {
+            write!(f, "+{build_metadata}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&protobuf::plugin::Version> for Version {
+    fn from(version: &protobuf::plugin::Version) -> Self {
+        Self {
+            major: version.major() as u32,
+            minor: version.minor() as u32,
+            patch: version.patch.map(|patch| patch as u32),
+            prerelease: version
+                .suffix
+                .as_ref()
+                .map(|suffix| suffix.trim_start_matches('-'))
+                .filter(|suffix| !suffix.is_empty())
+                .map(ToString::to_string),
+            build_metadata: None,
+            prefix: None,
+        }
+    }
+}
+
 pub trait Input {
     type Parameter;
     fn files(&self) -> &[protobuf::descriptor::FileDescriptorProto];
+    /// Names of the proto files which code should be generated for.
+    fn targets(&self) -> &[String];
+    fn parameter(&self) -> &Self::Parameter;
+    /// The version of protoc which produced the input, if known.
     fn protoc_version(&self) -> Option<Version>;
 }
 
-pub struct Generator {
-    artifacts: Vec<Artifact>,
-    targets: Vec<PathBuf>,
-    files: Vec<protobuf::descriptor::FileDescriptorProto>,
+/// `Generator` builds an [`Ast`] from an [`Input`] and executes each
+/// registered [`Generate`] implementation against it, in the order they were
+/// registered.
+pub struct Generator<I = ProtocPlugin> {
+    input: I,
+    generators: Vec<Box<dyn generator::DynGenerate>>,
+    custom_roots: Vec<PathBuf>,
+    minimum_protoc_version: Option<Version>,
+    features: Features,
 }
 
 /// The `Standalone` [`Input`] reads a `FileDescriptorSet`, typically from the
 /// contents of a saved output from `protoc`, and generates based on a list of
 /// target (proto) files. The output is saved to disk at the specified output
 /// path.
-pub struct Standalone<P> {
-    file_descriptor_set: protobuf::descriptor::FileDescriptorProto,
+pub struct Standalone<P = ()> {
+    file_descriptor_set: protobuf::descriptor::FileDescriptorSet,
+    targets: Vec<String>,
     output_path: PathBuf,
     parameter: P,
 }
 
-// impl<P> Standalone<P> {
-//     pub fn new<R, O>(input: R, output_path: O, parameter: P) -> Result<Self, Error>
-//     where
-//         R: Read,
-//         O: AsRef<Path>,
-//     {
-//         Self {
-//             output_path: output_path.as_ref().to_path_buf(),
-//             parameter,
-//         }
-//     }
-// }
-
-// /// ProtocPlugins reads a `CodeGeneratorRequest` from an implementation of
-// /// `Read` (typically `stdin`) and writes a `CodeGeneratorResponse` to
-// /// `stdout`.
-// ///
-// /// The input (`stdin`) and output (`stdout`) can be configured, using any
-// /// reader and writer respectively.
-// pub struct ProtocPlugin {}
-
-// pub struct Generator<W = ProtocPlugin>
-// where
-//     W: Workflow,
-//     I: Read,
-// {
-//     output_path: Option<PathBuf>,
-//     modules: Vec<Box<dyn Module>>,
-//     targets: Vec<PathBuf>,
-//     workflow: W,
-//     parsed_input: Input,
-//     param_mutators: Vec<ParamMutatorFn>,
-// }
+impl<P> Standalone<P> {
+    /// Decodes a serialized `FileDescriptorSet` from `input`.
+    ///
+    /// `targets` are the names of the proto files, as they appear in the
+    /// `FileDescriptorSet`, to generate code for.
+    pub fn new<R, T, O>(
+        mut input: R,
+        targets: T,
+        output_path: O,
+        parameter: P,
+    ) -> Result<Self, Error>
+    where
+        R: Read,
+        T: IntoIterator,
+        T::Item: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        let file_descriptor_set =
+            protobuf::descriptor::FileDescriptorSet::parse_from_reader(&mut input)
+                .map_err(|source| Error::DecodeFileDescriptorSet { source })?;
+        Ok(Self {
+            file_descriptor_set,
+            targets: targets
+                .into_iter()
+                .map(|t| t.as_ref().to_string_lossy().into_owned())
+                .collect(),
+            output_path: output_path.as_ref().to_path_buf(),
+            parameter,
+        })
+    }
+    pub fn output_path(&self) -> &Path {
+        &self.output_path
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<P> Standalone<P> {
+    /// Parses the proto files `inputs` with the pure Rust parser of
+    /// `protobuf-parse`, resolving imports from the `includes` directories,
+    /// rather than reading the output of `protoc`.
+    ///
+    /// Each of `inputs` is a target. The parser does not record source code
+    /// info, so nodes will not have comments or spans.
+    pub fn parse<I, T, O>(
+        includes: I,
+        inputs: T,
+        output_path: O,
+        parameter: P,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+        T: IntoIterator,
+        T::Item: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        let parsed = protobuf_parse::Parser::new()
+            .pure()
+            .includes(includes)
+            .inputs(inputs)
+            .parse_and_typecheck()
+            .map_err(|err| Error::ParseProto { source: err.into() })?;
+        let mut file_descriptor_set = protobuf::descriptor::FileDescriptorSet::new();
+        file_descriptor_set.file = parsed.file_descriptors;
+        Ok(Self {
+            file_descriptor_set,
+            targets: parsed
+                .relative_paths
+                .iter()
+                .map(|p| p.to_str().to_string())
+                .collect(),
+            output_path: output_path.as_ref().to_path_buf(),
+            parameter,
+        })
+    }
+}
+
+impl<P> Input for Standalone<P> {
+    type Parameter = P;
+    fn files(&self) -> &[protobuf::descriptor::FileDescriptorProto] {
+        &self.file_descriptor_set.file
+    }
+    fn targets(&self) -> &[String] {
+        &self.targets
+    }
+    fn parameter(&self) -> &P {
+        &self.parameter
+    }
+    fn protoc_version(&self) -> Option<Version> {
+        None
+    }
+}
+
+/// The `ProtocPlugin` [`Input`] holds the `CodeGeneratorRequest` sent by
+/// `protoc`, typically read from `stdin`. The resulting
+/// `CodeGeneratorResponse` is written back to `protoc`, typically through
+/// `stdout`.
+///
+/// The `parameter` of the request is parsed into [`Parameters`] and converted
+/// into `P` with [`FromParameters`].
+pub struct ProtocPlugin<P = ()> {
+    request: protobuf::plugin::CodeGeneratorRequest,
+    parameters: Parameters,
+    parameter: P,
+}
+
+impl<P: FromParameters> ProtocPlugin<P> {
+    pub fn new(request: protobuf::plugin::CodeGeneratorRequest) -> Result<Self, Error> {
+        let parameters = Parameters::parse(request.parameter());
+        let parameter = P::from_parameters(&parameters)?;
+        Ok(Self {
+            request,
+            parameters,
+            parameter,
+        })
+    }
+}
+
+impl<P> ProtocPlugin<P> {
+    pub fn request(&self) -> &protobuf::plugin::CodeGeneratorRequest {
+        &self.request
+    }
+    /// The untyped [`Parameters`] parsed from the request.
+    pub fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+}
+
+impl<P> Input for ProtocPlugin<P> {
+    type Parameter = P;
+    fn files(&self) -> &[protobuf::descriptor::FileDescriptorProto] {
+        &self.request.proto_file
+    }
+    fn targets(&self) -> &[String] {
+        &self.request.file_to_generate
+    }
+    fn parameter(&self) -> &P {
+        &self.parameter
+    }
+    fn protoc_version(&self) -> Option<Version> {
+        self.request.compiler_version.as_ref().map(Version::from)
+    }
+}
 
 /// Paths for nodes in a FileDescriptorProto
 #[derive(Clone, PartialEq, Eq, Copy)]
@@ -212,6 +517,7 @@ impl DescriptorPath {
     const FIELD: i32 = Self::Field as i32;
     const NESTED_TYPE: i32 = Self::NestedType as i32;
     const ENUM_TYPE: i32 = Self::EnumType as i32;
+    const EXTENSION: i32 = Self::Extension as i32;
     const ONEOF_DECL: i32 = Self::OneofDecl as i32;
 }
 impl TryFrom<i32> for DescriptorPath {
@@ -222,6 +528,7 @@ impl TryFrom<i32> for DescriptorPath {
             Self::FIELD => Ok(DescriptorPath::Field),
             Self::NESTED_TYPE => Ok(DescriptorPath::NestedType),
             Self::ENUM_TYPE => Ok(DescriptorPath::EnumType),
+            Self::EXTENSION => Ok(DescriptorPath::Extension),
             Self::ONEOF_DECL => Ok(DescriptorPath::OneofDecl),
             _ => Err(Error::UnknownDecriptorPath { path }),
         }
@@ -230,7 +537,7 @@ impl TryFrom<i32> for DescriptorPath {
 
 impl PartialEq<i32> for DescriptorPath {
     fn eq(&self, other: &i32) -> bool {
-        *other == *self
+        *other == *self as i32
     }
 }
 impl PartialEq<DescriptorPath> for i32 {
@@ -265,7 +572,7 @@ impl TryFrom<i32> for EnumDescriptorPath {
     fn try_from(v: i32) -> Result<Self, Self::Error> {
         match v {
             Self::VALUE => Ok(Self::Value),
-            _ => Err(()),
+            _ => Err(Error::UnknownEnumDecriptorPath { path: v }),
         }
     }
 }
@@ -304,3 +611,81 @@ impl PartialEq<ServiceDescriptorPath> for i32 {
         *other as i32 == *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> Version {
+        let (version, prerelease) = match s.split_once('-') {
+            Some((version, prerelease)) => (version, Some(prerelease.to_string())),
+            None => (s, None),
+        };
+        let mut parts = version.split('.').map(|p| p.parse().unwrap());
+        Version {
+            prerelease,
+            ..Version::new(parts.next().unwrap(), parts.next().unwrap(), parts.next())
+        }
+    }
+
+    #[test]
+    fn test_cmp_precedence() {
+        let ordered = [
+            "3.14.9",
+            "3.15.0-rc.1",
+            "3.15.0-rc.1.1",
+            "3.15.0-rc.2",
+            "3.15.0-rc.10",
+            "3.15.0-rc.beta",
+            "3.15-rc1",
+            "3.15.0-rc2",
+            "3.15.0-rc10",
+            "3.15.0",
+            "3.15.1",
+            "4.0.0",
+        ];
+        for (i, a) in ordered.iter().enumerate() {
+            for (j, b) in ordered.iter().enumerate() {
+                assert_eq!(
+                    version(a).cmp_precedence(&version(b)),
+                    i.cmp(&j),
+                    "{a} cmp {b}"
+                );
+            }
+        }
+        assert_eq!(
+            version("3.15.0-rc02").cmp_precedence(&version("3.15-rc2")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_at_least() {
+        let minimum = version("3.15.0");
+        assert!(version("3.15").at_least(&minimum));
+        assert!(version("3.21.12").at_least(&minimum));
+        assert!(!version("3.15.0-rc2").at_least(&minimum));
+        assert!(!version("3.14.0").at_least(&minimum));
+        assert!(version("3.15.0-rc10").at_least(&version("3.15.0-rc2")));
+    }
+
+    #[test]
+    fn test_from_plugin_version() {
+        let mut plugin = protobuf::plugin::Version::new();
+        plugin.set_major(3);
+        plugin.set_minor(21);
+        let version = Version::from(&plugin);
+        assert_eq!((version.major, version.minor), (3, 21));
+        assert_eq!((version.patch, version.prerelease), (None, None));
+
+        plugin.set_patch(12);
+        plugin.set_suffix("-rc1".to_string());
+        let version = Version::from(&plugin);
+        assert_eq!(version.patch, Some(12));
+        assert_eq!(version.prerelease.as_deref(), Some("rc1"));
+        assert_eq!(version.to_string(), "3.21.12-rc1");
+
+        plugin.set_suffix(String::new());
+        assert_eq!(Version::from(&plugin).prerelease, None);
+    }
+}
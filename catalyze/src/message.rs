@@ -1,11 +1,10 @@
-use std::cell::RefCell;
 use std::marker::PhantomData;
-use std::rc::{Rc, Weak};
 use std::str::FromStr;
 
 use protobuf::reflect::MessageDescriptor;
 
 use crate::comments::Comments;
+use crate::custom_option::CustomOption;
 use crate::enum_::{AllEnums, Enum};
 use crate::error::Error;
 use crate::extension::{Extension, WeakExtension};
@@ -16,42 +15,38 @@ use crate::iter::Iter;
 use crate::node::{Container, Node, Nodes, WeakContainer};
 use crate::oneof::Oneof;
 use crate::package::Package;
+use crate::range::Reserved;
+use crate::sync::{Rc, RefCell, Weak};
 use crate::uninterpreted_option::UninterpretedOption;
 use crate::well_known::{WellKnownMessage, WellKnownType};
 use crate::DescriptorPath;
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashSet, VecDeque};
 
 #[derive(Debug, Clone)]
 pub(crate) struct Detail {
     descriptor: MessageDescriptor,
     fqn: String,
-    messages: RefCell<Rc<[Message]>>,
-    enums: RefCell<Rc<[Enum]>>,
-    fields: RefCell<Rc<[Field]>>,
-    oneofs: RefCell<Rc<[Oneof]>>,
-    real_oneofs: RefCell<Rc<[Oneof]>>,
-    synthetic_oneofs: RefCell<Rc<[Oneof]>>,
-    dependents: RefCell<Rc<[WeakMessage]>>,
-    imports: RefCell<Rc<[WeakFile]>>,
+    messages: Rc<RefCell<Vec<Message>>>,
+    enums: Rc<RefCell<Vec<Enum>>>,
+    fields: Rc<RefCell<Vec<Field>>>,
+    oneofs: Rc<RefCell<Vec<Oneof>>>,
+    real_oneofs: Rc<RefCell<Vec<Oneof>>>,
+    synthetic_oneofs: Rc<RefCell<Vec<Oneof>>>,
+    dependents: Rc<RefCell<Vec<WeakMessage>>>,
+    imports: Rc<RefCell<Vec<WeakFile>>>,
     import_set: RefCell<HashSet<String>>,
     container: RefCell<WeakContainer>,
-    maps: RefCell<HashMap<String, Message>>,
+    /// Map entry messages, which are excluded from `messages`.
+    maps: Rc<RefCell<Vec<Message>>>,
     /// `Extension`s defined by this message.
-    defined_extensions: RefCell<Rc<[Extension]>>,
+    defined_extensions: Rc<RefCell<Vec<Extension>>>,
     /// `Extension`s applied to this `Message`
-    applied_extensions: Rc<RefCell<Rc<[WeakExtension]>>>,
+    applied_extensions: Rc<RefCell<Vec<WeakExtension>>>,
     comments: RefCell<Comments>,
     wkt: Option<WellKnownMessage>,
 }
 
-impl From<Option<&protobuf::descriptor::MessageOptions>> for Options {
-    fn from(opts: Option<&protobuf::descriptor::MessageOptions>) -> Self {
-        // MessageOptions { opts }
-        todo!()
-    }
-}
-
 impl Detail {
     fn new(desc: MessageDescriptor, container: Container) -> Rc<Self> {
         let fqn = format!("{}.{}", container.fully_qualified_name(), desc.name());
@@ -64,21 +59,21 @@ impl Detail {
         Rc::new(Self {
             container: RefCell::new(container.into()),
             fqn,
-            descriptor: desc,
             wkt,
-            enums: Rc::new(RefCell::new(Vec::with_capacity(desc.enums().len()))),
-            fields: Rc::new(RefCell::new(Vec::with_capacity(desc.fields().len()))),
-            oneofs: Rc::new(RefCell::new(Vec::with_capacity(desc.oneofs().len()))),
+            enums: Rc::new(RefCell::new(Vec::new())),
+            fields: Rc::new(RefCell::new(Vec::new())),
+            oneofs: Rc::new(RefCell::new(Vec::new())),
             real_oneofs: Rc::new(RefCell::new(Vec::new())),
             synthetic_oneofs: Rc::new(RefCell::new(Vec::new())),
             messages: Rc::new(RefCell::new(Vec::new())),
-            maps: Rc::new(RefCell::new(HashMap::new())),
+            maps: Rc::new(RefCell::new(Vec::new())),
             dependents: Rc::new(RefCell::new(Vec::new())),
             applied_extensions: Rc::new(RefCell::new(Vec::new())),
-            defined_extensions: Rc::new(RefCell::new(Vec::with_capacity(desc.extensions().len()))),
+            defined_extensions: Rc::new(RefCell::new(Vec::new())),
             comments: RefCell::new(Comments::default()),
             imports: Rc::new(RefCell::new(Vec::new())),
-            import_set: Rc::new(RefCell::new(HashSet::new())),
+            import_set: RefCell::new(HashSet::new()),
+            descriptor: desc,
         })
     }
 }
@@ -107,7 +102,7 @@ impl Message {
         self.into()
     }
     pub fn descriptor(&self) -> MessageDescriptor {
-        self.0.descriptor
+        self.0.descriptor.clone()
     }
     pub fn name(&self) -> &str {
         self.0.descriptor.name()
@@ -124,7 +119,7 @@ impl Message {
         self.0.container.borrow().package()
     }
     pub fn is_map_entry(&self) -> bool {
-        self.0.descriptor.options().is_map_entry()
+        self.0.descriptor.is_map_entry()
     }
     pub fn is_well_known_type(&self) -> bool {
         self.0.wkt.is_some()
@@ -144,13 +139,9 @@ impl Message {
     pub fn file(&self) -> File {
         self.0.container.borrow().file()
     }
-    pub(crate) fn maps(&self) -> HashMap<&str, Message> {
-        self.0
-            .maps
-            .borrow()
-            .iter()
-            .map(|(k, v)| (*k, v.clone()))
-            .collect()
+    /// The map entry messages nested in this message.
+    pub(crate) fn maps(&self) -> Iter<Message> {
+        Iter::from(&self.0.maps)
     }
     pub fn fields(&self) -> Iter<Field> {
         Iter::from(&self.0.fields)
@@ -196,8 +187,29 @@ impl Message {
         Iter::from(&self.0.defined_extensions)
     }
 
+    /// Ranges of field numbers which may not be used by fields of the message.
+    pub fn reserved_ranges(&self) -> Vec<Reserved> {
+        self.descriptor()
+            .proto()
+            .reserved_range
+            .iter()
+            .cloned()
+            .map(Reserved::from)
+            .collect()
+    }
+    /// Field names which may not be used by fields of the message.
+    pub fn reserved_names(&self) -> Vec<String> {
+        self.descriptor().proto().reserved_name.clone()
+    }
     pub fn comments(&self) -> Comments {
-        *self.0.comments.borrow()
+        self.0.comments.borrow().clone()
+    }
+    pub fn options(&self) -> Options {
+        Options::from(self.0.descriptor.proto().options.as_ref())
+    }
+    /// Returns the custom option `T` set on the message; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
     }
     pub fn field(&self, name: &str) -> Option<Field> {
         self.0
@@ -222,6 +234,29 @@ impl Message {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        let d = &self.0;
+        d.messages.freeze();
+        d.enums.freeze();
+        d.fields.freeze();
+        d.oneofs.freeze();
+        d.real_oneofs.freeze();
+        d.synthetic_oneofs.freeze();
+        d.dependents.freeze();
+        d.imports.freeze();
+        d.import_set.freeze();
+        d.container.freeze();
+        d.maps.freeze();
+        d.defined_extensions.freeze();
+        d.applied_extensions.freeze();
+        d.comments.freeze();
+        // map entries are not reachable from `nodes`
+        for map in self.maps() {
+            map.freeze();
+            map.fields().for_each(|f| f.freeze());
+        }
+    }
 
     pub(crate) fn add_dependent(&self, dependent: Message) {
         self.0.dependents.borrow_mut().push(dependent.into());
@@ -236,7 +271,7 @@ impl Message {
 
     pub(crate) fn register_import(&self, file: File) {
         let mut set = self.0.import_set.borrow_mut();
-        if set.contains(&*file.name()) {
+        if set.contains(file.name()) {
             return;
         }
         self.container().register_import(file.clone());
@@ -257,121 +292,100 @@ impl Message {
         if path.is_empty() {
             return Some(Node::Message(msg));
         }
-        if path.len() % 2 == 0 {
+        if path.len() % 2 == 1 {
             return None;
         }
 
         let next = path[1] as usize;
         DescriptorPath::try_from(path[0]).ok().and_then(|p| {
             let node: Option<Node> = match p {
-                DescriptorPath::EnumType => msg
-                    .0
-                    .enums
-                    .borrow()
-                    .get(next)
-                    .map(|e| Node::Enum(e.clone())),
-                DescriptorPath::Field => msg
-                    .0
-                    .fields
-                    .borrow()
-                    .get(next)
-                    .map(|f| Node::Field(f.clone())),
-                DescriptorPath::OneofDecl => msg
-                    .0
-                    .oneofs
-                    .borrow()
-                    .get(next)
-                    .map(|o| Node::Oneof(o.clone())),
-                DescriptorPath::NestedType => msg
-                    .0
-                    .messages
-                    .borrow()
-                    .get(next)
-                    .map(|m| Node::Message(m.clone())),
-                DescriptorPath::Extension => msg
-                    .0
-                    .applied_extensions
-                    .borrow()
-                    .get(next)
-                    .map(|m| Node::Extension(m.upgrade())),
+                DescriptorPath::EnumType => msg.enums().get(next).map(Node::Enum),
+                DescriptorPath::Field => msg.fields().get(next).map(Node::Field),
+                DescriptorPath::OneofDecl => msg.oneofs().get(next).map(Node::Oneof),
+                DescriptorPath::NestedType => {
+                    // map entries are excluded from `messages`, so the index
+                    // is resolved against the descriptor
+                    let name = msg
+                        .descriptor()
+                        .nested_messages()
+                        .nth(next)?
+                        .name()
+                        .to_string();
+                    msg.messages().find(|m| m.name() == name).map(Node::Message)
+                }
+                DescriptorPath::Extension => {
+                    msg.defined_extensions().get(next).map(Node::Extension)
+                }
             };
             node.and_then(|n| n.node_at_path(&path[2..]))
         })
     }
 
     fn hydrate_fields(self) -> Result<Message, Error> {
-        {
-            let oneofs = self.0.oneofs.borrow_mut();
-            let mut fields = self.0.fields.borrow_mut();
-            for fd in self.descriptor().fields() {
-                let oneof = fd
-                    .oneof_index()
-                    .map(|i| oneofs.get(i as usize).expect("Oneof index out of bounds"))
-                    .cloned();
-                let f = Field::new(fd, self.clone(), oneof.clone())?;
-                if let Some(oneof) = oneof {
-                    oneof.add_field(f.clone());
-                }
-
-                fields.push(f);
+        let oneofs = self.0.oneofs.borrow().clone();
+        let mut fields = Vec::new();
+        for fd in self.descriptor().fields() {
+            let oneof = fd
+                .containing_oneof_including_synthetic()
+                .map(|od| {
+                    oneofs
+                        .iter()
+                        .find(|o| o.name() == od.name())
+                        .expect("oneof not found")
+                })
+                .cloned();
+            let f = Field::new(fd, self.clone(), oneof.clone())?;
+            if let Some(oneof) = oneof {
+                oneof.add_field(f.clone());
             }
+            fields.push(f);
         }
+        self.0.fields.borrow_mut().extend(fields);
         Ok(self)
     }
 
     fn hydrate_exts(self) -> Self {
-        {
-            let container = self.as_container();
-            let mut def_exts = self.0.defined_extensions.borrow_mut();
-            for xd in self.descriptor().extensions() {
-                let ext = Extension::new(xd, container.clone());
-                def_exts.push(ext);
-            }
-        }
+        let container = self.as_container();
+        let exts = self
+            .descriptor()
+            .extensions()
+            .map(|xd| Extension::new(xd, container.clone()))
+            .collect::<Vec<_>>();
+        self.0.defined_extensions.borrow_mut().extend(exts);
         self
     }
 
     fn hydrate_oneofs(self) -> Self {
-        {
-            let mut oneofs = self.0.oneofs.borrow_mut();
-            let mut real_oneofs = self.0.real_oneofs.borrow_mut();
-            let mut synthetic_oneofs = self.0.synthetic_oneofs.borrow_mut();
-            for od in self.descriptor().oneofs() {
-                let o = Oneof::new(od, self.clone());
-                oneofs.push(o.clone());
-                if o.is_real() {
-                    real_oneofs.push(o);
-                } else {
-                    synthetic_oneofs.push(o);
-                }
+        for od in self.descriptor().all_oneofs() {
+            let o = Oneof::new(od, self.clone());
+            self.0.oneofs.borrow_mut().push(o.clone());
+            if o.is_real() {
+                self.0.real_oneofs.borrow_mut().push(o);
+            } else {
+                self.0.synthetic_oneofs.borrow_mut().push(o);
             }
         }
         self
     }
     fn hydrate_enums(self) -> Self {
-        {
-            let container = self.as_container();
-            let mut enums = self.0.enums.borrow_mut();
-            for ed in self.descriptor().enums() {
-                let e = Enum::new(ed, container.clone());
-                enums.push(e);
-            }
-        }
+        let container = self.as_container();
+        let enums = self
+            .descriptor()
+            .nested_enums()
+            .map(|ed| Enum::new(ed, container.clone()))
+            .collect::<Vec<_>>();
+        self.0.enums.borrow_mut().extend(enums);
         self
     }
 
     fn hydrate_nested_msgs(self) -> Result<Self, Error> {
-        {
-            let container = self.as_container();
-            let mut msgs = self.0.messages.borrow_mut();
-            let mut maps = self.0.maps.borrow_mut();
-            for md in self.descriptor().nested_messages() {
-                let m = Message::new(md, container.clone())?;
-                if m.is_map_entry() {
-                    maps.insert(m.fully_qualified_name().to_string(), m);
-                } else {
-                    msgs.push(m);
-                }
+        let container = self.as_container();
+        for md in self.descriptor().nested_messages() {
+            let m = Message::new(md, container.clone())?;
+            if m.is_map_entry() {
+                self.0.maps.borrow_mut().push(m);
+            } else {
+                self.0.messages.borrow_mut().push(m);
             }
         }
         Ok(self)
@@ -496,7 +510,7 @@ impl Iterator for AllMessages {
 
 #[derive(Debug, Clone)]
 pub struct Dependents<T = Message> {
-    vec: RefCell<Vec<WeakMessage>>,
+    vec: Rc<RefCell<Vec<WeakMessage>>>,
     idx: usize,
     _marker: PhantomData<T>,
 }
@@ -504,13 +518,9 @@ pub struct Dependents<T = Message> {
 impl Iterator for Dependents {
     type Item = Message;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.idx < self.vec.borrow().len() {
-            let msg = self.vec.borrow()[self.idx].upgrade();
-            self.idx += 1;
-            Some(msg)
-        } else {
-            None
-        }
+        let msg = self.vec.borrow().get(self.idx).map(WeakMessage::upgrade)?;
+        self.idx += 1;
+        Some(msg)
     }
 }
 
@@ -529,24 +539,30 @@ impl From<WeakMessage> for Option<Message> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Options {
-    // message fields
-    pub message_set_wire_format: Option<bool>,
-    ///  Disables the generation of the standard "descriptor()" accessor, which can
-    ///  conflict with a field of the same name.  This is meant to make migration
-    ///  from proto1 easier; new code should avoid fields named "descriptor".
-    pub no_standard_descriptor_accessor: Option<bool>,
-    ///  Is this message deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the message, or it will be completely ignored; in the very least,
-    ///  this is a formalization for deprecating messages.
-    pub deprecated: Option<bool>,
-    pub map_entry: Option<bool>,
-    ///  The parser stores options it doesn't recognize here.
-    pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
-    pub special_fields: protobuf::SpecialFields,
+    message_set_wire_format: Option<bool>,
+    no_standard_descriptor_accessor: Option<bool>,
+    deprecated: Option<bool>,
+    map_entry: Option<bool>,
+    uninterpreted_option: Vec<UninterpretedOption>,
 }
+
+impl From<Option<&protobuf::descriptor::MessageOptions>> for Options {
+    fn from(opts: Option<&protobuf::descriptor::MessageOptions>) -> Self {
+        let Some(opts) = opts else {
+            return Self::default();
+        };
+        Self {
+            message_set_wire_format: opts.message_set_wire_format,
+            no_standard_descriptor_accessor: opts.no_standard_descriptor_accessor,
+            deprecated: opts.deprecated,
+            map_entry: opts.map_entry,
+            uninterpreted_option: opts.uninterpreted_option.iter().map(Into::into).collect(),
+        }
+    }
+}
+
 impl Options {
     /// Set true to use the old proto1 MessageSet wire format for extensions.
     /// This is provided for backwards-compatibility with the MessageSet wire
@@ -567,7 +583,7 @@ impl Options {
     /// Because this is an option, the above two restrictions are not enforced by
     /// the protocol compiler.
     pub fn message_set_wire_format(&self) -> bool {
-        self.opts().message_set_wire_format()
+        self.message_set_wire_format.unwrap_or(false)
     }
     /// Whether the message is an automatically generated map entry type for the
     /// maps field.
@@ -591,24 +607,31 @@ impl Options {
     /// instead. The option should only be implicitly set by the proto compiler
     /// parser.
     pub fn map_entry(&self) -> bool {
-        self.opts().map_entry()
+        self.map_entry.unwrap_or(false)
     }
 
     pub fn is_map_entry(&self) -> bool {
         self.map_entry()
     }
 
+    ///  Is this message deprecated?
+    ///  Depending on the target platform, this can emit Deprecated annotations
+    ///  for the message, or it will be completely ignored; in the very least,
+    ///  this is a formalization for deprecating messages.
     pub fn deprecated(&self) -> bool {
-        self.opts().deprecated()
+        self.deprecated.unwrap_or(false)
     }
     pub fn is_deprecated(&self) -> bool {
-        self.opts().deprecated()
+        self.deprecated()
     }
+    ///  Disables the generation of the standard "descriptor()" accessor, which can
+    ///  conflict with a field of the same name.  This is meant to make migration
+    ///  from proto1 easier; new code should avoid fields named "descriptor".
     pub fn no_standard_descriptor_accessor(&self) -> bool {
-        self.opts().no_standard_descriptor_accessor()
+        self.no_standard_descriptor_accessor.unwrap_or(false)
     }
     /// The parser stores options it doesn't recognize here. See above.
     pub fn uninterpreted_option(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+        &self.uninterpreted_option
     }
 }
@@ -1,14 +1,15 @@
 use crate::comments::Comments;
+use crate::custom_option::CustomOption;
+use crate::error::Error;
 use crate::file::File;
 use crate::message::{Message, WeakMessage};
 use crate::node::Node;
 use crate::package::Package;
 use crate::service::{Service, WeakService};
+use crate::sync::{Rc, RefCell};
 use crate::uninterpreted_option::UninterpretedOption;
 use protobuf::descriptor::MethodDescriptorProto as MethodDescriptor;
-use protobuf::SpecialFields;
 use std::fmt;
-use std::{cell::RefCell, rc::Rc};
 
 pub struct Io<'a> {
     pub input: &'a str,
@@ -71,8 +72,8 @@ pub struct Method(Rc<Detail>);
 
 impl Method {
     pub(crate) fn new(descriptor: MethodDescriptor, svc: Service) -> Self {
-        let input = Rc::new(RefCell::new(WeakMessage::new()));
-        let output = Rc::new(RefCell::new(WeakMessage::new()));
+        let input = RefCell::new(WeakMessage::new());
+        let output = RefCell::new(WeakMessage::new());
         let fqn = format!("{}.{}", svc.fully_qualified_name(), descriptor.name());
         Method(Rc::new(Detail {
             descriptor,
@@ -92,13 +93,20 @@ impl Method {
     }
 
     pub fn name(&self) -> &str {
-        &self.0.descriptor.name()
+        self.0.descriptor.name()
     }
     pub fn descriptor(&self) -> MethodDescriptor {
-        self.0.descriptor
+        self.0.descriptor.clone()
+    }
+    pub fn options(&self) -> MethodOptions {
+        MethodOptions::from(self.0.descriptor.options.as_ref())
     }
     pub fn comments(&self) -> Comments {
-        Comments::default()
+        self.0.comments.borrow().clone()
+    }
+    /// Returns the custom option `T` set on the method; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
     }
     pub fn fully_qualified_name(&self) -> &str {
         &self.0.fqn
@@ -149,6 +157,12 @@ impl Method {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.comments.freeze();
+        self.0.input.freeze();
+        self.0.output.freeze();
+    }
 
     pub(crate) fn node_at_path(&self, path: &[i32]) -> Option<Node> {
         if path.is_empty() {
@@ -158,7 +172,7 @@ impl Method {
         }
     }
 
-    pub(crate) fn io(&self) -> Io {
+    pub(crate) fn io(&self) -> Io<'_> {
         Io {
             input: self.input_type(),
             output: self.output_type(),
@@ -200,17 +214,31 @@ impl fmt::Display for MethodIo {
 ///
 /// Note:  Field numbers 1 through 32 are reserved for Google's internal RPC
 /// framework.
+#[derive(Debug, Clone, Default)]
 pub struct MethodOptions {
-    ///  Is this method deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the method, or it will be completely ignored; in the very least,
-    ///  this is a formalization for deprecating methods.
-    pub deprecated: Option<bool>,
-    pub idempotency_level: Option<IdempotencyLevel>,
-    ///  The parser stores options it doesn't recognize here. See above.
-    pub uninterpreted_option: Vec<UninterpretedOption>,
-    pub special_fields: SpecialFields,
+    deprecated: Option<bool>,
+    idempotency_level: Option<IdempotencyLevel>,
+    uninterpreted_option: Vec<UninterpretedOption>,
+}
+
+impl From<Option<&protobuf::descriptor::MethodOptions>> for MethodOptions {
+    fn from(opts: Option<&protobuf::descriptor::MethodOptions>) -> Self {
+        let Some(opts) = opts else {
+            return Self::default();
+        };
+        Self {
+            deprecated: opts.deprecated,
+            idempotency_level: opts
+                .idempotency_level
+                .map(|level| match level.enum_value() {
+                    Ok(level) => level.into(),
+                    Err(value) => value.into(),
+                }),
+            uninterpreted_option: opts.uninterpreted_option.iter().map(Into::into).collect(),
+        }
+    }
 }
+
 impl MethodOptions {
     // Note:  Field numbers 1 through 32 are reserved for Google's internal RPC
     //   framework.  We apologize for hoarding these numbers to ourselves, but
@@ -222,17 +250,18 @@ impl MethodOptions {
     /// for the method, or it will be completely ignored; in the very least,
     /// this is a formalization for deprecating methods.
     pub fn deprecated(&self) -> bool {
-        self.opts().deprecated()
+        self.deprecated.unwrap_or(false)
     }
     /// The parser stores options it doesn't recognize here. See above.
     pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+        &self.uninterpreted_option
     }
 
     /// Is this method side-effect-free (or safe in HTTP parlance), or idempotent,
     /// or neither? HTTP based RPC implementation may choose GET verb for safe
     /// methods, and PUT verb for idempotent methods instead of the default POST.
     pub fn idempotency_level(&self) -> IdempotencyLevel {
-        self.opts().idempotency_level().into()
+        self.idempotency_level
+            .unwrap_or(IdempotencyLevel::IdempotencyUnknown)
     }
 }
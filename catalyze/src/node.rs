@@ -3,10 +3,16 @@ use std::convert::From;
 use std::fmt::{self, Display};
 use std::marker::PhantomData;
 
+use protobuf::{reflect::ReflectValueBox, MessageField, UnknownFields};
+
 use crate::comments::Comments;
+use crate::custom_option::{self, CustomOption, FromValue};
+use crate::error::Error;
 use crate::field::{MapField, OneofField, RepeatedField};
 use crate::file::Syntax;
 use crate::iter::Iter;
+use crate::relative::RelativeName;
+use crate::span::Span;
 use crate::Ast;
 use crate::{
     enum_::{AllEnums, Enum, EnumValue},
@@ -119,6 +125,11 @@ impl Container {
             Container::Message(m) => m.fully_qualified_name(),
         }
     }
+    /// Returns the name of `target`, a message or enum, relative to this
+    /// container.
+    pub fn relative_name(&self, target: impl Into<Node>) -> RelativeName {
+        RelativeName::new(self, target.into())
+    }
     pub(crate) fn register_import(&self, arg: File) {
         match self {
             Container::File(f) => f.mark_import_as_used(arg),
@@ -331,6 +342,21 @@ impl Node {
             Node::Package(_) | Node::File(_) => unreachable!(),
         }
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        match self {
+            Node::Package(p) => p.freeze(),
+            Node::File(f) => f.freeze(),
+            Node::Message(m) => m.freeze(),
+            Node::Field(f) => f.freeze(),
+            Node::Oneof(o) => o.freeze(),
+            Node::Enum(e) => e.freeze(),
+            Node::EnumValue(ev) => ev.freeze(),
+            Node::Service(s) => s.freeze(),
+            Node::Method(m) => m.freeze(),
+            Node::Extension(e) => e.freeze(),
+        }
+    }
 
     pub fn package(&self) -> Package {
         match self {
@@ -347,6 +373,165 @@ impl Node {
         }
     }
 
+    /// The [`File`] the node is defined in. Returns `None` for a
+    /// [`Package`].
+    pub fn file(&self) -> Option<File> {
+        match self {
+            Node::Package(_) => None,
+            Node::File(f) => Some(f.clone()),
+            Node::Message(m) => Some(m.file()),
+            Node::Field(f) => Some(f.file()),
+            Node::Oneof(o) => Some(o.file()),
+            Node::Enum(e) => Some(e.file()),
+            Node::EnumValue(ev) => Some(ev.file()),
+            Node::Service(s) => Some(s.file()),
+            Node::Method(m) => Some(m.file()),
+            Node::Extension(e) => Some(e.file()),
+        }
+    }
+
+    /// The [`Comments`] attached to the node. Returns `None` for a
+    /// [`Package`].
+    pub fn comments(&self) -> Option<Comments> {
+        match self {
+            Node::Package(_) => None,
+            Node::File(f) => Some(f.comments()),
+            Node::Message(m) => Some(m.comments()),
+            Node::Field(f) => Some(f.comments()),
+            Node::Oneof(o) => Some(o.comments()),
+            Node::Enum(e) => Some(e.comments()),
+            Node::EnumValue(ev) => Some(ev.comments()),
+            Node::Service(s) => Some(s.comments()),
+            Node::Method(m) => Some(m.comments()),
+            Node::Extension(e) => Some(e.comments()),
+        }
+    }
+
+    /// The location of the node's definition within its file. For a
+    /// [`File`], this is the location of its `syntax` statement.
+    ///
+    /// Returns `None` for a [`Package`] or if the input did not include
+    /// source code info.
+    pub fn span(&self) -> Option<Span> {
+        let file = self.file()?;
+        let comments = self.comments()?;
+        Span::from_location(file.file_path(), comments.location())
+    }
+
+    /// Returns the value of the custom option `extension` set on the node, or
+    /// `None` if it is not set or does not apply to the node's kind.
+    ///
+    /// Scalars are returned as the corresponding [`ReflectValueBox`] variant,
+    /// enums as [`ReflectValueBox::Enum`] and messages as
+    /// [`ReflectValueBox::Message`]. For a repeated option, the last value is
+    /// returned; see [`custom_option_values`](Self::custom_option_values).
+    pub fn custom_option(&self, extension: &Extension) -> Result<Option<ReflectValueBox>, Error> {
+        Ok(self.custom_option_values(extension)?.pop())
+    }
+
+    /// Returns each value of the custom option `extension` set on the node.
+    ///
+    /// protoc encodes custom options into the unknown fields of the node's
+    /// options message, from which they are decoded using the type of
+    /// `extension`.
+    pub fn custom_option_values(
+        &self,
+        extension: &Extension,
+    ) -> Result<Vec<ReflectValueBox>, Error> {
+        let descriptor = extension.descriptor();
+        let extendee = descriptor.proto().extendee();
+        if self.options_message() != Some(extendee) {
+            return Ok(Vec::new());
+        }
+        match self.option_unknown_fields() {
+            Some(unknown_fields) => custom_option::decode(extension, &unknown_fields),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the custom option `T` set on the node, decoded from the value of
+    /// its extension, [`T::EXTENSION`](CustomOption::EXTENSION).
+    ///
+    /// The extension must be defined in the node's file or one of its
+    /// transitive imports, otherwise [`Error::NodeNotFound`] is returned.
+    /// Returns `None` if the option is not set, or if the node is a package,
+    /// which has no options. For a repeated extension, the last value is
+    /// returned; see [`option_values`](Self::option_values).
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        let Some(extension) = self.option_extension::<T>()? else {
+            return Ok(None);
+        };
+        self.custom_option(&extension)?
+            .map(T::from_value)
+            .transpose()
+    }
+
+    /// Returns each value of the custom option `T` set on the node, in the
+    /// order they appear, for an option whose extension is repeated.
+    ///
+    /// The extension is resolved as for [`option`](Self::option).
+    pub fn option_values<T: CustomOption>(&self) -> Result<Vec<T>, Error> {
+        let Some(extension) = self.option_extension::<T>()? else {
+            return Ok(Vec::new());
+        };
+        self.custom_option_values(&extension)?
+            .into_iter()
+            .map(T::from_value)
+            .collect()
+    }
+
+    /// Resolves the extension of the custom option `T` from the node's file,
+    /// or returns `None` for a package.
+    fn option_extension<T: CustomOption>(&self) -> Result<Option<Extension>, Error> {
+        let Some(file) = self.file() else {
+            return Ok(None);
+        };
+        let fqn = custom_option::extension_name(T::EXTENSION);
+        match file.visible_extension(&fqn) {
+            Some(extension) => Ok(Some(extension)),
+            None => Err(Error::NodeNotFound {
+                fully_qualified_name: fqn,
+            }),
+        }
+    }
+
+    /// The fully qualified name of the options message for the node's kind.
+    fn options_message(&self) -> Option<&'static str> {
+        Some(match self {
+            Node::Package(_) => return None,
+            Node::File(_) => ".google.protobuf.FileOptions",
+            Node::Message(_) => ".google.protobuf.MessageOptions",
+            Node::Field(_) | Node::Extension(_) => ".google.protobuf.FieldOptions",
+            Node::Oneof(_) => ".google.protobuf.OneofOptions",
+            Node::Enum(_) => ".google.protobuf.EnumOptions",
+            Node::EnumValue(_) => ".google.protobuf.EnumValueOptions",
+            Node::Service(_) => ".google.protobuf.ServiceOptions",
+            Node::Method(_) => ".google.protobuf.MethodOptions",
+        })
+    }
+
+    /// The unknown fields of the node's options message, which hold its
+    /// custom options.
+    fn option_unknown_fields(&self) -> Option<UnknownFields> {
+        fn unknown<M: protobuf::Message>(options: &MessageField<M>) -> Option<UnknownFields> {
+            options
+                .as_ref()
+                .map(|o| o.special_fields().unknown_fields().clone())
+        }
+        match self {
+            Node::Package(_) => None,
+            Node::File(f) => unknown(&f.descriptor().proto().options),
+            Node::Message(m) => unknown(&m.descriptor().proto().options),
+            Node::Field(f) => unknown(&f.descriptor().proto().options),
+            Node::Oneof(o) => unknown(&o.descriptor().proto().options),
+            Node::Enum(e) => unknown(&e.descriptor().proto().options),
+            Node::EnumValue(ev) => unknown(&ev.descriptor().proto().options),
+            Node::Service(s) => unknown(&s.descriptor().proto().options),
+            Node::Method(m) => unknown(&m.descriptor().options),
+            Node::Extension(e) => unknown(&e.descriptor().proto().options),
+        }
+    }
+
     pub(crate) fn add_dependent(&self, dep: Message) {
         match self {
             Node::Message(m) => m.add_dependent(dep),
@@ -386,7 +571,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_package(self) -> Result<Package, Self> {
         if let Self::Package(v) = self {
             Ok(v)
@@ -412,7 +596,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_file(self) -> Result<File, Self> {
         if let Self::File(v) = self {
             Ok(v)
@@ -446,7 +629,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_oneof(self) -> Result<Oneof, Self> {
         if let Self::Oneof(v) = self {
             Ok(v)
@@ -472,7 +654,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_enum(self) -> Result<Enum, Self> {
         if let Self::Enum(v) = self {
             Ok(v)
@@ -490,7 +671,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_message(self) -> Result<Message, Self> {
         if let Self::Message(v) = self {
             Ok(v)
@@ -516,7 +696,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_enum_value(self) -> Result<EnumValue, Self> {
         if let Self::EnumValue(v) = self {
             Ok(v)
@@ -542,7 +721,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_service(self) -> Result<Service, Self> {
         if let Self::Service(v) = self {
             Ok(v)
@@ -568,7 +746,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_method(self) -> Result<Method, Self> {
         if let Self::Method(v) = self {
             Ok(v)
@@ -594,7 +771,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_field(self) -> Result<Field, Self> {
         if let Self::Field(v) = self {
             Ok(v)
@@ -620,7 +796,6 @@ impl Node {
         }
     }
 
-    #[must_use]
     pub fn try_into_extension(self) -> Result<Extension, Self> {
         if let Self::Extension(v) = self {
             Ok(v)
@@ -683,10 +858,6 @@ impl Display for Node {
     }
 }
 
-pub trait IntoNode {
-    fn into_node(self) -> Node;
-}
-
 impl From<File> for Node {
     fn from(file: File) -> Self {
         Node::File(file)
@@ -828,7 +999,7 @@ pub enum NodeIter<T = Node> {
     Extensions(Iter<Extension>),
     _Phantom(PhantomData<T>),
 }
-impl<'a, T> NodeIter<T> {
+impl<T> NodeIter<T> {
     pub fn len(&self) -> usize {
         match self {
             NodeIter::Nodes(nodes) => nodes.len(),
@@ -1023,19 +1194,23 @@ impl From<&Ast> for AllNodes {
 
 #[cfg(test)]
 mod tests {
-    use protobuf::reflect::FileDescriptor;
+    use crate::file::File;
+    use crate::package::Package;
+    use crate::test_util::{self, FileBuilder};
 
-    use crate::{enum_::Enum, file::File, message::Message, package::Package};
+    fn file() -> (Package, File) {
+        let mut m1 = test_util::message("M1", vec![]);
+        m1.enum_type
+            .push(test_util::enum_("E1", &["E1_UNSPECIFIED"]));
+        FileBuilder::new("pkg/nodes.proto", "pkg")
+            .message(m1)
+            .message(test_util::message("M2", vec![]))
+            .file()
+    }
 
     #[test]
     fn test_nodes() {
-        let pkg = Package::new("pkg");
-
-        let f = File::new(true, FileDescriptor::default(), pkg.clone()).unwrap();
-        let m1 = Message::new(Default::default(), f.clone().into()).unwrap();
-        let m2 = Message::new(Default::default(), f.clone().into()).unwrap();
-        f.add_node(m1.into());
-        f.add_node(m2.into());
+        let (_pkg, f) = file();
         let mut count = 0;
         for n in f.nodes() {
             count += 1;
@@ -1045,20 +1220,13 @@ mod tests {
     }
     #[test]
     fn test_all_nodes() {
-        let pkg = Package::new("pkg");
-
-        let f = File::new(true, FileDescriptor::default(), pkg.clone()).unwrap();
-        let m1 = Message::new(Default::default(), f.clone().into()).unwrap();
-        let m1e1 = Enum::new(Default::default(), m1.clone().into());
-        m1.add_node(m1e1.into());
-        let m2 = Message::new(Default::default(), f.clone().into()).unwrap();
-        f.add_node(m1.into());
-        f.add_node(m2.into());
+        let (_pkg, f) = file();
         let mut count = 0;
         for n in f.all_nodes() {
             count += 1;
             println!("{:?}", n)
         }
-        assert_eq!(count, 3)
+        // M1, M1.E1, M1.E1.E1_UNSPECIFIED and M2
+        assert_eq!(count, 4)
     }
 }
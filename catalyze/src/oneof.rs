@@ -1,12 +1,11 @@
-use std::{
-    cell::RefCell,
-    rc::{Rc, Weak},
-};
+use crate::sync::{Rc, RefCell, Weak};
 
 use protobuf::reflect::OneofDescriptor;
 
 use crate::{
     comments::Comments,
+    custom_option::CustomOption,
+    error::Error,
     field::Field,
     file::{File, FileRefs, WeakFile},
     iter::Iter,
@@ -16,25 +15,23 @@ use crate::{
     uninterpreted_option::UninterpretedOption,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Options {
-    // message fields
-    ///  The parser stores options it doesn't recognize here. See above.
-    // @@protoc_insertion_point(field:google.protobuf.OneofOptions.uninterpreted_option)
-    pub uninterpreted_option: Vec<UninterpretedOption>,
-    // special fields
-    // @@protoc_insertion_point(special_field:google.protobuf.OneofOptions.special_fields)
-    pub special_fields: protobuf::SpecialFields,
+    uninterpreted_option: Vec<UninterpretedOption>,
 }
 impl Options {
     /// The parser stores options it doesn't recognize here. See above.
     pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+        &self.uninterpreted_option
     }
 }
 impl From<Option<&protobuf::descriptor::OneofOptions>> for Options {
     fn from(opts: Option<&protobuf::descriptor::OneofOptions>) -> Self {
-        todo!()
+        Self {
+            uninterpreted_option: opts
+                .map(|o| o.uninterpreted_option.iter().map(Into::into).collect())
+                .unwrap_or_default(),
+        }
     }
 }
 
@@ -55,12 +52,13 @@ pub struct Oneof(Rc<OneofDetail>);
 impl Oneof {
     pub fn new(descriptor: OneofDescriptor, msg: Message) -> Self {
         let fully_qualified_name = format!("{}.{}", msg.fully_qualified_name(), descriptor.name());
+        let is_synthetic = descriptor.is_synthetic();
         Oneof(Rc::new(OneofDetail {
             descriptor,
             fqn: fully_qualified_name,
             fields: Rc::new(RefCell::new(Vec::default())),
             msg: msg.clone().into(),
-            is_synthetic: true,
+            is_synthetic,
             comments: RefCell::new(Comments::default()),
             imports: Rc::new(RefCell::new(Vec::default())),
         }))
@@ -69,13 +67,17 @@ impl Oneof {
         &self.0.fqn
     }
     pub fn name(&self) -> &str {
-        &self.0.name
+        self.0.descriptor.name()
     }
     pub fn fields(&self) -> Iter<Field> {
         Iter::from(&self.0.fields)
     }
     pub fn comments(&self) -> Comments {
-        *self.0.comments.borrow()
+        self.0.comments.borrow().clone()
+    }
+    /// Returns the custom option `T` set on the oneof; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
     }
     pub fn message(&self) -> Message {
         self.0.msg.clone().into()
@@ -85,7 +87,10 @@ impl Oneof {
         self.0.msg.file()
     }
     pub fn descriptor(&self) -> OneofDescriptor {
-        self.0.descriptor
+        self.0.descriptor.clone()
+    }
+    pub fn options(&self) -> Options {
+        Options::from(self.0.descriptor.proto().options.as_ref())
     }
     pub fn package(&self) -> Package {
         self.file().package()
@@ -108,6 +113,12 @@ impl Oneof {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.fields.freeze();
+        self.0.comments.freeze();
+        self.0.imports.freeze();
+    }
 
     fn downgrade(&self) -> WeakOneof {
         WeakOneof(Rc::downgrade(&self.0))
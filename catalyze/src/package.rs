@@ -1,16 +1,16 @@
+use crate::comments::PackageComments;
 use crate::iter::Iter;
 use crate::well_known;
 use crate::{
     file::File,
-    node::{AllNodes, IntoNode, Node, Nodes},
+    node::{AllNodes, Nodes},
 };
 
-use std::cell::RefCell;
-use std::rc::{Rc, Weak};
-use std::*;
+use crate::sync::{Rc, RefCell, Weak};
 
 #[derive(Debug, Clone)]
 struct PackageDetail {
+    name: String,
     fqn: String,
     files: Rc<RefCell<Vec<File>>>,
     is_well_known: bool,
@@ -23,11 +23,6 @@ impl std::fmt::Display for Package {
         write!(f, "{};", self.0.fqn)
     }
 }
-impl IntoNode for Package {
-    fn into_node(self) -> Node {
-        Node::Package(self)
-    }
-}
 
 impl Package {
     pub fn new(name: &str) -> Self {
@@ -38,6 +33,7 @@ impl Package {
         };
 
         Self(Rc::new(PackageDetail {
+            name: name.to_string(),
             fqn,
             files: Rc::new(RefCell::new(vec![])),
             is_well_known: name == well_known::PACKAGE,
@@ -56,6 +52,11 @@ impl Package {
     pub fn all_nodes(&self) -> AllNodes {
         AllNodes::new(self.clone().into())
     }
+    /// Returns the comments attached to the package statement of each of its
+    /// files which has them.
+    pub fn comments(&self) -> PackageComments {
+        PackageComments::new(self.clone())
+    }
     pub fn files(&self) -> Iter<File> {
         Iter::from(&self.0.files)
     }
@@ -71,6 +72,10 @@ impl Package {
     pub(crate) fn add_file(&self, file: File) {
         self.0.files.borrow_mut().push(file.clone());
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.files.freeze();
+    }
     fn downgrade(&self) -> WeakPackage {
         WeakPackage(Rc::downgrade(&self.0))
     }
@@ -0,0 +1,190 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::error::Error;
+
+/// Parameters passed to a protoc plugin, parsed from the comma-separated
+/// `CodeGeneratorRequest.parameter` string (e.g.
+/// `--rust_opt=out_dir=src,serde,extern=foo,extern=bar`).
+///
+/// Each entry is either a `key=value` pair or a bare flag. Keys may be
+/// repeated, in which case all values are retained in the order they appear.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Parameters {
+    params: HashMap<String, Vec<Option<String>>>,
+    /// The keys of `params`, in the order they first appear.
+    keys: Vec<String>,
+}
+
+impl Parameters {
+    pub fn parse(parameter: &str) -> Self {
+        let mut params: HashMap<String, Vec<Option<String>>> = HashMap::new();
+        let mut keys = Vec::new();
+        for param in parameter.split(',') {
+            let param = param.trim();
+            if param.is_empty() {
+                continue;
+            }
+            let (key, value) = match param.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+                None => (param, None),
+            };
+            params
+                .entry(key.to_string())
+                .or_insert_with(|| {
+                    keys.push(key.to_string());
+                    Vec::new()
+                })
+                .push(value);
+        }
+        Self { params, keys }
+    }
+
+    /// Returns `true` if `key` is present, either as a flag or with a value.
+    pub fn contains(&self, key: &str) -> bool {
+        self.params.contains_key(key)
+    }
+
+    /// Returns each key, in the order it first appears.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Returns the last value given for `key`, if any.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.get_all(key).last()
+    }
+
+    /// Returns every value given for `key`, in order. Bare flags are skipped.
+    pub fn get_all<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a str> {
+        self.params
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_deref())
+    }
+
+    /// Returns `true` if `key` is given as a bare flag or with a value of
+    /// `true`, `false` if it is absent or has a value of `false`.
+    pub fn flag(&self, key: &str) -> Result<bool, Error> {
+        match self.params.get(key).and_then(|v| v.last()) {
+            None => Ok(false),
+            Some(None) => Ok(true),
+            Some(Some(value)) => parse_value(key, value),
+        }
+    }
+
+    /// Parses the last value given for `key`.
+    pub fn value<T>(&self, key: &str) -> Result<Option<T>, Error>
+    where
+        T: FromStr,
+        T::Err: 'static + std::error::Error + Send + Sync,
+    {
+        self.get(key)
+            .map(|value| parse_value(key, value))
+            .transpose()
+    }
+
+    /// Parses the last value given for `key`, returning an error if it is
+    /// absent.
+    pub fn required<T>(&self, key: &str) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: 'static + std::error::Error + Send + Sync,
+    {
+        self.value(key)?.ok_or_else(|| Error::MissingParameter {
+            key: key.to_string(),
+        })
+    }
+
+    /// Parses every value given for `key`.
+    pub fn values<T>(&self, key: &str) -> Result<Vec<T>, Error>
+    where
+        T: FromStr,
+        T::Err: 'static + std::error::Error + Send + Sync,
+    {
+        self.get_all(key)
+            .map(|value| parse_value(key, value))
+            .collect()
+    }
+
+    /// Returns an error naming the first key which is not in `known`.
+    pub fn deny_unknown(&self, known: &[&str]) -> Result<(), Error> {
+        match self.keys().find(|key| !known.contains(key)) {
+            Some(key) => Err(Error::UnknownParameter {
+                key: key.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_value<T>(key: &str, value: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: 'static + std::error::Error + Send + Sync,
+{
+    value.parse().map_err(|err| Error::InvalidParameter {
+        key: key.to_string(),
+        value: value.to_string(),
+        source: Box::new(err),
+    })
+}
+
+/// Converts [`Parameters`] into a typed [`Input::Parameter`](crate::Input::Parameter).
+pub trait FromParameters: Sized {
+    fn from_parameters(parameters: &Parameters) -> Result<Self, Error>;
+}
+
+impl FromParameters for () {
+    fn from_parameters(_: &Parameters) -> Result<Self, Error> {
+        Ok(())
+    }
+}
+
+impl FromParameters for Parameters {
+    fn from_parameters(parameters: &Parameters) -> Result<Self, Error> {
+        Ok(parameters.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let params = Parameters::parse("out_dir=src, serde,extern=foo,extern=bar,,n=3");
+        assert_eq!(params.get("out_dir"), Some("src"));
+        assert!(params.flag("serde").unwrap());
+        assert!(!params.flag("missing").unwrap());
+        assert_eq!(
+            params.get_all("extern").collect::<Vec<_>>(),
+            vec!["foo", "bar"]
+        );
+        assert_eq!(params.value::<u32>("n").unwrap(), Some(3));
+        assert!(matches!(
+            params.value::<u32>("out_dir"),
+            Err(Error::InvalidParameter { key, .. }) if key == "out_dir"
+        ));
+        assert!(matches!(
+            params.required::<String>("missing"),
+            Err(Error::MissingParameter { key }) if key == "missing"
+        ));
+        assert!(matches!(
+            params.deny_unknown(&["out_dir", "serde", "extern"]),
+            Err(Error::UnknownParameter { key }) if key == "n"
+        ));
+        assert!(matches!(
+            Parameters::parse("b,a,c,d,e,f").deny_unknown(&["a"]),
+            Err(Error::UnknownParameter { key }) if key == "b"
+        ));
+        assert_eq!(
+            params.keys().collect::<Vec<_>>(),
+            vec!["out_dir", "serde", "extern", "n"]
+        );
+    }
+}
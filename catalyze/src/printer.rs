@@ -0,0 +1,755 @@
+use std::fmt::Write;
+
+use protobuf::{
+    descriptor::{
+        field_descriptor_proto::{Label, Type},
+        FieldDescriptorProto,
+    },
+    reflect::{ReflectValueRef, RuntimeFieldType},
+    MessageDyn,
+};
+
+use crate::{
+    comments::Comments, enum_::Enum, extension::Extension, field::Field, file::File,
+    message::Message, oneof::Oneof, service::Service,
+};
+
+/// The largest field number, used to print `max` in ranges.
+const MAX_FIELD_NUMBER: i32 = 536_870_911;
+
+/// `Printer` renders a [`File`] back to protobuf IDL, including its comments.
+///
+/// Type references are written fully qualified (e.g. `.pkg.Message`).
+/// Options defined by `descriptor.proto` are written; custom options are not
+/// (see [`Node::custom_option`](crate::Node::custom_option)). Groups are
+/// written as message fields.
+#[derive(Debug, Clone)]
+pub struct Printer {
+    indent: String,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+        }
+    }
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the string used for each level of indentation. Defaults to two
+    /// spaces.
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// Returns the `.proto` source of `file`.
+    pub fn print(&self, file: &File) -> String {
+        let mut w = Writer {
+            out: String::new(),
+            indent: &self.indent,
+            depth: 0,
+        };
+        w.file(file);
+        w.out
+    }
+}
+
+struct Writer<'a> {
+    out: String,
+    indent: &'a str,
+    depth: usize,
+}
+
+impl Writer<'_> {
+    fn line(&mut self, line: impl AsRef<str>) {
+        let line = line.as_ref();
+        if !line.is_empty() {
+            for _ in 0..self.depth {
+                self.out.push_str(self.indent);
+            }
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    fn blank(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("{\n") && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn comment(&mut self, text: &str) {
+        for line in text.strip_suffix('\n').unwrap_or(text).split('\n') {
+            self.line(format!("//{line}"));
+        }
+    }
+
+    /// Writes `decl`, preceded by the detached and leading comments and
+    /// followed by the trailing comment of `comments`.
+    fn commented(&mut self, comments: &Comments, decl: impl AsRef<str>) {
+        for detached in comments.leading_detached() {
+            self.comment(detached);
+            self.line("");
+        }
+        self.comment_if_any(comments.leading());
+        let trailing = comments.trailing();
+        match trailing.strip_suffix('\n').unwrap_or(trailing) {
+            t if !t.is_empty() && !t.contains('\n') => {
+                self.line(format!("{} //{t}", decl.as_ref()));
+            }
+            _ => {
+                self.line(decl);
+                self.comment_if_any(trailing);
+            }
+        }
+    }
+
+    fn comment_if_any(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.comment(text);
+        }
+    }
+
+    fn options(&mut self, options: Option<&dyn MessageDyn>) {
+        for (name, value) in options.map(option_values).unwrap_or_default() {
+            self.line(format!("option {name} = {value};"));
+        }
+    }
+
+    fn file(&mut self, file: &File) {
+        let descriptor = file.descriptor();
+        let proto = descriptor.proto();
+
+        self.commented(&file.comments(), format!("syntax = \"{}\";", file.syntax()));
+        if !proto.package().is_empty() {
+            self.blank();
+            self.commented(
+                &file.package_comments(),
+                format!("package {};", proto.package()),
+            );
+        }
+        if !proto.dependency.is_empty() {
+            self.blank();
+            for (i, dep) in proto.dependency.iter().enumerate() {
+                let i = i as i32;
+                let modifier = if proto.public_dependency.contains(&i) {
+                    "public "
+                } else if proto.weak_dependency.contains(&i) {
+                    "weak "
+                } else {
+                    ""
+                };
+                self.line(format!("import {modifier}\"{}\";", escape(dep)));
+            }
+        }
+        if proto.options.is_some() {
+            self.blank();
+            self.options(proto.options.as_ref().map(|o| o as &dyn MessageDyn));
+        }
+        for message in file.messages() {
+            self.blank();
+            self.message(&message);
+        }
+        for enum_ in file.enums() {
+            self.blank();
+            self.enum_(&enum_);
+        }
+        for service in file.services() {
+            self.blank();
+            self.service(&service);
+        }
+        self.extensions(file.defined_extensions());
+    }
+
+    fn message(&mut self, message: &Message) {
+        let descriptor = message.descriptor();
+        let proto = descriptor.proto();
+        self.commented(
+            &message.comments(),
+            format!("message {} {{", message.name()),
+        );
+        self.depth += 1;
+        self.options(proto.options.as_ref().map(|o| o as &dyn MessageDyn));
+
+        let mut printed_oneofs: Vec<String> = Vec::new();
+        for field in message.fields() {
+            let oneof = message
+                .real_oneofs()
+                .find(|o| o.fields().any(|f| f.name() == field.name()));
+            match oneof {
+                Some(oneof) if !printed_oneofs.iter().any(|n| n == oneof.name()) => {
+                    printed_oneofs.push(oneof.name().to_string());
+                    self.oneof(&oneof);
+                }
+                Some(_) => {}
+                None => self.field(&field, message),
+            }
+        }
+        for nested in message.messages().filter(|m| !m.is_map_entry()) {
+            self.blank();
+            self.message(&nested);
+        }
+        for enum_ in message.enums() {
+            self.blank();
+            self.enum_(&enum_);
+        }
+        self.extensions(message.defined_extensions());
+
+        if !proto.extension_range.is_empty() {
+            let ranges = proto
+                .extension_range
+                .iter()
+                .map(|r| range(r.start(), r.end() - 1, MAX_FIELD_NUMBER))
+                .collect::<Vec<_>>();
+            self.line(format!("extensions {};", ranges.join(", ")));
+        }
+        let reserved = message.reserved_ranges();
+        if !reserved.is_empty() {
+            let ranges = reserved
+                .iter()
+                .map(|r| range(r.start(), r.end() - 1, MAX_FIELD_NUMBER))
+                .collect::<Vec<_>>();
+            self.line(format!("reserved {};", ranges.join(", ")));
+        }
+        let reserved_names = message.reserved_names();
+        if !reserved_names.is_empty() {
+            self.line(format!("reserved {};", quoted_names(&reserved_names)));
+        }
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn oneof(&mut self, oneof: &Oneof) {
+        let descriptor = oneof.descriptor();
+        let proto = descriptor.proto();
+        self.commented(&oneof.comments(), format!("oneof {} {{", oneof.name()));
+        self.depth += 1;
+        self.options(proto.options.as_ref().map(|o| o as &dyn MessageDyn));
+        let message = oneof.message();
+        for field in oneof.fields() {
+            self.field(&field, &message);
+        }
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn field(&mut self, field: &Field, message: &Message) {
+        let descriptor = field.descriptor();
+        let proto = descriptor.proto();
+        let decl = if let Some((key, value)) = map_types(field, message) {
+            format!("map<{key}, {value}> {} = {}", proto.name(), proto.number())
+        } else {
+            let label = if field.is_in_real_oneof() {
+                ""
+            } else {
+                label(proto, field.syntax().is_proto2())
+            };
+            format!(
+                "{label}{} {} = {}",
+                type_name(proto),
+                proto.name(),
+                proto.number()
+            )
+        };
+        self.commented(
+            &field.comments(),
+            format!("{decl}{};", field_options(proto)),
+        );
+    }
+
+    fn enum_(&mut self, enum_: &Enum) {
+        let descriptor = enum_.descriptor();
+        let proto = descriptor.proto();
+        self.commented(&enum_.comments(), format!("enum {} {{", enum_.name()));
+        self.depth += 1;
+        self.options(proto.options.as_ref().map(|o| o as &dyn MessageDyn));
+        for value in enum_.values() {
+            let descriptor = value.descriptor();
+            let options = descriptor
+                .proto()
+                .options
+                .as_ref()
+                .map(|o| option_values(o as &dyn MessageDyn))
+                .unwrap_or_default();
+            self.commented(
+                &value.comments(),
+                format!(
+                    "{} = {}{};",
+                    value.name(),
+                    value.number(),
+                    bracketed(options)
+                ),
+            );
+        }
+        if !proto.reserved_range.is_empty() {
+            let ranges = proto
+                .reserved_range
+                .iter()
+                .map(|r| range(r.start(), r.end(), i32::MAX))
+                .collect::<Vec<_>>();
+            self.line(format!("reserved {};", ranges.join(", ")));
+        }
+        if !proto.reserved_name.is_empty() {
+            self.line(format!("reserved {};", quoted_names(&proto.reserved_name)));
+        }
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn service(&mut self, service: &Service) {
+        let descriptor = service.descriptor();
+        let proto = descriptor.proto();
+        self.commented(
+            &service.comments(),
+            format!("service {} {{", service.name()),
+        );
+        self.depth += 1;
+        self.options(proto.options.as_ref().map(|o| o as &dyn MessageDyn));
+        for method in service.methods() {
+            let descriptor = method.descriptor();
+            let stream = |streaming: bool| if streaming { "stream " } else { "" };
+            let decl = format!(
+                "rpc {}({}{}) returns ({}{})",
+                method.name(),
+                stream(descriptor.client_streaming()),
+                descriptor.input_type(),
+                stream(descriptor.server_streaming()),
+                descriptor.output_type(),
+            );
+            let options = descriptor
+                .options
+                .as_ref()
+                .map(|o| option_values(o as &dyn MessageDyn))
+                .unwrap_or_default();
+            if options.is_empty() {
+                self.commented(&method.comments(), format!("{decl};"));
+            } else {
+                self.commented(&method.comments(), format!("{decl} {{"));
+                self.depth += 1;
+                for (name, value) in options {
+                    self.line(format!("option {name} = {value};"));
+                }
+                self.depth -= 1;
+                self.line("}");
+            }
+        }
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    /// Writes `extensions`, grouped into `extend` blocks by extendee in the
+    /// order each extendee is first seen.
+    fn extensions(&mut self, extensions: impl Iterator<Item = Extension>) {
+        let mut groups: Vec<(String, Vec<Extension>)> = Vec::new();
+        for ext in extensions {
+            let extendee = ext.descriptor().proto().extendee().to_string();
+            match groups.iter_mut().find(|(e, _)| *e == extendee) {
+                Some((_, exts)) => exts.push(ext),
+                None => groups.push((extendee, vec![ext])),
+            }
+        }
+        for (extendee, exts) in groups {
+            self.blank();
+            self.line(format!("extend {extendee} {{"));
+            self.depth += 1;
+            for ext in exts {
+                let descriptor = ext.descriptor();
+                let proto = descriptor.proto();
+                let decl = format!(
+                    "{}{} {} = {}{};",
+                    label(proto, true),
+                    type_name(proto),
+                    proto.name(),
+                    proto.number(),
+                    field_options(proto)
+                );
+                self.commented(&ext.comments(), decl);
+            }
+            self.depth -= 1;
+            self.line("}");
+        }
+    }
+}
+
+/// Returns the label of a field, followed by a space, if it must be written.
+fn label(proto: &FieldDescriptorProto, is_proto2: bool) -> &'static str {
+    match proto.label() {
+        Label::LABEL_REPEATED => "repeated ",
+        Label::LABEL_REQUIRED => "required ",
+        Label::LABEL_OPTIONAL if is_proto2 || proto.proto3_optional() => "optional ",
+        Label::LABEL_OPTIONAL => "",
+    }
+}
+
+/// Returns the key and value types of a map field of `message`.
+pub(crate) fn map_types(field: &Field, message: &Message) -> Option<(String, String)> {
+    if !field.is_map() {
+        return None;
+    }
+    let descriptor = field.descriptor();
+    let entry = message
+        .maps()
+        .find(|m| m.fully_qualified_name() == descriptor.proto().type_name())?
+        .descriptor();
+    let fields = &entry.proto().field;
+    Some((type_name(fields.first()?), type_name(fields.get(1)?)))
+}
+
+/// Returns the name of a scalar type, or the fully qualified name of a message
+/// or enum type.
+pub(crate) fn type_name(proto: &FieldDescriptorProto) -> String {
+    let name = match proto.type_() {
+        Type::TYPE_DOUBLE => "double",
+        Type::TYPE_FLOAT => "float",
+        Type::TYPE_INT64 => "int64",
+        Type::TYPE_UINT64 => "uint64",
+        Type::TYPE_INT32 => "int32",
+        Type::TYPE_FIXED64 => "fixed64",
+        Type::TYPE_FIXED32 => "fixed32",
+        Type::TYPE_BOOL => "bool",
+        Type::TYPE_STRING => "string",
+        Type::TYPE_BYTES => "bytes",
+        Type::TYPE_UINT32 => "uint32",
+        Type::TYPE_SFIXED32 => "sfixed32",
+        Type::TYPE_SFIXED64 => "sfixed64",
+        Type::TYPE_SINT32 => "sint32",
+        Type::TYPE_SINT64 => "sint64",
+        Type::TYPE_GROUP | Type::TYPE_MESSAGE | Type::TYPE_ENUM => proto.type_name(),
+    };
+    name.to_string()
+}
+
+/// Returns the bracketed options of a field, including `default` and a
+/// `json_name` which differs from the one protoc would derive.
+fn field_options(proto: &FieldDescriptorProto) -> String {
+    let mut options = Vec::new();
+    if proto.has_default_value() {
+        let value = match proto.type_() {
+            Type::TYPE_STRING => format!("\"{}\"", escape(proto.default_value())),
+            // protoc stores bytes defaults already escaped
+            Type::TYPE_BYTES => format!("\"{}\"", proto.default_value()),
+            _ => proto.default_value().to_string(),
+        };
+        options.push(("default".to_string(), value));
+    }
+    if proto.has_json_name() && proto.json_name() != json_name(proto.name()) {
+        options.push((
+            "json_name".to_string(),
+            format!("\"{}\"", escape(proto.json_name())),
+        ));
+    }
+    if let Some(opts) = proto.options.as_ref() {
+        options.extend(option_values(opts as &dyn MessageDyn));
+    }
+    bracketed(options)
+}
+
+fn bracketed(options: Vec<(String, String)>) -> String {
+    if options.is_empty() {
+        return String::new();
+    }
+    let options = options
+        .into_iter()
+        .map(|(name, value)| format!("{name} = {value}"))
+        .collect::<Vec<_>>();
+    format!(" [{}]", options.join(", "))
+}
+
+/// Returns the name and value of each singular option set on `options`.
+/// Message-typed options and custom options, which protoc passes as unknown
+/// fields, are not included.
+pub(crate) fn option_fields(options: &dyn MessageDyn) -> Vec<(String, ReflectValueRef<'_>)> {
+    let mut values = Vec::new();
+    for field in options.descriptor_dyn().fields() {
+        if !matches!(field.runtime_field_type(), RuntimeFieldType::Singular(_)) {
+            continue;
+        }
+        match field.get_singular(options) {
+            None | Some(ReflectValueRef::Message(_)) => {}
+            Some(value) => values.push((field.name().to_string(), value)),
+        }
+    }
+    values
+}
+
+/// Returns the name and formatted value of each option in
+/// [`option_fields`].
+fn option_values(options: &dyn MessageDyn) -> Vec<(String, String)> {
+    option_fields(options)
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                ReflectValueRef::String(s) => format!("\"{}\"", escape(s)),
+                ReflectValueRef::Bytes(b) => {
+                    format!("\"{}\"", escape(&String::from_utf8_lossy(b)))
+                }
+                ReflectValueRef::Enum(e, n) => e
+                    .value_by_number(n)
+                    .map(|v| v.name().to_string())
+                    .unwrap_or_else(|| n.to_string()),
+                v => v.to_string(),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// Derives the JSON name of a field as protoc does.
+pub(crate) fn json_name(name: &str) -> String {
+    let mut json = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            json.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            json.push(c);
+        }
+    }
+    json
+}
+
+/// Formats an inclusive range, as written in `reserved` and `extensions`
+/// statements.
+fn range(start: i32, end: i32, max: i32) -> String {
+    match end {
+        end if end == start => start.to_string(),
+        end if end >= max => format!("{start} to max"),
+        end => format!("{start} to {end}"),
+    }
+}
+
+fn quoted_names(names: &[String]) -> String {
+    let mut out = String::new();
+    for (i, name) in names.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write!(out, "\"{}\"", escape(name)).unwrap();
+    }
+    out
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::descriptor::{
+        descriptor_proto::ReservedRange, enum_descriptor_proto::EnumReservedRange,
+        source_code_info::Location, SourceCodeInfo,
+    };
+
+    use super::*;
+    use crate::{
+        package::Package,
+        test_util::{self, kitchen, FileBuilder},
+    };
+
+    #[test]
+    fn test_range() {
+        assert_eq!(range(5, 5, MAX_FIELD_NUMBER), "5");
+        assert_eq!(range(5, 9, MAX_FIELD_NUMBER), "5 to 9");
+        assert_eq!(range(100, MAX_FIELD_NUMBER, MAX_FIELD_NUMBER), "100 to max");
+    }
+
+    #[test]
+    fn test_json_name() {
+        assert_eq!(json_name("foo_bar_baz"), "fooBarBaz");
+        assert_eq!(json_name("foo"), "foo");
+    }
+
+    #[test]
+    fn test_print_kitchen() {
+        let ast = kitchen();
+        let printed = Printer::new().print(&ast.file("kitchen/kitchen.proto").unwrap());
+        let kitchen = printed.split("\nmessage SauteRequest").next().unwrap();
+        assert_eq!(
+            kitchen,
+            r#"syntax = "proto3";
+
+package kitchen;
+
+import "kitchen/sink.proto";
+import "google/protobuf/timestamp.proto";
+
+option go_package = "github.com/lyft/protoc-gen-star/testdata/generated/kitchen";
+
+message Kitchen {
+  .kitchen.Style style = 1;
+  .kitchen.Sink sink = 2;
+  repeated string utensils = 4;
+  map<string, uint32> dish_counts = 5;
+  repeated .kitchen.Color wall_colors = 6;
+  map<string, .kitchen.Color> appliance_colors = 7;
+}
+
+message Color {
+  oneof model {
+    string pantone = 1;
+    .kitchen.Color.RGB rgb = 2;
+    .kitchen.Color.CMYK cmyk = 3;
+  }
+  double alpha = 4;
+
+  message RGB {
+    uint32 r = 1;
+    uint32 g = 2;
+    uint32 b = 3;
+  }
+
+  message CMYK {
+    uint32 c = 1;
+    uint32 m = 2;
+    uint32 y = 3;
+    uint32 k = 4;
+  }
+}
+"#
+        );
+        assert!(printed.ends_with(
+            r#"
+service Cooking {
+  rpc Saute(.kitchen.SauteRequest) returns (.kitchen.SauteResponse);
+  rpc DispenseIce(.kitchen.IceRequest) returns (stream .kitchen.IceResponse);
+  rpc LoadFridge(stream .kitchen.GroceryItem) returns (.kitchen.LoadSummary);
+  rpc OrderDrinks(stream .kitchen.DrinkOrder) returns (stream .kitchen.PreparedDrink);
+  rpc CheckBestByDate(.kitchen.GroceryItem) returns (.google.protobuf.Timestamp);
+}
+"#
+        ));
+    }
+
+    /// `kitchen.proto` has neither comments nor reserved ranges.
+    fn commented() -> (Package, File) {
+        let id = test_util::field("id", Label::LABEL_OPTIONAL, Type::TYPE_INT32, "");
+        let mut message = test_util::message("Reserved", vec![id]);
+        for (start, end) in [(2, 3), (5, 8), (100, MAX_FIELD_NUMBER + 1)] {
+            let mut range = ReservedRange::new();
+            range.set_start(start);
+            range.set_end(end);
+            message.reserved_range.push(range);
+        }
+        message.reserved_name = vec!["foo".to_string(), "bar".to_string()];
+
+        let mut enum_ = test_util::enum_("Kind", &["KIND_UNSPECIFIED"]);
+        for (start, end) in [(2, 2), (10, i32::MAX)] {
+            let mut range = EnumReservedRange::new();
+            range.set_start(start);
+            range.set_end(end);
+            enum_.reserved_range.push(range);
+        }
+        enum_.reserved_name.push("OLD".to_string());
+
+        let location = |path: &[i32], leading: &str, trailing: &str, detached: &[&str]| {
+            let mut location = Location::new();
+            location.path = path.to_vec();
+            if !leading.is_empty() {
+                location.set_leading_comments(leading.to_string());
+            }
+            if !trailing.is_empty() {
+                location.set_trailing_comments(trailing.to_string());
+            }
+            location.leading_detached_comments = detached.iter().map(|d| d.to_string()).collect();
+            location
+        };
+        let mut info = SourceCodeInfo::new();
+        info.location = vec![
+            location(&[12], " Syntax\n", "", &[" Detached\n"]),
+            location(&[2], "", " Package\n", &[]),
+            location(&[4, 0], " Leading\n", "", &[]),
+            location(&[4, 0, 2, 0], "", " Trailing\n", &[]),
+            location(&[5, 0], " Multi\n line\n", "", &[]),
+            location(&[5, 0, 2, 0], "", " Trailing\n block\n", &[]),
+        ];
+
+        FileBuilder::new("pkg/commented.proto", "pkg")
+            .message(message)
+            .enum_(enum_)
+            .source_code_info(info)
+            .file()
+    }
+
+    #[test]
+    fn test_print_comments_and_reserved() {
+        let (_pkg, file) = commented();
+        assert_eq!(
+            Printer::new().indent("\t").print(&file),
+            r#"// Detached
+
+// Syntax
+syntax = "proto3";
+
+package pkg; // Package
+
+// Leading
+message Reserved {
+	int32 id = 1; // Trailing
+	reserved 2, 5 to 7, 100 to max;
+	reserved "foo", "bar";
+}
+
+// Multi
+// line
+enum Kind {
+	KIND_UNSPECIFIED = 0;
+	// Trailing
+	// block
+	reserved 2, 10 to max;
+	reserved "OLD";
+}
+"#
+        );
+    }
+
+    /// The printed files parse back into the descriptors they were printed
+    /// from.
+    #[cfg(feature = "parse")]
+    #[test]
+    fn test_print_reparse() {
+        let ast = kitchen();
+        let dir = std::env::temp_dir().join("catalyze-test-printer");
+        let names = ["kitchen/kitchen.proto", "kitchen/sink.proto"];
+        for name in names {
+            let path = dir.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, Printer::new().print(&ast.file(name).unwrap())).unwrap();
+        }
+        let parsed = protobuf_parse::Parser::new()
+            .pure()
+            .include(&dir)
+            .inputs(names.map(|name| dir.join(name)))
+            .parse_and_typecheck()
+            .unwrap();
+        for name in names {
+            let mut expected = ast.file(name).unwrap().descriptor().proto().clone();
+            expected.source_code_info.clear();
+            let reparsed = parsed
+                .file_descriptors
+                .iter()
+                .find(|f| f.name() == name)
+                .unwrap();
+            assert_eq!(reparsed, &expected, "{name}");
+        }
+    }
+}
@@ -9,12 +9,12 @@ pub struct Reserved {
 impl Reserved {
     /// Inclusive.
     pub fn start(&self) -> i32 {
-        self.range.start
+        self.start.unwrap_or_default()
     }
 
     /// Exclusive.
     pub fn end(&self) -> i32 {
-        self.range.end
+        self.end.unwrap_or_default()
     }
 
     pub fn in_range(&self, val: i32) -> bool {
@@ -35,49 +35,46 @@ impl From<protobuf::descriptor::descriptor_proto::ReservedRange> for Reserved {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Range of field numbers reserved for extensions of a message.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Extension {
-    // message fields
-    // @@protoc_insertion_point(field:google.protobuf.DescriptorProto.ExtensionRange.start)
-    pub start: ::std::option::Option<i32>,
-    // @@protoc_insertion_point(field:google.protobuf.DescriptorProto.ExtensionRange.end)
-    pub end: ::std::option::Option<i32>,
-    // @@protoc_insertion_point(field:google.protobuf.DescriptorProto.ExtensionRange.options)
-    pub options: crate::MessageField<protobuf::descriptor::ExtensionRangeOptions>,
-    // special fields
-    // @@protoc_insertion_point(special_field:google.protobuf.DescriptorProto.ExtensionRange.special_fields)
-}
-impl PartialEq for Extension {
-    fn eq(&self, other: &Self) -> bool {
-        self.range.start() == other.start() && self.end() == other.end()
-    }
+    start: Option<i32>,
+    end: Option<i32>,
+    options: Option<protobuf::descriptor::ExtensionRangeOptions>,
 }
 impl Extension {
     /// Inclusive.
     pub fn start(&self) -> i32 {
-        self.range.start()
+        self.start.unwrap_or_default()
     }
     /// Exclusive.
     pub fn end(&self) -> i32 {
-        self.range.end()
+        self.end.unwrap_or_default()
     }
     pub fn in_range(&self, val: i32) -> bool {
         self.start() <= val && val < self.end()
     }
+    pub fn options(&self) -> Option<&protobuf::descriptor::ExtensionRangeOptions> {
+        self.options.as_ref()
+    }
 }
-impl From<&'a protobuf::descriptor::descriptor_proto::ExtensionRange> for Extension {
-    fn from(range: &'a protobuf::descriptor::descriptor_proto::ExtensionRange) -> Self {
-        Extension { range }
+impl From<&protobuf::descriptor::descriptor_proto::ExtensionRange> for Extension {
+    fn from(range: &protobuf::descriptor::descriptor_proto::ExtensionRange) -> Self {
+        Extension {
+            start: range.start,
+            end: range.end,
+            options: range.options.as_ref().cloned(),
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ExtensionRanges {
-    ranges: &'a [protobuf::descriptor::descriptor_proto::ExtensionRange],
+    ranges: Vec<Extension>,
 }
 impl ExtensionRanges {
-    pub fn iter(&self) -> ExtensionRangeIter {
-        self.ranges.into()
+    pub fn iter(&self) -> std::slice::Iter<'_, Extension> {
+        self.ranges.iter()
     }
     pub fn len(&self) -> usize {
         self.ranges.len()
@@ -85,71 +82,78 @@ impl ExtensionRanges {
     pub fn is_empty(&self) -> bool {
         self.ranges.is_empty()
     }
-    pub fn first(&self) -> Option<Extension> {
-        self.ranges.first().map(|r| r.into())
+    pub fn first(&self) -> Option<&Extension> {
+        self.ranges.first()
     }
-    pub fn last(&self) -> Option<Extension> {
-        self.ranges.last().map(|r| r.into())
+    pub fn last(&self) -> Option<&Extension> {
+        self.ranges.last()
     }
-    pub fn get(&self, n: usize) -> Option<Extension> {
-        self.ranges.get(n).map(|r| r.into())
+    pub fn get(&self, n: usize) -> Option<&Extension> {
+        self.ranges.get(n)
+    }
+    pub fn is_in_range(&self, num: i32) -> bool {
+        self.iter().any(|r| r.in_range(num))
     }
 }
-impl IntoIterator for ExtensionRanges {
-    type Item = Extension;
-    type IntoIter = ExtensionRangeIter;
+impl<'a> IntoIterator for &'a ExtensionRanges {
+    type Item = &'a Extension;
+    type IntoIter = std::slice::Iter<'a, Extension>;
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
-impl From<&'a Vec<protobuf::descriptor::descriptor_proto::ExtensionRange>> for ExtensionRanges {
-    fn from(ranges: &'a Vec<protobuf::descriptor::descriptor_proto::ExtensionRange>) -> Self {
-        ExtensionRanges { ranges }
+impl From<&[protobuf::descriptor::descriptor_proto::ExtensionRange]> for ExtensionRanges {
+    fn from(ranges: &[protobuf::descriptor::descriptor_proto::ExtensionRange]) -> Self {
+        ExtensionRanges {
+            ranges: ranges.iter().map(Extension::from).collect(),
+        }
     }
 }
 
-// Range of reserved numeric values. Reserved values may not be used by
+/// Range of reserved numeric values. Reserved values may not be used by
 /// entries in the same enum. Reserved ranges may not overlap.
 ///
 /// Note that this is distinct from DescriptorProto.ReservedRange in that it
 /// is inclusive such that it can appropriately represent the entire int32
 /// domain.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EnumReservedRange {
-    rr: &'a protobuf::descriptor::enum_descriptor_proto::EnumReservedRange,
+    start: Option<i32>,
+    end: Option<i32>,
 }
-impl From<&'a protobuf::descriptor::enum_descriptor_proto::EnumReservedRange>
-    for EnumReservedRange
-{
-    fn from(r: &'a protobuf::descriptor::enum_descriptor_proto::EnumReservedRange) -> Self {
-        Self { rr: r }
+impl From<&protobuf::descriptor::enum_descriptor_proto::EnumReservedRange> for EnumReservedRange {
+    fn from(r: &protobuf::descriptor::enum_descriptor_proto::EnumReservedRange) -> Self {
+        Self {
+            start: r.start,
+            end: r.end,
+        }
     }
 }
 impl EnumReservedRange {
     /// Inclusive
     pub fn start(&self) -> i32 {
-        self.rr.start()
+        self.start.unwrap_or_default()
     }
     /// Inclusive
     pub fn end(&self) -> i32 {
-        self.rr.end()
+        self.end.unwrap_or_default()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct EnumReservedRanges {
-    ranges: &'a [protobuf::descriptor::enum_descriptor_proto::EnumReservedRange],
+    ranges: Vec<EnumReservedRange>,
 }
-impl IntoIterator for EnumReservedRanges {
-    type Item = EnumReservedRange;
-    type IntoIter = EnumReservedRangeIter;
+impl<'a> IntoIterator for &'a EnumReservedRanges {
+    type Item = &'a EnumReservedRange;
+    type IntoIter = std::slice::Iter<'a, EnumReservedRange>;
     fn into_iter(self) -> Self::IntoIter {
-        self.ranges.into()
+        self.iter()
     }
 }
 impl EnumReservedRanges {
-    pub fn iter(&self) -> EnumReservedRangeIter {
-        self.ranges.into()
+    pub fn iter(&self) -> std::slice::Iter<'_, EnumReservedRange> {
+        self.ranges.iter()
     }
     pub fn len(&self) -> usize {
         self.ranges.len()
@@ -157,8 +161,8 @@ impl EnumReservedRanges {
     pub fn is_empty(&self) -> bool {
         self.ranges.is_empty()
     }
-    pub fn get(&self, index: usize) -> Option<EnumReservedRange> {
-        self.ranges.get(index).map(|r| r.into())
+    pub fn get(&self, index: usize) -> Option<&EnumReservedRange> {
+        self.ranges.get(index)
     }
     pub fn is_range_reserved(&self, min: i32, max: i32) -> bool {
         self.iter().any(|r| r.start() <= min && r.end() >= max)
@@ -167,12 +171,12 @@ impl EnumReservedRanges {
         self.iter().any(|r| r.start() <= num && r.end() >= num)
     }
 }
-impl From<&'a Vec<protobuf::descriptor::enum_descriptor_proto::EnumReservedRange>>
+impl From<&[protobuf::descriptor::enum_descriptor_proto::EnumReservedRange]>
     for EnumReservedRanges
 {
-    fn from(
-        ranges: &'a Vec<protobuf::descriptor::enum_descriptor_proto::EnumReservedRange>,
-    ) -> Self {
-        Self { ranges }
+    fn from(ranges: &[protobuf::descriptor::enum_descriptor_proto::EnumReservedRange]) -> Self {
+        Self {
+            ranges: ranges.iter().map(EnumReservedRange::from).collect(),
+        }
     }
 }
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::{
+    file::File,
+    node::{Container, Node},
+    package::Package,
+};
+
+/// The name of a [`Message`](crate::message::Message) or
+/// [`Enum`](crate::enum_::Enum) relative to the [`Container`] which refers to
+/// it.
+///
+/// Within the same package, the scopes shared by the referencing container and
+/// the target (outer messages) are dropped. For example, from within
+/// `.pkg.Outer`, `.pkg.Outer.Inner` is `Inner` while `.pkg.Other` is `Other`,
+/// [`up`](Self::up) one scope.
+///
+/// Across packages, the segments are those of the target within its package,
+/// which is returned by [`package`](Self::package).
+///
+/// Displayed, the name is the shortest which protoc resolves to the target from
+/// the referencing container. Symbols of the container's file and its
+/// transitive imports are taken into account: from within `.pkg.Outer`, which
+/// nests a message `Other`, `.pkg.Other` is displayed as `pkg.Other`.
+#[derive(Debug, Clone)]
+pub struct RelativeName {
+    segments: Vec<String>,
+    up: usize,
+    package: Option<Package>,
+    file: Option<File>,
+    import: bool,
+    display: String,
+}
+
+impl RelativeName {
+    pub(crate) fn new(scope: &Container, target: Node) -> Self {
+        let scope_package = scope.package();
+        let target_package = match target.file() {
+            Some(file) => file.package(),
+            None => scope_package.clone(),
+        };
+        let scope_segments = segments(scope.fully_qualified_name(), &scope_package);
+        let target_segments = segments(target.fully_qualified_name(), &target_package);
+        let is_same_package =
+            scope_package.fully_qualified_name() == target_package.fully_qualified_name();
+
+        let shared = if is_same_package {
+            scope_segments
+                .iter()
+                .zip(&target_segments)
+                .take(target_segments.len().saturating_sub(1))
+                .take_while(|(a, b)| a == b)
+                .count()
+        } else {
+            0
+        };
+        let file = target.file();
+        let scope_file = match scope {
+            Container::File(f) => f.clone(),
+            Container::Message(m) => m.file(),
+        };
+        let import = file.as_ref().is_some_and(|file| *file != scope_file);
+        let display = unambiguous(
+            scope.fully_qualified_name(),
+            target.fully_qualified_name(),
+            |fqn| scope_file.is_visible_symbol(fqn),
+        );
+        Self {
+            segments: target_segments[shared..]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            up: scope_segments.len() - shared,
+            package: (!is_same_package).then_some(target_package),
+            file,
+            import,
+            display,
+        }
+    }
+
+    /// The segments of the name, relative to the shared scope.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// The number of scopes between the referencing container and the scope
+    /// it shares with the target. When the target is in another package, this
+    /// is the depth of the container within its own package.
+    pub fn up(&self) -> usize {
+        self.up
+    }
+
+    /// The package of the target, if it differs from that of the referencing
+    /// container.
+    pub fn package(&self) -> Option<&Package> {
+        self.package.as_ref()
+    }
+
+    /// Returns `true` if the target is in a different package.
+    pub fn is_cross_package(&self) -> bool {
+        self.package.is_some()
+    }
+
+    /// The file which defines the target.
+    pub fn file(&self) -> Option<&File> {
+        self.file.as_ref()
+    }
+
+    /// Returns `true` if the target is defined in a different file than the
+    /// referencing container, and so must be imported.
+    pub fn requires_import(&self) -> bool {
+        self.import
+    }
+}
+
+/// Formats the name as it could be written in a proto file from within the
+/// referencing container.
+impl fmt::Display for RelativeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display)
+    }
+}
+
+/// Returns the shortest suffix of `target` which resolves to it from `scope`,
+/// or `target` itself if every suffix is shadowed.
+///
+/// A suffix is resolved by searching for its first segment from `scope`
+/// outward, so it resolves to `target` if the scope enclosing it is an
+/// ancestor of `scope` and no scope between them defines a symbol, as
+/// determined by `is_symbol`, of the same name.
+fn unambiguous(scope: &str, target: &str, is_symbol: impl Fn(&str) -> bool) -> String {
+    let scope: Vec<&str> = scope.split('.').filter(|s| !s.is_empty()).collect();
+    let target: Vec<&str> = target.split('.').filter(|s| !s.is_empty()).collect();
+    (0..target.len())
+        .rev()
+        .find(|&k| {
+            let (enclosing, first) = (&target[..k], target[k]);
+            scope.starts_with(enclosing)
+                && (k + 1..=scope.len())
+                    .all(|depth| !is_symbol(&format!(".{}.{first}", scope[..depth].join("."))))
+        })
+        .map_or_else(
+            || format!(".{}", target.join(".")),
+            |k| target[k..].join("."),
+        )
+}
+
+/// Splits a fully qualified name into its segments beneath `package`.
+fn segments<'a>(fqn: &'a str, package: &Package) -> Vec<&'a str> {
+    fqn.strip_prefix(package.fully_qualified_name())
+        .unwrap_or(fqn)
+        .split('.')
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::descriptor::DescriptorProto;
+
+    use super::*;
+    use crate::test_util::{self, kitchen, FileBuilder};
+
+    #[test]
+    fn test_segments() {
+        let pkg = Package::new("foo.bar");
+        assert_eq!(
+            segments(".foo.bar.Outer.Inner", &pkg),
+            vec!["Outer", "Inner"]
+        );
+        assert!(segments(".foo.bar", &pkg).is_empty());
+        let root = Package::new("");
+        assert_eq!(segments(".Outer", &root), vec!["Outer"]);
+    }
+
+    #[test]
+    fn test_unambiguous() {
+        let symbols = [
+            ".pkg",
+            ".pkg.Outer",
+            ".pkg.Outer.Other",
+            ".pkg.Other",
+            ".pkg.Outer.pkg",
+            ".pkg.Outer.Inner",
+        ];
+        let is_symbol = |fqn: &str| symbols.contains(&fqn);
+        let name = |scope, target| unambiguous(scope, target, is_symbol);
+        assert_eq!(name(".pkg.Outer", ".pkg.Outer.Inner"), "Inner");
+        assert_eq!(name(".pkg.Outer.Inner", ".pkg.Outer.Other"), "Other");
+        assert_eq!(name(".pkg", ".pkg.Other"), "Other");
+        // `Other` and `pkg` are both shadowed within `.pkg.Outer`
+        assert_eq!(name(".pkg.Outer", ".pkg.Other"), ".pkg.Other");
+        assert_eq!(name(".pkg.Outer.Inner", ".pkg.Other"), ".pkg.Other");
+        assert_eq!(name(".other", ".pkg.Other"), "pkg.Other");
+    }
+
+    #[test]
+    fn test_relative_name() {
+        let ast = kitchen();
+        let container = |fqn: &str| match ast.node(fqn) {
+            Some(Node::Message(m)) => Container::from(m),
+            Some(Node::File(f)) => Container::from(f),
+            _ => panic!("{fqn} is not a container"),
+        };
+        let name =
+            |scope: &str, target: &str| container(scope).relative_name(ast.node(target).unwrap());
+
+        let rgb = name(".kitchen.Color", ".kitchen.Color.RGB");
+        assert_eq!(rgb.to_string(), "RGB");
+        assert_eq!((rgb.segments(), rgb.up()), (&["RGB".to_string()][..], 0));
+        assert!(!rgb.requires_import());
+
+        let brand = name(".kitchen.Sink.Material", ".kitchen.Sink.Brand");
+        assert_eq!(brand.to_string(), "Brand");
+        assert_eq!(brand.up(), 1);
+
+        let sink = name(".kitchen.Kitchen", ".kitchen.Sink");
+        assert_eq!(sink.to_string(), "Sink");
+        assert!(!sink.is_cross_package());
+        assert!(sink.requires_import());
+        assert_eq!(sink.file().unwrap().name(), "kitchen/sink.proto");
+
+        let timestamp = name(".kitchen.Sink", ".google.protobuf.Timestamp");
+        assert_eq!(timestamp.to_string(), "google.protobuf.Timestamp");
+        assert_eq!(timestamp.segments(), ["Timestamp"]);
+        assert_eq!(timestamp.up(), 1);
+        assert_eq!(timestamp.package().unwrap().name(), "google.protobuf");
+        assert!(timestamp.requires_import());
+    }
+
+    /// `pkg.Outer` nests a message named `Other`, as is `pkg.Other`.
+    #[test]
+    fn test_relative_name_shadowed() {
+        let message = |name: &str, nested: Vec<DescriptorProto>| {
+            let mut message = test_util::message(name, vec![]);
+            message.nested_type = nested;
+            message
+        };
+        let (_pkg, file) = FileBuilder::new("pkg/shadow.proto", "pkg")
+            .message(message("Outer", vec![message("Other", vec![])]))
+            .message(message("Other", vec![]))
+            .file();
+        let outer = file.message("Outer").unwrap();
+        let nested = outer.messages().next().unwrap();
+        let other = file.messages().find(|m| m.name() == "Other").unwrap();
+
+        let scope = Container::from(&outer);
+        assert_eq!(scope.relative_name(&nested).to_string(), "Other");
+        let shadowed = scope.relative_name(&other);
+        assert_eq!(shadowed.to_string(), "pkg.Other");
+        assert_eq!(
+            (shadowed.segments(), shadowed.up()),
+            (&["Other".to_string()][..], 1)
+        );
+        assert_eq!(
+            Container::from(&file).relative_name(&other).to_string(),
+            "Other"
+        );
+    }
+}
@@ -0,0 +1,568 @@
+//! A serializable snapshot of the [`Ast`], for consumers which cannot link
+//! against catalyze.
+//!
+//! [`Schema`] implements `serde::Serialize`, so it can be written with any
+//! serde format, e.g. `serde_json::to_string_pretty(&Schema::new(&ast))` or
+//! `serde_yaml::to_string(&Schema::new(&ast))`.
+//!
+//! The output is stable: every list is in declaration order and options are
+//! sorted by name.
+
+use std::collections::BTreeMap;
+
+use protobuf::{
+    descriptor::field_descriptor_proto::{Label, Type},
+    reflect::ReflectValueRef,
+    MessageDyn,
+};
+use serde::Serialize;
+
+use crate::{
+    comments, enum_, extension, field, file, message, method, oneof, package, printer, service, Ast,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Schema {
+    pub packages: Vec<Package>,
+}
+
+impl Schema {
+    pub fn new(ast: &Ast) -> Self {
+        Self {
+            packages: ast.packages().map(|p| Package::new(&p)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Package {
+    pub name: String,
+    pub files: Vec<File>,
+}
+
+impl Package {
+    pub fn new(package: &package::Package) -> Self {
+        Self {
+            name: package.name().to_string(),
+            files: package.files().map(|f| File::new(&f)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct File {
+    pub name: String,
+    pub package: String,
+    pub syntax: String,
+    pub build_target: bool,
+    pub imports: Vec<String>,
+    /// The comments of the file's `syntax` statement, which lead the file.
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub services: Vec<Service>,
+    pub extensions: Vec<Extension>,
+}
+
+impl File {
+    pub fn new(file: &file::File) -> Self {
+        let descriptor = file.descriptor();
+        let proto = descriptor.proto();
+        Self {
+            name: file.name().to_string(),
+            package: proto.package().to_string(),
+            syntax: file.syntax().to_string(),
+            build_target: file.build_target(),
+            imports: proto.dependency.clone(),
+            comments: Comments::new(&file.comments()),
+            options: options(proto.options.as_ref().map(|o| o as &dyn MessageDyn)),
+            messages: file.messages().map(|m| Message::new(&m)).collect(),
+            enums: file.enums().map(|e| Enum::new(&e)).collect(),
+            services: file.services().map(|s| Service::new(&s)).collect(),
+            extensions: file
+                .defined_extensions()
+                .map(|e| Extension::new(&e))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Message {
+    pub name: String,
+    pub fully_qualified_name: String,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+    pub fields: Vec<Field>,
+    pub oneofs: Vec<Oneof>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub extensions: Vec<Extension>,
+}
+
+impl Message {
+    pub fn new(message: &message::Message) -> Self {
+        let descriptor = message.descriptor();
+        let proto = descriptor.proto();
+        Self {
+            name: message.name().to_string(),
+            fully_qualified_name: message.fully_qualified_name().to_string(),
+            comments: Comments::new(&message.comments()),
+            options: options(proto.options.as_ref().map(|o| o as &dyn MessageDyn)),
+            fields: message.fields().map(|f| Field::new(&f, message)).collect(),
+            oneofs: message.real_oneofs().map(|o| Oneof::new(&o)).collect(),
+            messages: message
+                .messages()
+                .filter(|m| !m.is_map_entry())
+                .map(|m| Message::new(&m))
+                .collect(),
+            enums: message.enums().map(|e| Enum::new(&e)).collect(),
+            extensions: message
+                .defined_extensions()
+                .map(|e| Extension::new(&e))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Scalar,
+    Message,
+    Enum,
+    Group,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Field {
+    pub name: String,
+    pub number: i32,
+    pub label: String,
+    pub kind: Kind,
+    /// The scalar type or fully qualified name of the message or enum.
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub json_name: String,
+    /// Whether the field tracks presence, distinguishing unset from the
+    /// default value.
+    pub presence: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oneof: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map: Option<Map>,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+}
+
+impl Field {
+    pub fn new(field: &field::Field, message: &message::Message) -> Self {
+        let descriptor = field.descriptor();
+        let proto = descriptor.proto();
+        Self {
+            name: proto.name().to_string(),
+            number: proto.number(),
+            label: label(proto.label()).to_string(),
+            kind: kind(proto.type_()),
+            type_: printer::type_name(proto),
+            json_name: if proto.has_json_name() {
+                proto.json_name().to_string()
+            } else {
+                printer::json_name(proto.name())
+            },
+            presence: field.has_presence(),
+            oneof: message
+                .real_oneofs()
+                .find(|o| o.fields().any(|f| f.name() == field.name()))
+                .map(|o| o.name().to_string()),
+            map: printer::map_types(field, message).map(|(key, value)| Map { key, value }),
+            comments: Comments::new(&field.comments()),
+            options: options(proto.options.as_ref().map(|o| o as &dyn MessageDyn)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Map {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Oneof {
+    pub name: String,
+    pub fields: Vec<String>,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+}
+
+impl Oneof {
+    pub fn new(oneof: &oneof::Oneof) -> Self {
+        Self {
+            name: oneof.name().to_string(),
+            fields: oneof.fields().map(|f| f.name().to_string()).collect(),
+            comments: Comments::new(&oneof.comments()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Enum {
+    pub name: String,
+    pub fully_qualified_name: String,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+    pub values: Vec<EnumValue>,
+}
+
+impl Enum {
+    pub fn new(enum_: &enum_::Enum) -> Self {
+        let descriptor = enum_.descriptor();
+        let proto = descriptor.proto();
+        Self {
+            name: enum_.name().to_string(),
+            fully_qualified_name: enum_.fully_qualified_name().to_string(),
+            comments: Comments::new(&enum_.comments()),
+            options: options(proto.options.as_ref().map(|o| o as &dyn MessageDyn)),
+            values: enum_.values().map(|v| EnumValue::new(&v)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnumValue {
+    pub name: String,
+    pub number: i32,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+}
+
+impl EnumValue {
+    pub fn new(value: &enum_::EnumValue) -> Self {
+        let descriptor = value.descriptor();
+        let proto = descriptor.proto();
+        Self {
+            name: value.name().to_string(),
+            number: value.number(),
+            comments: Comments::new(&value.comments()),
+            options: options(proto.options.as_ref().map(|o| o as &dyn MessageDyn)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Service {
+    pub name: String,
+    pub fully_qualified_name: String,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+    pub methods: Vec<Method>,
+}
+
+impl Service {
+    pub fn new(service: &service::Service) -> Self {
+        let descriptor = service.descriptor();
+        let proto = descriptor.proto();
+        Self {
+            name: service.name().to_string(),
+            fully_qualified_name: service.fully_qualified_name().to_string(),
+            comments: Comments::new(&service.comments()),
+            options: options(proto.options.as_ref().map(|o| o as &dyn MessageDyn)),
+            methods: service.methods().map(|m| Method::new(&m)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Method {
+    pub name: String,
+    /// The fully qualified name of the input message.
+    pub input: String,
+    /// The fully qualified name of the output message.
+    pub output: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+}
+
+impl Method {
+    pub fn new(method: &method::Method) -> Self {
+        let descriptor = method.descriptor();
+        Self {
+            name: method.name().to_string(),
+            input: descriptor.input_type().to_string(),
+            output: descriptor.output_type().to_string(),
+            client_streaming: method.is_client_streaming(),
+            server_streaming: method.is_server_streaming(),
+            comments: Comments::new(&method.comments()),
+            options: options(descriptor.options.as_ref().map(|o| o as &dyn MessageDyn)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Extension {
+    pub name: String,
+    pub fully_qualified_name: String,
+    /// The fully qualified name of the extended message.
+    pub extendee: String,
+    pub number: i32,
+    pub label: String,
+    pub kind: Kind,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(skip_serializing_if = "Comments::is_empty")]
+    pub comments: Comments,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, OptionValue>,
+}
+
+impl Extension {
+    pub fn new(extension: &extension::Extension) -> Self {
+        let descriptor = extension.descriptor();
+        let proto = descriptor.proto();
+        Self {
+            name: extension.name().to_string(),
+            fully_qualified_name: extension.fully_qualified_name().to_string(),
+            extendee: proto.extendee().to_string(),
+            number: proto.number(),
+            label: label(proto.label()).to_string(),
+            kind: kind(proto.type_()),
+            type_: printer::type_name(proto),
+            comments: Comments::new(&extension.comments()),
+            options: options(proto.options.as_ref().map(|o| o as &dyn MessageDyn)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Comments {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub leading: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub trailing: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub detached: Vec<String>,
+}
+
+impl Comments {
+    pub fn new(comments: &comments::Comments) -> Self {
+        Self {
+            leading: comments.leading().to_string(),
+            trailing: comments.trailing().to_string(),
+            detached: comments.leading_detached().cloned().collect(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty() && self.detached.is_empty()
+    }
+}
+
+/// The value of an option. Enum values are represented by name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum OptionValue {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    String(String),
+}
+
+fn label(label: Label) -> &'static str {
+    match label {
+        Label::LABEL_OPTIONAL => "optional",
+        Label::LABEL_REQUIRED => "required",
+        Label::LABEL_REPEATED => "repeated",
+    }
+}
+
+fn kind(type_: Type) -> Kind {
+    match type_ {
+        Type::TYPE_MESSAGE => Kind::Message,
+        Type::TYPE_ENUM => Kind::Enum,
+        Type::TYPE_GROUP => Kind::Group,
+        _ => Kind::Scalar,
+    }
+}
+
+/// Collects the options set on `options`, as [`Printer`](crate::Printer)
+/// prints them.
+fn options(options: Option<&dyn MessageDyn>) -> BTreeMap<String, OptionValue> {
+    options
+        .map(printer::option_fields)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, option_value(value)?)))
+        .collect()
+}
+
+fn option_value(value: ReflectValueRef) -> Option<OptionValue> {
+    Some(match value {
+        ReflectValueRef::Bool(b) => OptionValue::Bool(b),
+        ReflectValueRef::I32(n) => OptionValue::Int(n.into()),
+        ReflectValueRef::I64(n) => OptionValue::Int(n),
+        ReflectValueRef::U32(n) => OptionValue::Uint(n.into()),
+        ReflectValueRef::U64(n) => OptionValue::Uint(n),
+        ReflectValueRef::F32(n) => OptionValue::Float(n.into()),
+        ReflectValueRef::F64(n) => OptionValue::Float(n),
+        ReflectValueRef::String(s) => OptionValue::String(s.to_string()),
+        ReflectValueRef::Bytes(b) => OptionValue::String(String::from_utf8_lossy(b).into()),
+        ReflectValueRef::Enum(e, n) => match e.value_by_number(n) {
+            Some(v) => OptionValue::String(v.name().to_string()),
+            None => OptionValue::Int(n.into()),
+        },
+        ReflectValueRef::Message(_) => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::descriptor::{source_code_info::Location, SourceCodeInfo};
+
+    use super::*;
+    use crate::test_util::{self, kitchen, FileBuilder};
+
+    #[test]
+    fn test_schema() {
+        let schema = Schema::new(&kitchen());
+        let json = serde_json::to_value(&schema).unwrap();
+        let kitchen = json["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["name"] == "kitchen")
+            .unwrap();
+        let file = kitchen["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["name"] == "kitchen/kitchen.proto")
+            .unwrap();
+        assert_eq!(file["messages"][0]["name"], "Kitchen");
+        let dish_counts = &file["messages"][0]["fields"][3];
+        assert_eq!(dish_counts["name"], "dish_counts");
+        assert_eq!(
+            dish_counts["map"],
+            serde_json::json!({ "key": "string", "value": "uint32" })
+        );
+        assert_eq!(file["enums"][0]["name"], "Style");
+        assert_eq!(
+            file["options"]["go_package"],
+            "github.com/lyft/protoc-gen-star/testdata/generated/kitchen"
+        );
+
+        let field = |message: usize, name: &str| {
+            file["messages"][message]["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|f| f["name"] == name)
+                .unwrap()
+                .clone()
+        };
+        let style = field(0, "style");
+        assert_eq!(
+            (&style["label"], &style["presence"]),
+            (&"optional".into(), &false.into())
+        );
+        assert_eq!(style["kind"], "enum");
+        let sink = field(0, "sink");
+        assert_eq!(
+            (&sink["label"], &sink["presence"]),
+            (&"optional".into(), &true.into())
+        );
+        let utensils = field(0, "utensils");
+        assert_eq!(
+            (&utensils["label"], &utensils["presence"]),
+            (&"repeated".into(), &false.into())
+        );
+        let pantone = field(1, "pantone");
+        assert_eq!(
+            (&pantone["presence"], &pantone["oneof"]),
+            (&true.into(), &"model".into())
+        );
+
+        let streaming: Vec<_> = file["services"][0]["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| {
+                (
+                    m["name"].as_str().unwrap(),
+                    m["client_streaming"].as_bool().unwrap(),
+                    m["server_streaming"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            streaming,
+            [
+                ("Saute", false, false),
+                ("DispenseIce", false, true),
+                ("LoadFridge", true, false),
+                ("OrderDrinks", true, true),
+                ("CheckBestByDate", false, false),
+            ]
+        );
+
+        // kitchen.proto has no comments, which are then left out.
+        assert!(file.get("comments").is_none());
+        assert!(file["messages"][0].get("comments").is_none());
+        assert!(style.get("comments").is_none());
+    }
+
+    #[test]
+    fn test_comments() {
+        let location = |path: Vec<i32>, leading: &str, trailing: &str| {
+            let mut location = Location::new();
+            location.path = path;
+            location.set_leading_comments(leading.to_string());
+            location.set_trailing_comments(trailing.to_string());
+            location
+        };
+        let mut info = SourceCodeInfo::new();
+        // The `syntax` and `package` statements, the message and its field.
+        info.location = vec![
+            location(vec![12], " Syntax\n", ""),
+            location(vec![2], " Package\n", ""),
+            location(vec![4, 0], " Message\n", ""),
+            location(vec![4, 0, 2, 0], "", " Field\n"),
+        ];
+        let id = test_util::field("id", Label::LABEL_OPTIONAL, Type::TYPE_INT32, "");
+        let (_pkg, file) = FileBuilder::new("pkg/commented.proto", "pkg")
+            .message(test_util::message("Commented", vec![id]))
+            .source_code_info(info)
+            .file();
+        let json = serde_json::to_value(File::new(&file)).unwrap();
+        assert_eq!(
+            json["comments"],
+            serde_json::json!({ "leading": " Syntax\n" })
+        );
+        let message = &json["messages"][0];
+        assert_eq!(
+            message["comments"],
+            serde_json::json!({ "leading": " Message\n" })
+        );
+        assert_eq!(
+            message["fields"][0]["comments"],
+            serde_json::json!({ "trailing": " Field\n" })
+        );
+    }
+}
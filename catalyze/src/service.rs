@@ -1,12 +1,13 @@
-use std::{
-    cell::RefCell,
-    rc::{Rc, Weak},
-};
+use std::fmt;
+
+use crate::sync::{Rc, RefCell, Weak};
 
 use protobuf::reflect::ServiceDescriptor;
 
 use crate::{
     comments::Comments,
+    custom_option::CustomOption,
+    error::Error,
     file::{File, WeakFile},
     iter::Iter,
     method::Method,
@@ -25,6 +26,11 @@ pub struct ServiceOptions<'a> {
     opts: Option<&'a protobuf::descriptor::ServiceOptions>,
 }
 impl<'a> ServiceOptions<'a> {
+    fn opts(&self) -> &'a protobuf::descriptor::ServiceOptions {
+        self.opts.unwrap_or_else(|| {
+            <protobuf::descriptor::ServiceOptions as protobuf::Message>::default_instance()
+        })
+    }
     /// Is this service deprecated?
     /// Depending on the target platform, this can emit Deprecated annotations
     /// for the service, or it will be completely ignored; in the very least,
@@ -36,8 +42,12 @@ impl<'a> ServiceOptions<'a> {
         self.opts().deprecated()
     }
     /// The parser stores options it doesn't recognize here. See above.
-    pub fn uninterpreted_options(&self) -> &[UninterpretedOption] {
-        (&self.opts().uninterpreted_option).into()
+    pub fn uninterpreted_options(&self) -> Vec<UninterpretedOption> {
+        self.opts()
+            .uninterpreted_option
+            .iter()
+            .map(Into::into)
+            .collect()
     }
 }
 
@@ -47,7 +57,7 @@ impl<'a> From<Option<&'a protobuf::descriptor::ServiceOptions>> for ServiceOptio
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 struct ServiceDetail {
     fqn: String,
     methods: Rc<RefCell<Vec<Method>>>,
@@ -56,31 +66,59 @@ struct ServiceDetail {
     descriptor: ServiceDescriptor,
 }
 
+impl fmt::Debug for ServiceDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceDetail")
+            .field("fqn", &self.fqn)
+            .field("methods", &self.methods)
+            .field("comments", &self.comments)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Service(Rc<ServiceDetail>);
 
 impl Service {
     pub(crate) fn new(descriptor: ServiceDescriptor, file: File) -> Self {
-        let fully_qualified_name = format!("{}.{}", file.fully_qualified_name(), descriptor.name());
+        let fully_qualified_name = format!(
+            "{}.{}",
+            file.fully_qualified_name(),
+            descriptor.proto().name()
+        );
         let svc = Service(Rc::new(ServiceDetail {
             fqn: fully_qualified_name,
-            methods: Rc::new(RefCell::new(Vec::with_capacity(descriptor.methods().len()))),
+            methods: Rc::new(RefCell::new(Vec::new())),
             comments: RefCell::new(Comments::default()),
             file: file.clone().into(),
             descriptor,
         }));
 
-        for method in descriptor.methods() {
-            svc.0
-                .methods
-                .borrow_mut()
-                .push(Method::new(method, svc.clone()));
-        }
+        let methods = svc
+            .0
+            .descriptor
+            .proto()
+            .method
+            .iter()
+            .map(|method| Method::new(method.clone(), svc.clone()))
+            .collect::<Vec<_>>();
+        svc.0.methods.borrow_mut().extend(methods);
         svc
     }
 
+    pub fn descriptor(&self) -> ServiceDescriptor {
+        self.0.descriptor.clone()
+    }
+    pub fn options(&self) -> ServiceOptions<'_> {
+        ServiceOptions::from(self.0.descriptor.proto().options.as_ref())
+    }
+
     pub fn comments(&self) -> Comments {
-        *self.0.comments.borrow()
+        self.0.comments.borrow().clone()
+    }
+    /// Returns the custom option `T` set on the service; see [`Node::option`].
+    pub fn option<T: CustomOption>(&self) -> Result<Option<T>, Error> {
+        Node::from(self).option()
     }
 
     pub fn file(&self) -> File {
@@ -92,11 +130,16 @@ impl Service {
     pub(crate) fn set_comments(&self, comments: Comments) {
         self.0.comments.replace(comments);
     }
+    #[cfg(feature = "sync")]
+    pub(crate) fn freeze(&self) {
+        self.0.methods.freeze();
+        self.0.comments.freeze();
+    }
     pub fn methods(&self) -> Iter<Method> {
         Iter::from(&self.0.methods)
     }
     pub fn name(&self) -> &str {
-        &self.0.name
+        self.0.descriptor.proto().name()
     }
     fn downgrade(&self) -> WeakService {
         WeakService(Rc::downgrade(&self.0))
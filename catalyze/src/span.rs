@@ -0,0 +1,153 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use protobuf::descriptor::source_code_info::Location;
+
+/// The region of a proto file in which a node is defined, as recorded in the
+/// file's `SourceCodeInfo`.
+///
+/// Lines and columns are zero-based, as they are in `SourceCodeInfo`. The
+/// [`Display`](fmt::Display) implementation renders one-based positions, e.g.
+/// `foo.proto:42:5`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    file_path: PathBuf,
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl Span {
+    /// Returns the `Span` of `location` within the file at `file_path`, or
+    /// `None` if the location does not have a valid span.
+    ///
+    /// A span has either three elements (start line, start column, end column)
+    /// or four (start line, start column, end line, end column).
+    pub fn from_location(file_path: impl AsRef<Path>, location: &Location) -> Option<Self> {
+        let span = location
+            .span
+            .iter()
+            .map(|&n| usize::try_from(n).ok())
+            .collect::<Option<Vec<_>>>()?;
+        let (start_line, start_column, end_line, end_column) = match span[..] {
+            [start_line, start_column, end_column] => {
+                (start_line, start_column, start_line, end_column)
+            }
+            [start_line, start_column, end_line, end_column] => {
+                (start_line, start_column, end_line, end_column)
+            }
+            _ => return None,
+        };
+        Some(Self {
+            file_path: file_path.as_ref().to_path_buf(),
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        })
+    }
+    /// The path of the proto file, relative to the import root.
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+    /// The zero-based line on which the node starts.
+    pub fn start_line(&self) -> usize {
+        self.start_line
+    }
+    /// The zero-based column at which the node starts.
+    pub fn start_column(&self) -> usize {
+        self.start_column
+    }
+    /// The zero-based line on which the node ends.
+    pub fn end_line(&self) -> usize {
+        self.end_line
+    }
+    /// The zero-based column at which the node ends (exclusive).
+    pub fn end_column(&self) -> usize {
+        self.end_column
+    }
+    /// The one-based line on which the node starts, as shown by editors.
+    pub fn line(&self) -> usize {
+        self.start_line + 1
+    }
+    /// The one-based column at which the node starts, as shown by editors.
+    pub fn column(&self) -> usize {
+        self.start_column + 1
+    }
+    /// Returns `true` if the span covers more than one line.
+    pub fn is_multiline(&self) -> bool {
+        self.end_line > self.start_line
+    }
+    /// Returns a marker such as `source: foo.proto:42`, suitable for a
+    /// comment in generated code.
+    pub fn source_marker(&self) -> String {
+        format!("source: {}:{}", self.file_path.display(), self.line())
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.file_path.display(),
+            self.line(),
+            self.column()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, Node};
+
+    #[test]
+    fn test_from_location() {
+        let mut loc = Location::new();
+        loc.span = vec![41, 4, 17];
+        let span = Span::from_location("foo.proto", &loc).unwrap();
+        assert_eq!(span.end_line(), 41);
+        assert_eq!(span.end_column(), 17);
+        assert_eq!(span.to_string(), "foo.proto:42:5");
+        assert_eq!(span.source_marker(), "source: foo.proto:42");
+
+        loc.span = vec![41, 4, 45, 1];
+        let span = Span::from_location("foo.proto", &loc).unwrap();
+        assert!(span.is_multiline());
+
+        loc.span = vec![41];
+        assert!(Span::from_location("foo.proto", &loc).is_none());
+    }
+
+    #[test]
+    fn test_node_span() {
+        let ast = test_util::kitchen();
+        let span = |fqn: &str| ast.node(fqn).unwrap().span().unwrap();
+
+        let style = span(".kitchen.Style");
+        assert_eq!(style.file_path(), Path::new("kitchen/kitchen.proto"));
+        assert_eq!((style.line(), style.column()), (9, 1));
+        assert_eq!((style.end_line(), style.end_column()), (15, 1));
+        assert!(style.is_multiline());
+
+        let r = span(".kitchen.Color.RGB.r");
+        assert_eq!((r.start_line(), r.start_column()), (38, 8));
+        assert_eq!((r.end_line(), r.end_column()), (38, 21));
+        assert!(!r.is_multiline());
+        assert_eq!(r.to_string(), "kitchen/kitchen.proto:39:9");
+
+        let saute = span(".kitchen.Cooking.Saute");
+        assert_eq!((saute.line(), saute.column()), (53, 5));
+
+        let file = ast.file("kitchen/kitchen.proto").unwrap();
+        let syntax = Node::File(file).span().unwrap();
+        assert_eq!((syntax.line(), syntax.column()), (1, 1));
+
+        let package = ast.package("kitchen").unwrap();
+        assert!(Node::Package(package).span().is_none());
+    }
+}
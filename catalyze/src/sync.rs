@@ -0,0 +1,166 @@
+//! Shared ownership and interior mutability primitives for the node graph.
+//!
+//! By default, nodes are built on `Rc` and `RefCell`. With the `sync` feature
+//! enabled, `Rc` is swapped for `Arc` and `RefCell` for a freezable cell with
+//! the same interface, making the [`Ast`](crate::Ast) `Send + Sync`.
+//!
+//! Nodes are only mutated while the `Ast` is being hydrated. With `sync`,
+//! [`Ast::new`](crate::Ast::new) freezes every cell before returning: the value
+//! is moved into a `OnceLock`, reads no longer take a lock, and any further
+//! mutation panics.
+
+#[cfg(not(feature = "sync"))]
+pub(crate) use std::{
+    cell::{OnceCell, RefCell},
+    rc::{Rc, Weak},
+};
+
+#[cfg(feature = "sync")]
+pub(crate) use std::sync::{Arc as Rc, OnceLock as OnceCell, Weak};
+
+#[cfg(feature = "sync")]
+pub(crate) use self::cell::RefCell;
+
+#[cfg(feature = "sync")]
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    #[allow(dead_code)]
+    fn assert_ast() {
+        assert_send_sync::<crate::Ast>();
+    }
+};
+
+#[cfg(feature = "sync")]
+mod cell {
+    use std::{
+        fmt,
+        ops::{Deref, DerefMut},
+        sync::{OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    };
+
+    /// A cell with the borrowing interface of `std::cell::RefCell` which is
+    /// backed by a `RwLock` until it is frozen and by a `OnceLock` after.
+    pub(crate) struct RefCell<T> {
+        hydrating: RwLock<Option<T>>,
+        frozen: OnceLock<T>,
+    }
+
+    pub(crate) enum Ref<'a, T> {
+        Hydrating(RwLockReadGuard<'a, Option<T>>),
+        Frozen(&'a T),
+    }
+
+    impl<T> Deref for Ref<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            match self {
+                Ref::Hydrating(guard) => guard.as_ref().expect("cell is hydrating"),
+                Ref::Frozen(value) => value,
+            }
+        }
+    }
+
+    pub(crate) struct RefMut<'a, T>(RwLockWriteGuard<'a, Option<T>>);
+
+    impl<T> Deref for RefMut<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            self.0.as_ref().expect("cell is hydrating")
+        }
+    }
+
+    impl<T> DerefMut for RefMut<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            self.0.as_mut().expect("cell is hydrating")
+        }
+    }
+
+    impl<T> RefCell<T> {
+        pub(crate) fn new(value: T) -> Self {
+            Self {
+                hydrating: RwLock::new(Some(value)),
+                frozen: OnceLock::new(),
+            }
+        }
+
+        pub(crate) fn borrow(&self) -> Ref<'_, T> {
+            if let Some(value) = self.frozen.get() {
+                return Ref::Frozen(value);
+            }
+            let guard = self
+                .hydrating
+                .read()
+                .unwrap_or_else(PoisonError::into_inner);
+            match self.frozen.get() {
+                Some(value) => Ref::Frozen(value),
+                None => Ref::Hydrating(guard),
+            }
+        }
+
+        pub(crate) fn borrow_mut(&self) -> RefMut<'_, T> {
+            assert!(
+                self.frozen.get().is_none(),
+                "nodes must not be mutated once the Ast is hydrated"
+            );
+            RefMut(
+                self.hydrating
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner),
+            )
+        }
+
+        pub(crate) fn replace(&self, value: T) -> T {
+            std::mem::replace(&mut *self.borrow_mut(), value)
+        }
+
+        /// Moves the value out of the lock; the cell is read-only afterwards.
+        pub(crate) fn freeze(&self) {
+            let value = self
+                .hydrating
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            if let Some(value) = value {
+                let _ = self.frozen.set(value);
+            }
+        }
+    }
+
+    impl<T: Default> Default for RefCell<T> {
+        fn default() -> Self {
+            Self::new(T::default())
+        }
+    }
+
+    impl<T: Clone> Clone for RefCell<T> {
+        fn clone(&self) -> Self {
+            Self::new(self.borrow().clone())
+        }
+    }
+
+    impl<T: fmt::Debug> fmt::Debug for RefCell<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("RefCell").field(&*self.borrow()).finish()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_freeze() {
+            let cell = RefCell::new(vec![1]);
+            cell.borrow_mut().push(2);
+            {
+                let outer = cell.borrow();
+                assert_eq!(*outer, *cell.borrow());
+            }
+            cell.freeze();
+            assert!(matches!(cell.borrow(), Ref::Frozen(_)));
+            assert_eq!(*cell.borrow(), vec![1, 2]);
+            let result = std::panic::catch_unwind(|| cell.borrow_mut().push(3));
+            assert!(result.is_err());
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Fixtures shared by the unit tests.
+
+use std::{env, fs, path::PathBuf};
+
+use protobuf::{
+    descriptor::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto, SourceCodeInfo,
+    },
+    reflect::FileDescriptor,
+};
+
+use crate::{package::Package, Ast, File, Standalone};
+
+/// The path of `name` within the workspace's `tests` directory.
+pub(crate) fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests")
+        .join(name)
+}
+
+/// The serialized `FileDescriptorSet` of `tests/proto/kitchen`.
+pub(crate) fn kitchen_bin() -> fs::File {
+    fs::File::open(fixture("proto_op/kitchen.bin")).unwrap()
+}
+
+/// The serialized `CodeGeneratorRequest` protoc sends for
+/// `kitchen/kitchen.proto`.
+pub(crate) fn kitchen_request() -> Vec<u8> {
+    fs::read(fixture("code-generator-requests/kitchen")).unwrap()
+}
+
+/// The `Ast` of `tests/proto/kitchen`, targeting `kitchen/kitchen.proto`.
+pub(crate) fn kitchen() -> Ast {
+    let input = Standalone::new(
+        kitchen_bin(),
+        ["kitchen/kitchen.proto"],
+        env::temp_dir(),
+        (),
+    )
+    .unwrap();
+    Ast::new(&input).unwrap()
+}
+
+/// The `Ast` of `tests/proto/options/contact.proto`, which sets custom
+/// options, parsed without `protoc`.
+#[cfg(feature = "parse")]
+pub(crate) fn contact() -> Ast {
+    let root = fixture("proto");
+    let input = Standalone::parse(
+        [&root],
+        [root.join("options/contact.proto")],
+        env::temp_dir(),
+        (),
+    )
+    .unwrap();
+    Ast::new(&input).unwrap()
+}
+
+/// Builds a proto3 file descriptor, for tests which need declarations the
+/// fixtures lack.
+pub(crate) struct FileBuilder(FileDescriptorProto);
+
+impl FileBuilder {
+    pub(crate) fn new(name: &str, package: &str) -> Self {
+        let mut proto = FileDescriptorProto::new();
+        proto.set_name(name.to_string());
+        proto.set_package(package.to_string());
+        proto.set_syntax("proto3".to_string());
+        Self(proto)
+    }
+
+    pub(crate) fn message(mut self, message: DescriptorProto) -> Self {
+        self.0.message_type.push(message);
+        self
+    }
+
+    pub(crate) fn enum_(mut self, enum_: EnumDescriptorProto) -> Self {
+        self.0.enum_type.push(enum_);
+        self
+    }
+
+    pub(crate) fn source_code_info(mut self, info: SourceCodeInfo) -> Self {
+        self.0.source_code_info = Some(info).into();
+        self
+    }
+
+    /// Returns the package too, as files only hold a weak reference to it.
+    pub(crate) fn file(self) -> (Package, File) {
+        let package = Package::new(self.0.package());
+        let fd = FileDescriptor::new_dynamic(self.0, &[]).unwrap();
+        let file = File::new(true, fd, package.clone()).unwrap();
+        (package, file)
+    }
+
+    /// The `Ast` targeting the file.
+    #[cfg(feature = "message-graph")]
+    pub(crate) fn ast(self) -> Ast {
+        use protobuf::{descriptor::FileDescriptorSet, Message as _};
+        use std::io::Cursor;
+
+        let name = self.0.name().to_string();
+        let mut set = FileDescriptorSet::new();
+        set.file.push(self.0);
+        let input = Standalone::new(
+            Cursor::new(set.write_to_bytes().unwrap()),
+            [name],
+            env::temp_dir(),
+            (),
+        )
+        .unwrap();
+        Ast::new(&input).unwrap()
+    }
+}
+
+/// A message named `name` with `fields`, numbered from 1.
+pub(crate) fn message(name: &str, fields: Vec<FieldDescriptorProto>) -> DescriptorProto {
+    let mut message = DescriptorProto::new();
+    message.set_name(name.to_string());
+    for (number, mut field) in (1..).zip(fields) {
+        field.set_number(number);
+        message.field.push(field);
+    }
+    message
+}
+
+/// A field of type `type_`; `type_name` is the fully qualified name of a
+/// message or enum, or empty for a scalar.
+pub(crate) fn field(
+    name: &str,
+    label: Label,
+    type_: Type,
+    type_name: &str,
+) -> FieldDescriptorProto {
+    let mut field = FieldDescriptorProto::new();
+    field.set_name(name.to_string());
+    field.set_json_name(name.to_string());
+    field.set_label(label);
+    field.set_type(type_);
+    if !type_name.is_empty() {
+        field.set_type_name(type_name.to_string());
+    }
+    field
+}
+
+/// An enum named `name` with `values`, numbered from 0.
+pub(crate) fn enum_(name: &str, values: &[&str]) -> EnumDescriptorProto {
+    let mut enum_ = EnumDescriptorProto::new();
+    enum_.set_name(name.to_string());
+    for (number, name) in (0..).zip(values) {
+        let mut value = EnumValueDescriptorProto::new();
+        value.set_name(name.to_string());
+        value.set_number(number);
+        enum_.value.push(value);
+    }
+    enum_
+}
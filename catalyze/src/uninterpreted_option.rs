@@ -1,7 +1,7 @@
 use ::std::fmt;
 
 /// A message representing an option that parser does not recognize.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UninterpretedOption {
     name: Vec<NamePart>,
     identifier_value: Option<String>,
@@ -13,8 +13,8 @@ pub struct UninterpretedOption {
 }
 
 impl UninterpretedOption {
-    pub fn name(&self) -> &[NamePart] {
-        self.name.as_ref()
+    pub fn name(&self) -> NameParts<'_> {
+        NameParts { parts: &self.name }
     }
 
     pub fn identifier_value(&self) -> Option<&str> {
@@ -42,24 +42,44 @@ impl UninterpretedOption {
     }
 }
 
+impl From<&protobuf::descriptor::UninterpretedOption> for UninterpretedOption {
+    fn from(opt: &protobuf::descriptor::UninterpretedOption) -> Self {
+        Self {
+            name: opt.name.iter().map(NamePart::from).collect(),
+            identifier_value: opt.identifier_value.clone(),
+            positive_int_value: opt.positive_int_value,
+            negative_int_value: opt.negative_int_value,
+            double_value: opt.double_value,
+            string_value: opt.string_value.clone(),
+            aggregate_value: opt.aggregate_value.clone(),
+        }
+    }
+}
+
+impl From<protobuf::descriptor::UninterpretedOption> for UninterpretedOption {
+    fn from(opt: protobuf::descriptor::UninterpretedOption) -> Self {
+        Self::from(&opt)
+    }
+}
+
 ///  The name of the uninterpreted option.  Each string represents a segment in
 ///  a dot-separated name.
 ///
 ///  E.g.,`{ ["foo", false], ["bar.baz", true], ["qux", false] }` represents
 ///  `"foo.(bar.baz).qux"`.
-#[derive(PartialEq, Hash, Clone, Default, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Default, Debug)]
 pub struct NamePart {
-    name_part: Option<String>,
-    is_extension: Option<bool>,
+    name_part: String,
+    is_extension: bool,
 }
 
 impl NamePart {
-    pub fn name_part(&self) -> Option<&String> {
-        self.name_part.as_ref()
+    pub fn name_part(&self) -> &str {
+        &self.name_part
     }
     /// true if a segment represents an extension (denoted with parentheses in
     ///  options specs in .proto files).
-    pub fn is_extension(&self) -> Option<bool> {
+    pub fn is_extension(&self) -> bool {
         self.is_extension
     }
 
@@ -68,14 +88,14 @@ impl NamePart {
     /// If `is_extension` is `true`, the formatted value will be wrapped in
     /// parentheses.
     pub fn formatted(&self) -> String {
-        if self.part.is_extension() {
-            format!("({})", self.part.name_part())
+        if self.is_extension {
+            format!("({})", self.name_part)
         } else {
-            self.part.name_part().to_string()
+            self.name_part.clone()
         }
     }
     pub fn as_str(&self) -> &str {
-        self.part.name_part()
+        &self.name_part
     }
 }
 
@@ -85,6 +105,18 @@ impl AsRef<str> for NamePart {
     }
 }
 
+impl PartialEq<str> for NamePart {
+    fn eq(&self, other: &str) -> bool {
+        self.name_part == other
+    }
+}
+
+impl PartialEq<&str> for NamePart {
+    fn eq(&self, other: &&str) -> bool {
+        self.name_part == *other
+    }
+}
+
 impl fmt::Display for NamePart {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
@@ -99,41 +131,37 @@ impl From<protobuf::descriptor::uninterpreted_option::NamePart> for NamePart {
 impl From<&protobuf::descriptor::uninterpreted_option::NamePart> for NamePart {
     fn from(part: &protobuf::descriptor::uninterpreted_option::NamePart) -> Self {
         Self {
-            is_extension: part.is_extension,
-            name_part: part.name_part.clone(),
+            is_extension: part.is_extension(),
+            name_part: part.name_part().to_string(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct NameParts<'a> {
     parts: &'a [NamePart],
 }
 
-impl ToString for NameParts<'_> {
-    fn to_string(&self) -> String {
-        self.formatted()
+impl fmt::Display for NameParts<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.formatted())
     }
 }
 
-impl<'a> std::iter::IntoIterator for &'a NameParts {
+impl<'a> std::iter::IntoIterator for NameParts<'a> {
     type Item = &'a NamePart;
     type IntoIter = std::slice::Iter<'a, NamePart>;
     fn into_iter(self) -> Self::IntoIter {
-        self.parts
-            .iter()
-            .map(NamePart::from)
-            .collect::<Vec<_>>()
-            .into_iter()
+        self.parts.iter()
     }
 }
 
-impl NameParts {
-    pub fn iter(&self) -> std::slice::Iter<'_, NamePart> {
-        self.into()
+impl<'a> NameParts<'a> {
+    pub fn iter(&self) -> std::slice::Iter<'a, NamePart> {
+        self.parts.iter()
     }
-    pub fn get(&self, idx: usize) -> Option<NamePart> {
-        self.parts.get(idx).map(NamePart::from)
+    pub fn get(&self, idx: usize) -> Option<&'a NamePart> {
+        self.parts.get(idx)
     }
 
     pub fn len(&self) -> usize {
@@ -146,6 +174,25 @@ impl NameParts {
         self.parts.iter().any(|p| p.name_part() == part)
     }
     pub fn formatted(&self) -> String {
-        itertools::join(self.iter().map(|v| v.formatted()), '.')
+        itertools::join(self.iter().map(|v| v.formatted()), ".")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_string() {
+        let mut opt = protobuf::descriptor::UninterpretedOption::new();
+        for (part, is_extension) in [("foo", false), ("bar", true), ("baz", false)] {
+            let mut p = protobuf::descriptor::uninterpreted_option::NamePart::new();
+            p.set_name_part(part.to_string());
+            p.set_is_extension(is_extension);
+            opt.name.push(p);
+        }
+        let opt = UninterpretedOption::from(&opt);
+        assert_eq!(opt.name().to_string(), "foo.(bar).baz");
+        assert_eq!(opt.name().get(0).unwrap(), "foo");
     }
 }
@@ -1,3 +1,5 @@
+use protobuf::{rt::WireType, UnknownValueRef};
+
 pub enum Value {
     /// 32-bit unknown (e. g. `fixed32` or `float`)
     Fixed32(u32),
@@ -26,7 +28,7 @@ impl Value {
             Value::Fixed32(fixed32) => UnknownValueRef::Fixed32(fixed32),
             Value::Fixed64(fixed64) => UnknownValueRef::Fixed64(fixed64),
             Value::Varint(varint) => UnknownValueRef::Varint(varint),
-            Value::LengthDelimited(ref bytes) => UnknownValueRef::LengthDelimited(&bytes),
+            Value::LengthDelimited(ref bytes) => UnknownValueRef::LengthDelimited(bytes),
         }
     }
 
@@ -42,12 +44,12 @@ impl Value {
 
     /// Construct unknown value from `sint32` value.
     pub fn sint32(i: i32) -> Value {
-        Value::Varint(encode_zig_zag_32(i) as u64)
+        Value::Varint(((i << 1) ^ (i >> 31)) as u32 as u64)
     }
 
     /// Construct unknown value from `sint64` value.
     pub fn sint64(i: i64) -> Value {
-        Value::Varint(encode_zig_zag_64(i))
+        Value::Varint(((i << 1) ^ (i >> 63)) as u64)
     }
 
     /// Construct unknown value from `float` value.
@@ -70,18 +72,3 @@ impl Value {
         Value::Fixed64(i as u64)
     }
 }
-
-/// Field unknown values.
-///
-/// See [`UnknownFields`](crate::UnknownFields) for explanations.
-#[derive(Clone, PartialEq, Eq, Debug, Default, Hash)]
-struct Values {
-    /// 32-bit unknowns
-    fixed32: Vec<u32>,
-    /// 64-bit unknowns
-    fixed64: Vec<u64>,
-    /// Varint unknowns
-    varint: Vec<u64>,
-    /// Length-delimited unknowns
-    length_delimited: Vec<Vec<u8>>,
-}
@@ -1,2 +1,6 @@
 #[cfg(feature = "message-graph")]
 mod message_graph;
+pub(crate) mod topo;
+
+#[cfg(feature = "message-graph")]
+pub use message_graph::MessageGraph;
@@ -0,0 +1,309 @@
+use std::collections::{HashMap, HashSet};
+
+use petgraph::{
+    algo::{has_path_connecting, tarjan_scc},
+    graph::NodeIndex,
+    visit::EdgeRef,
+    Graph,
+};
+
+use crate::{field::Field, message::Message, Ast};
+
+/// `MessageGraph` is a directed graph of every [`Message`] in an [`Ast`], with
+/// an edge from a message to each message it embeds through a field
+/// (singular, repeated, map value or oneof).
+///
+/// Repeated and map fields are stored on the heap and so break recursion on
+/// their own; a cycle made up entirely of singular fields requires one of
+/// those fields to be boxed.
+///
+/// Sourced from prost's `MessageGraph`.
+#[derive(Debug)]
+pub struct MessageGraph {
+    index: HashMap<String, NodeIndex>,
+    graph: Graph<Message, Field>,
+    boxed: Vec<Field>,
+    boxed_names: HashSet<String>,
+}
+
+impl MessageGraph {
+    pub fn new(ast: &Ast) -> Self {
+        let mut graph = Graph::new();
+        let mut index = HashMap::new();
+        for file in ast.files() {
+            for msg in file.all_messages() {
+                let idx = graph.add_node(msg.clone());
+                index.insert(msg.fully_qualified_name().to_string(), idx);
+            }
+        }
+        for from in graph.node_indices().collect::<Vec<_>>() {
+            for field in graph[from].fields() {
+                let Some(to) = field
+                    .embed()
+                    .and_then(|embed| index.get(embed.fully_qualified_name()).copied())
+                else {
+                    continue;
+                };
+                graph.add_edge(from, to, field);
+            }
+        }
+        let (boxed, boxed_names) = boxed_fields(&graph);
+        Self {
+            index,
+            graph,
+            boxed,
+            boxed_names,
+        }
+    }
+
+    /// Returns `true` if `inner` is reachable from `outer` through any chain
+    /// of fields, including when `outer` and `inner` are the same message and
+    /// it is recursive.
+    pub fn is_nested(&self, outer: &Message, inner: &Message) -> bool {
+        let (Some(&outer), Some(&inner)) = (
+            self.index.get(outer.fully_qualified_name()),
+            self.index.get(inner.fully_qualified_name()),
+        ) else {
+            return false;
+        };
+        if outer == inner {
+            return self
+                .graph
+                .neighbors(outer)
+                .any(|n| has_path_connecting(&self.graph, n, inner, None));
+        }
+        has_path_connecting(&self.graph, outer, inner, None)
+    }
+
+    /// Returns the strongly connected components of the graph, in reverse
+    /// topological order: a message appears after every message it embeds,
+    /// unless they are part of the same component.
+    ///
+    /// A component with more than one message, or with a single message
+    /// which embeds itself, is a cycle.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<Message>> {
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|scc| scc.into_iter().map(|idx| self.graph[idx].clone()).collect())
+            .collect()
+    }
+
+    /// Returns `true` if `field` must be boxed to break a recursive cycle.
+    pub fn needs_box(&self, field: &Field) -> bool {
+        self.boxed_names.contains(field.fully_qualified_name())
+    }
+
+    /// Returns the fields which must be boxed to break recursive cycles.
+    pub fn boxed_fields(&self) -> &[Field] {
+        &self.boxed
+    }
+}
+
+/// Determines the singular embed fields which take part in a cycle made up
+/// entirely of singular fields.
+fn boxed_fields(graph: &Graph<Message, Field>) -> (Vec<Field>, HashSet<String>) {
+    let singular = graph.filter_map(
+        |_, _| Some(()),
+        |_, field| (!field.is_repeated() && !field.is_map()).then_some(()),
+    );
+    let mut component = HashMap::new();
+    for (id, scc) in tarjan_scc(&singular).into_iter().enumerate() {
+        let is_cycle = scc.len() > 1 || singular.contains_edge(scc[0], scc[0]);
+        if is_cycle {
+            for idx in scc {
+                component.insert(idx, id);
+            }
+        }
+    }
+    let mut boxed = Vec::new();
+    let mut names = HashSet::new();
+    for edge in graph.edge_references() {
+        let field = edge.weight();
+        if field.is_repeated() || field.is_map() {
+            continue;
+        }
+        let (Some(from), Some(to)) = (component.get(&edge.source()), component.get(&edge.target()))
+        else {
+            continue;
+        };
+        if from == to && names.insert(field.fully_qualified_name().to_string()) {
+            boxed.push(field.clone());
+        }
+    }
+    (boxed, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::descriptor::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, MessageOptions, OneofDescriptorProto,
+    };
+
+    use super::*;
+    use crate::test_util::{self, FileBuilder};
+
+    /// A message named `name` with `fields`; the type of each is either the
+    /// fully qualified name of a message or empty for a string.
+    fn message(name: &str, fields: &[(&str, Label, &str)]) -> DescriptorProto {
+        let fields = fields
+            .iter()
+            .map(|&(name, label, type_name)| field(name, label, type_name))
+            .collect();
+        test_util::message(name, fields)
+    }
+
+    fn field(name: &str, label: Label, type_name: &str) -> FieldDescriptorProto {
+        let type_ = if type_name.is_empty() {
+            Type::TYPE_STRING
+        } else {
+            Type::TYPE_MESSAGE
+        };
+        test_util::field(name, label, type_, type_name)
+    }
+
+    /// The `Ast` of `rec/recursive.proto`, equivalent to:
+    ///
+    /// ```proto
+    /// message Tree { Tree left = 1; Tree right = 2; repeated Tree children = 3; Leaf leaf = 4; }
+    /// message Leaf { string name = 1; }
+    /// message A { B b = 1; }
+    /// message B { C c = 1; }
+    /// message C { A a = 1; repeated B bs = 2; }
+    /// message List { repeated List next = 1; }
+    /// message Graph { map<string, Graph> nodes = 1; }
+    /// message Expr { oneof kind { Expr not = 1; string value = 2; } }
+    /// ```
+    fn recursive() -> Ast {
+        use Label::{LABEL_OPTIONAL as OPTIONAL, LABEL_REPEATED as REPEATED};
+
+        let mut entry = message(
+            "NodesEntry",
+            &[("key", OPTIONAL, ""), ("value", OPTIONAL, ".rec.Graph")],
+        );
+        let mut options = MessageOptions::new();
+        options.set_map_entry(true);
+        entry.options = Some(options).into();
+        let mut graph = message("Graph", &[("nodes", REPEATED, ".rec.Graph.NodesEntry")]);
+        graph.nested_type.push(entry);
+
+        let mut expr = message(
+            "Expr",
+            &[("not", OPTIONAL, ".rec.Expr"), ("value", OPTIONAL, "")],
+        );
+        let mut kind = OneofDescriptorProto::new();
+        kind.set_name("kind".to_string());
+        expr.oneof_decl.push(kind);
+        for field in &mut expr.field {
+            field.set_oneof_index(0);
+        }
+
+        FileBuilder::new("rec/recursive.proto", "rec")
+            .message(message(
+                "Tree",
+                &[
+                    ("left", OPTIONAL, ".rec.Tree"),
+                    ("right", OPTIONAL, ".rec.Tree"),
+                    ("children", REPEATED, ".rec.Tree"),
+                    ("leaf", OPTIONAL, ".rec.Leaf"),
+                ],
+            ))
+            .message(message("Leaf", &[("name", OPTIONAL, "")]))
+            .message(message("A", &[("b", OPTIONAL, ".rec.B")]))
+            .message(message("B", &[("c", OPTIONAL, ".rec.C")]))
+            .message(message(
+                "C",
+                &[("a", OPTIONAL, ".rec.A"), ("bs", REPEATED, ".rec.B")],
+            ))
+            .message(message("List", &[("next", REPEATED, ".rec.List")]))
+            .message(graph)
+            .message(expr)
+            .ast()
+    }
+
+    fn msg(ast: &Ast, name: &str) -> Message {
+        ast.file("rec/recursive.proto")
+            .unwrap()
+            .message(name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_is_nested() {
+        let ast = recursive();
+        let graph = MessageGraph::new(&ast);
+        let nested = |outer, inner| graph.is_nested(&msg(&ast, outer), &msg(&ast, inner));
+
+        assert!(nested("Tree", "Tree"));
+        assert!(nested("Tree", "Leaf"));
+        assert!(!nested("Leaf", "Leaf"));
+        assert!(!nested("Leaf", "Tree"));
+        assert!(nested("A", "C"));
+        assert!(nested("C", "A"));
+        assert!(nested("B", "B"));
+        assert!(nested("List", "List"));
+        assert!(nested("Graph", "Graph"));
+        assert!(nested("Expr", "Expr"));
+        assert!(!nested("A", "Tree"));
+
+        let kitchen = test_util::kitchen();
+        let sink = kitchen.file("kitchen/sink.proto").unwrap();
+        let sink = sink.message("Sink").unwrap();
+        assert!(!graph.is_nested(&sink, &msg(&ast, "Tree")));
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let ast = recursive();
+        let graph = MessageGraph::new(&ast);
+        let sccs: Vec<Vec<String>> = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|scc| {
+                let mut names: Vec<_> = scc.iter().map(|m| m.name().to_string()).collect();
+                names.sort();
+                names
+            })
+            .collect();
+        let position = |name: &str| sccs.iter().position(|scc| scc.contains(&name.into()));
+
+        assert!(sccs.contains(&vec!["A".into(), "B".into(), "C".into()]));
+        for name in ["Tree", "Leaf", "List", "Graph", "Expr"] {
+            assert!(sccs.contains(&vec![name.to_string()]), "{name}");
+        }
+        assert!(position("Leaf") < position("Tree"));
+    }
+
+    #[test]
+    fn test_boxed_fields() {
+        let ast = recursive();
+        let graph = MessageGraph::new(&ast);
+        let mut boxed: Vec<_> = graph
+            .boxed_fields()
+            .iter()
+            .map(|f| f.fully_qualified_name().to_string())
+            .collect();
+        boxed.sort();
+        assert_eq!(
+            boxed,
+            [
+                ".rec.A.b",
+                ".rec.B.c",
+                ".rec.C.a",
+                ".rec.Expr.not",
+                ".rec.Tree.left",
+                ".rec.Tree.right",
+            ]
+        );
+
+        let field = |message, name| msg(&ast, message).field(name).unwrap();
+        assert!(graph.needs_box(&field("Tree", "left")));
+        assert!(!graph.needs_box(&field("Tree", "children")));
+        assert!(!graph.needs_box(&field("Tree", "leaf")));
+        assert!(!graph.needs_box(&field("C", "bs")));
+        assert!(!graph.needs_box(&field("List", "next")));
+        assert!(!graph.needs_box(&field("Graph", "nodes")));
+        assert!(graph.needs_box(&field("Expr", "not")));
+        assert!(!graph.needs_box(&field("Expr", "value")));
+    }
+}
@@ -0,0 +1,121 @@
+/// Groups the nodes `0..len` of a directed graph into strongly connected
+/// components using Tarjan's algorithm, where `dependencies(n)` returns the
+/// nodes which `n` depends on.
+///
+/// Components are returned in dependency order: every component appears after
+/// the components it depends on. Nodes within a component, and independent
+/// components, retain their relative input order where possible.
+///
+/// The traversal is iterative, so deep dependency chains cannot overflow the
+/// stack.
+pub(crate) fn dependency_order<F, I>(len: usize, dependencies: F) -> Vec<Vec<usize>>
+where
+    F: Fn(usize) -> I,
+    I: IntoIterator<Item = usize>,
+{
+    let mut tarjan = Tarjan {
+        index: 0,
+        indices: vec![None; len],
+        low_links: vec![0; len],
+        on_stack: vec![false; len],
+        stack: Vec::new(),
+        components: Vec::new(),
+    };
+    // Each frame holds a node being visited and its dependencies yet to be
+    // visited, in place of the recursive calls.
+    let mut frames: Vec<(usize, I::IntoIter)> = Vec::new();
+    for root in 0..len {
+        if tarjan.indices[root].is_some() {
+            continue;
+        }
+        tarjan.enter(root);
+        frames.push((root, dependencies(root).into_iter()));
+        while let Some((node, deps)) = frames.last_mut() {
+            let node = *node;
+            match deps.next() {
+                Some(dep) => match tarjan.indices[dep] {
+                    None => {
+                        tarjan.enter(dep);
+                        frames.push((dep, dependencies(dep).into_iter()));
+                    }
+                    Some(index) if tarjan.on_stack[dep] => {
+                        tarjan.low_links[node] = tarjan.low_links[node].min(index);
+                    }
+                    Some(_) => {}
+                },
+                None => {
+                    frames.pop();
+                    if let Some(&(parent, _)) = frames.last() {
+                        tarjan.low_links[parent] =
+                            tarjan.low_links[parent].min(tarjan.low_links[node]);
+                    }
+                    tarjan.leave(node);
+                }
+            }
+        }
+    }
+    tarjan.components
+}
+
+struct Tarjan {
+    index: usize,
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan {
+    /// Numbers `node` and pushes it onto the stack, before its dependencies
+    /// are visited.
+    fn enter(&mut self, node: usize) {
+        self.indices[node] = Some(self.index);
+        self.low_links[node] = self.index;
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+    }
+
+    /// Pops the component rooted at `node`, if any, once its dependencies have
+    /// been visited.
+    fn leave(&mut self, node: usize) {
+        if Some(self.low_links[node]) != self.indices[node] {
+            return;
+        }
+        let mut component = Vec::new();
+        while let Some(n) = self.stack.pop() {
+            self.on_stack[n] = false;
+            component.push(n);
+            if n == node {
+                break;
+            }
+        }
+        component.sort_unstable();
+        self.components.push(component);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_order() {
+        // 0 -> 1 -> 2 -> 1, 3 -> 0
+        let deps = [vec![1], vec![2], vec![1], vec![0]];
+        let order = dependency_order(deps.len(), |n| deps[n].clone());
+        assert_eq!(order, vec![vec![1, 2], vec![0], vec![3]]);
+    }
+
+    #[test]
+    fn test_dependency_order_deep() {
+        // n -> n + 1, and the last node back to the first
+        let len = 1_000_000;
+        let order = dependency_order(len, |n| [(n + 1) % len]);
+        assert_eq!(order, vec![(0..len).collect::<Vec<_>>()]);
+        let order = dependency_order(len, |n| (n + 1 < len).then_some(n + 1));
+        assert_eq!(order.len(), len);
+        assert_eq!(order[0], vec![len - 1]);
+    }
+}
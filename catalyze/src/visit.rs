@@ -0,0 +1,671 @@
+//! Structured traversal of the node graph.
+//!
+//! [`Visitor`] and its fallible variant [`TryVisitor`] share one convention:
+//! each hook has a default implementation which calls the corresponding
+//! `walk_*` (or `try_walk_*`) function, and that function calls the hooks of
+//! the node's children. The function never calls the hook of the node it is
+//! given. To skip a subtree, override the hook and do not call `walk_*`:
+//!
+//! ```
+//! use catalyze::{service::Service, visit::{self, Visitor}, File};
+//!
+//! /// Counts the services of build targets.
+//! struct Count(usize);
+//!
+//! impl Visitor for Count {
+//!     fn visit_file(&mut self, file: &File) {
+//!         if file.build_target() {
+//!             visit::walk_file(self, file);
+//!         }
+//!     }
+//!     fn visit_service(&mut self, service: &Service) {
+//!         self.0 += 1;
+//!         visit::walk_service(self, service);
+//!     }
+//! }
+//! ```
+
+use crate::{
+    enum_::{Enum, EnumValue},
+    extension::Extension,
+    field::Field,
+    file::File,
+    message::Message,
+    method::Method,
+    oneof::Oneof,
+    package::Package,
+    service::Service,
+    Ast,
+};
+
+/// Hooks for each kind of node, called by the `walk_*` functions.
+///
+/// Each hook defaults to calling the matching `walk_*` function, which calls
+/// the hooks of the node's children. An overriding hook visits the children
+/// by calling it too, or skips them by not doing so.
+///
+/// Fields which belong to a oneof are visited as fields of their containing
+/// message; [`walk_oneof`] does not revisit them.
+pub trait Visitor {
+    fn visit_package(&mut self, package: &Package) {
+        walk_package(self, package);
+    }
+    fn visit_file(&mut self, file: &File) {
+        walk_file(self, file);
+    }
+    fn visit_message(&mut self, message: &Message) {
+        walk_message(self, message);
+    }
+    fn visit_field(&mut self, field: &Field) {
+        walk_field(self, field);
+    }
+    fn visit_oneof(&mut self, oneof: &Oneof) {
+        walk_oneof(self, oneof);
+    }
+    fn visit_enum(&mut self, enum_: &Enum) {
+        walk_enum(self, enum_);
+    }
+    fn visit_enum_value(&mut self, value: &EnumValue) {
+        walk_enum_value(self, value);
+    }
+    fn visit_service(&mut self, service: &Service) {
+        walk_service(self, service);
+    }
+    fn visit_method(&mut self, method: &Method) {
+        walk_method(self, method);
+    }
+    fn visit_extension(&mut self, extension: &Extension) {
+        walk_extension(self, extension);
+    }
+}
+
+/// Visits every package of the [`Ast`].
+pub fn walk_ast<V: Visitor + ?Sized>(v: &mut V, ast: &Ast) {
+    for package in ast.packages() {
+        v.visit_package(&package);
+    }
+}
+
+pub fn walk_package<V: Visitor + ?Sized>(v: &mut V, package: &Package) {
+    for file in package.files() {
+        v.visit_file(&file);
+    }
+}
+
+/// Visits the messages, enums, services and extensions defined at the top
+/// level of the file, in that order.
+pub fn walk_file<V: Visitor + ?Sized>(v: &mut V, file: &File) {
+    for message in file.messages() {
+        v.visit_message(&message);
+    }
+    for enum_ in file.enums() {
+        v.visit_enum(&enum_);
+    }
+    for service in file.services() {
+        v.visit_service(&service);
+    }
+    for extension in file.defined_extensions() {
+        v.visit_extension(&extension);
+    }
+}
+
+/// Visits the fields, oneofs, nested messages, nested enums and extensions
+/// defined within the message, in that order.
+pub fn walk_message<V: Visitor + ?Sized>(v: &mut V, message: &Message) {
+    for field in message.fields() {
+        v.visit_field(&field);
+    }
+    for oneof in message.oneofs() {
+        v.visit_oneof(&oneof);
+    }
+    for nested in message.messages() {
+        v.visit_message(&nested);
+    }
+    for enum_ in message.enums() {
+        v.visit_enum(&enum_);
+    }
+    for extension in message.defined_extensions() {
+        v.visit_extension(&extension);
+    }
+}
+
+pub fn walk_field<V: Visitor + ?Sized>(_v: &mut V, _field: &Field) {}
+
+pub fn walk_oneof<V: Visitor + ?Sized>(_v: &mut V, _oneof: &Oneof) {}
+
+pub fn walk_enum<V: Visitor + ?Sized>(v: &mut V, enum_: &Enum) {
+    for value in enum_.values() {
+        v.visit_enum_value(&value);
+    }
+}
+
+pub fn walk_enum_value<V: Visitor + ?Sized>(_v: &mut V, _value: &EnumValue) {}
+
+pub fn walk_service<V: Visitor + ?Sized>(v: &mut V, service: &Service) {
+    for method in service.methods() {
+        v.visit_method(&method);
+    }
+}
+
+pub fn walk_method<V: Visitor + ?Sized>(_v: &mut V, _method: &Method) {}
+
+pub fn walk_extension<V: Visitor + ?Sized>(_v: &mut V, _extension: &Extension) {}
+
+/// Directs traversal after a [`TryVisitor`] hook returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Flow {
+    /// Continue with the node's siblings.
+    #[default]
+    Continue,
+    /// Continue with the node's siblings, its children having been skipped by
+    /// not calling its `try_walk_*` function.
+    SkipChildren,
+    /// Stop the traversal.
+    Break,
+}
+
+impl Flow {
+    pub fn is_break(&self) -> bool {
+        matches!(self, Flow::Break)
+    }
+}
+
+/// A fallible variant of [`Visitor`] whose hooks direct the traversal.
+///
+/// As with [`Visitor`], each hook defaults to calling the matching
+/// `try_walk_*` function, which calls the hooks of the node's children and
+/// returns [`Flow::Break`] if one of them does. An overriding hook visits the
+/// children by returning the result of `try_walk_*`, or skips them by
+/// returning [`Flow::SkipChildren`] instead. Returning [`Flow::Break`] or an
+/// `Err` stops the traversal entirely.
+///
+/// To report every problem in a single pass, rather than stopping at the
+/// first, collect them into [`Diagnostics`](crate::Diagnostics) and continue.
+pub trait TryVisitor {
+    type Error;
+
+    fn visit_package(&mut self, package: &Package) -> Result<Flow, Self::Error> {
+        try_walk_package(self, package)
+    }
+    fn visit_file(&mut self, file: &File) -> Result<Flow, Self::Error> {
+        try_walk_file(self, file)
+    }
+    fn visit_message(&mut self, message: &Message) -> Result<Flow, Self::Error> {
+        try_walk_message(self, message)
+    }
+    fn visit_field(&mut self, field: &Field) -> Result<Flow, Self::Error> {
+        try_walk_field(self, field)
+    }
+    fn visit_oneof(&mut self, oneof: &Oneof) -> Result<Flow, Self::Error> {
+        try_walk_oneof(self, oneof)
+    }
+    fn visit_enum(&mut self, enum_: &Enum) -> Result<Flow, Self::Error> {
+        try_walk_enum(self, enum_)
+    }
+    fn visit_enum_value(&mut self, value: &EnumValue) -> Result<Flow, Self::Error> {
+        try_walk_enum_value(self, value)
+    }
+    fn visit_service(&mut self, service: &Service) -> Result<Flow, Self::Error> {
+        try_walk_service(self, service)
+    }
+    fn visit_method(&mut self, method: &Method) -> Result<Flow, Self::Error> {
+        try_walk_method(self, method)
+    }
+    fn visit_extension(&mut self, extension: &Extension) -> Result<Flow, Self::Error> {
+        try_walk_extension(self, extension)
+    }
+}
+
+/// Calls the hook of each child, returning early if the traversal is stopped.
+macro_rules! children {
+    ($v:expr, $hook:ident, $iter:expr) => {
+        for child in $iter {
+            if $v.$hook(&child)?.is_break() {
+                return Ok(Flow::Break);
+            }
+        }
+    };
+}
+
+/// Visits every package of the [`Ast`], returning [`Flow::Break`] if the
+/// traversal was stopped.
+pub fn try_walk_ast<V: TryVisitor + ?Sized>(v: &mut V, ast: &Ast) -> Result<Flow, V::Error> {
+    children!(v, visit_package, ast.packages());
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_package<V: TryVisitor + ?Sized>(
+    v: &mut V,
+    package: &Package,
+) -> Result<Flow, V::Error> {
+    children!(v, visit_file, package.files());
+    Ok(Flow::Continue)
+}
+
+/// Visits the messages, enums, services and extensions defined at the top
+/// level of the file, in that order.
+pub fn try_walk_file<V: TryVisitor + ?Sized>(v: &mut V, file: &File) -> Result<Flow, V::Error> {
+    children!(v, visit_message, file.messages());
+    children!(v, visit_enum, file.enums());
+    children!(v, visit_service, file.services());
+    children!(v, visit_extension, file.defined_extensions());
+    Ok(Flow::Continue)
+}
+
+/// Visits the fields, oneofs, nested messages, nested enums and extensions
+/// defined within the message, in that order.
+pub fn try_walk_message<V: TryVisitor + ?Sized>(
+    v: &mut V,
+    message: &Message,
+) -> Result<Flow, V::Error> {
+    children!(v, visit_field, message.fields());
+    children!(v, visit_oneof, message.oneofs());
+    children!(v, visit_message, message.messages());
+    children!(v, visit_enum, message.enums());
+    children!(v, visit_extension, message.defined_extensions());
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_field<V: TryVisitor + ?Sized>(
+    _v: &mut V,
+    _field: &Field,
+) -> Result<Flow, V::Error> {
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_oneof<V: TryVisitor + ?Sized>(
+    _v: &mut V,
+    _oneof: &Oneof,
+) -> Result<Flow, V::Error> {
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_enum<V: TryVisitor + ?Sized>(v: &mut V, enum_: &Enum) -> Result<Flow, V::Error> {
+    children!(v, visit_enum_value, enum_.values());
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_enum_value<V: TryVisitor + ?Sized>(
+    _v: &mut V,
+    _value: &EnumValue,
+) -> Result<Flow, V::Error> {
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_service<V: TryVisitor + ?Sized>(
+    v: &mut V,
+    service: &Service,
+) -> Result<Flow, V::Error> {
+    children!(v, visit_method, service.methods());
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_method<V: TryVisitor + ?Sized>(
+    _v: &mut V,
+    _method: &Method,
+) -> Result<Flow, V::Error> {
+    Ok(Flow::Continue)
+}
+
+pub fn try_walk_extension<V: TryVisitor + ?Sized>(
+    _v: &mut V,
+    _extension: &Extension,
+) -> Result<Flow, V::Error> {
+    Ok(Flow::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, convert::Infallible};
+
+    use super::*;
+    use crate::{test_util, Diagnostics};
+
+    /// Records each node it visits, skipping the children of `skip`.
+    #[derive(Default)]
+    struct Record {
+        skip: &'static str,
+        seen: Vec<String>,
+    }
+
+    impl Record {
+        fn enter(&mut self, kind: &str, name: &str) -> bool {
+            self.seen.push(format!("{kind} {name}"));
+            name != self.skip
+        }
+    }
+
+    impl Visitor for Record {
+        fn visit_package(&mut self, package: &Package) {
+            if self.enter("package", package.name()) {
+                walk_package(self, package);
+            }
+        }
+        fn visit_file(&mut self, file: &File) {
+            if self.enter("file", file.name()) {
+                walk_file(self, file);
+            }
+        }
+        fn visit_message(&mut self, message: &Message) {
+            if self.enter("message", message.name()) {
+                walk_message(self, message);
+            }
+        }
+        fn visit_field(&mut self, field: &Field) {
+            self.enter("field", field.name());
+        }
+        fn visit_oneof(&mut self, oneof: &Oneof) {
+            self.enter("oneof", oneof.name());
+        }
+        fn visit_enum(&mut self, enum_: &Enum) {
+            if self.enter("enum", enum_.name()) {
+                walk_enum(self, enum_);
+            }
+        }
+        fn visit_enum_value(&mut self, value: &EnumValue) {
+            self.enter("value", value.name());
+        }
+        fn visit_service(&mut self, service: &Service) {
+            if self.enter("service", service.name()) {
+                walk_service(self, service);
+            }
+        }
+        fn visit_method(&mut self, method: &Method) {
+            self.enter("method", method.name());
+        }
+        fn visit_extension(&mut self, extension: &Extension) {
+            self.enter("extension", extension.name());
+        }
+    }
+
+    /// Records each node it visits, returning the [`Flow`] configured for its
+    /// name, or an error if it is named `fail`. Children are visited unless
+    /// another `Flow` is configured.
+    #[derive(Default)]
+    struct Steer {
+        flows: HashMap<&'static str, Flow>,
+        fail: &'static str,
+        seen: Vec<String>,
+    }
+
+    impl Steer {
+        fn enter(&mut self, kind: &str, name: &str) -> Result<Flow, String> {
+            self.seen.push(format!("{kind} {name}"));
+            if name == self.fail {
+                return Err(name.to_string());
+            }
+            Ok(self.flows.get(name).copied().unwrap_or_default())
+        }
+    }
+
+    impl TryVisitor for Steer {
+        type Error = String;
+        fn visit_package(&mut self, package: &Package) -> Result<Flow, String> {
+            match self.enter("package", package.name())? {
+                Flow::Continue => try_walk_package(self, package),
+                flow => Ok(flow),
+            }
+        }
+        fn visit_file(&mut self, file: &File) -> Result<Flow, String> {
+            match self.enter("file", file.name())? {
+                Flow::Continue => try_walk_file(self, file),
+                flow => Ok(flow),
+            }
+        }
+        fn visit_message(&mut self, message: &Message) -> Result<Flow, String> {
+            match self.enter("message", message.name())? {
+                Flow::Continue => try_walk_message(self, message),
+                flow => Ok(flow),
+            }
+        }
+        fn visit_field(&mut self, field: &Field) -> Result<Flow, String> {
+            self.enter("field", field.name())
+        }
+        fn visit_oneof(&mut self, oneof: &Oneof) -> Result<Flow, String> {
+            self.enter("oneof", oneof.name())
+        }
+        fn visit_enum(&mut self, enum_: &Enum) -> Result<Flow, String> {
+            match self.enter("enum", enum_.name())? {
+                Flow::Continue => try_walk_enum(self, enum_),
+                flow => Ok(flow),
+            }
+        }
+        fn visit_enum_value(&mut self, value: &EnumValue) -> Result<Flow, String> {
+            self.enter("value", value.name())
+        }
+        fn visit_service(&mut self, service: &Service) -> Result<Flow, String> {
+            match self.enter("service", service.name())? {
+                Flow::Continue => try_walk_service(self, service),
+                flow => Ok(flow),
+            }
+        }
+        fn visit_method(&mut self, method: &Method) -> Result<Flow, String> {
+            self.enter("method", method.name())
+        }
+        fn visit_extension(&mut self, extension: &Extension) -> Result<Flow, String> {
+            self.enter("extension", extension.name())
+        }
+    }
+
+    fn sink() -> File {
+        test_util::kitchen().file("kitchen/sink.proto").unwrap()
+    }
+
+    #[test]
+    fn test_visit_order() {
+        let mut record = Record::default();
+        record.visit_file(&sink());
+        assert_eq!(
+            record.seen,
+            [
+                "file kitchen/sink.proto",
+                "message Sink",
+                "field brand",
+                "field material",
+                "field model",
+                "field basin_count",
+                "field installed",
+                "message Material",
+                "field type",
+                "field finish",
+                "enum Type",
+                "value STAINLESS_STEEL",
+                "value COPPER",
+                "value GRANITE",
+                "value SOAPSTONE",
+                "value CERAMIC",
+                "value CAST_IRON",
+                "enum Finish",
+                "value NONE",
+                "value POLISHED",
+                "value MIRROR",
+                "value HAMMERED_MIRROR",
+                "value BRIGHT_SATIN",
+                "value BRUSHED_SATIN",
+                "enum Brand",
+                "value KRAUS",
+                "value SWANSTONE",
+                "value HOUZER",
+                "value BLANCO",
+                "value KOHLER",
+            ]
+        );
+
+        let ast = test_util::kitchen();
+        let kitchen = ast.file("kitchen/kitchen.proto").unwrap();
+        let mut record = Record::default();
+        record.visit_message(&kitchen.message("Color").unwrap());
+        assert_eq!(
+            record.seen,
+            [
+                "message Color",
+                "field pantone",
+                "field rgb",
+                "field cmyk",
+                "field alpha",
+                "oneof model",
+                "message RGB",
+                "field r",
+                "field g",
+                "field b",
+                "message CMYK",
+                "field c",
+                "field m",
+                "field y",
+                "field k",
+            ]
+        );
+
+        let mut record = Record::default();
+        record.visit_service(&kitchen.services().next().unwrap());
+        assert_eq!(
+            record.seen,
+            [
+                "service Cooking",
+                "method Saute",
+                "method DispenseIce",
+                "method LoadFridge",
+                "method OrderDrinks",
+                "method CheckBestByDate",
+            ]
+        );
+
+        let mut record = Record::default();
+        walk_ast(&mut record, &ast);
+        let mut packages: Vec<_> = record
+            .seen
+            .iter()
+            .filter(|s| s.starts_with("package "))
+            .collect();
+        packages.sort();
+        assert_eq!(packages, ["package google.protobuf", "package kitchen"]);
+        for file in ast.files() {
+            let entry = format!("file {}", file.name());
+            assert_eq!(record.seen.iter().filter(|s| **s == entry).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_visit_skip_children() {
+        let mut record = Record {
+            skip: "Material",
+            ..Default::default()
+        };
+        record.visit_file(&sink());
+        let material = record.seen.iter().position(|s| s == "message Material");
+        assert_eq!(
+            record.seen[material.unwrap()..],
+            [
+                "message Material",
+                "enum Brand",
+                "value KRAUS",
+                "value SWANSTONE",
+                "value HOUZER",
+                "value BLANCO",
+                "value KOHLER"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_visit_flow() {
+        let mut steer = Steer {
+            flows: HashMap::from([("Material", Flow::SkipChildren), ("SWANSTONE", Flow::Break)]),
+            ..Default::default()
+        };
+        assert_eq!(steer.visit_file(&sink()), Ok(Flow::Break));
+        assert_eq!(
+            steer.seen[steer.seen.len() - 5..],
+            [
+                "field installed",
+                "message Material",
+                "enum Brand",
+                "value KRAUS",
+                "value SWANSTONE",
+            ]
+        );
+
+        let ast = test_util::kitchen();
+        let mut steer = Steer {
+            flows: HashMap::from([("Sink", Flow::Break)]),
+            ..Default::default()
+        };
+        assert_eq!(try_walk_ast(&mut steer, &ast), Ok(Flow::Break));
+        assert_eq!(steer.seen.last().unwrap(), "message Sink");
+
+        let mut steer = Steer {
+            fail: "model",
+            ..Default::default()
+        };
+        assert_eq!(steer.visit_file(&sink()), Err("model".to_string()));
+        assert_eq!(steer.seen.last().unwrap(), "field model");
+
+        // Without intervention, the traversal matches that of `Visitor`.
+        let mut steer = Steer::default();
+        assert_eq!(try_walk_ast(&mut steer, &ast), Ok(Flow::Continue));
+        let mut record = Record::default();
+        walk_ast(&mut record, &ast);
+        assert_eq!(steer.seen, record.seen);
+    }
+
+    /// Reports messages without fields as errors and enums whose zero value is
+    /// not `*_UNSPECIFIED` as warnings.
+    #[derive(Default)]
+    struct Lint(Diagnostics);
+
+    impl TryVisitor for Lint {
+        type Error = Infallible;
+        fn visit_message(&mut self, message: &Message) -> Result<Flow, Infallible> {
+            if message.fields().next().is_none() {
+                self.0.error(message.clone(), "message has no fields");
+            }
+            try_walk_message(self, message)
+        }
+        fn visit_enum_value(&mut self, value: &EnumValue) -> Result<Flow, Infallible> {
+            if value.number() == 0 && !value.name().ends_with("_UNSPECIFIED") {
+                self.0
+                    .warning(value.clone(), "zero value should be named *_UNSPECIFIED");
+            }
+            Ok(Flow::Continue)
+        }
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut lint = Lint::default();
+        let flow = try_walk_ast(&mut lint, &test_util::kitchen()).unwrap();
+        assert_eq!(flow, Flow::Continue);
+        let diagnostics = lint.0;
+        assert!(diagnostics.has_errors());
+        let names = |iter: &mut dyn Iterator<Item = &crate::Diagnostic>| {
+            let mut names: Vec<_> = iter
+                .map(|d| d.node().fully_qualified_name().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(&mut diagnostics.errors()),
+            [
+                ".kitchen.DrinkOrder",
+                ".kitchen.GroceryItem",
+                ".kitchen.IceRequest",
+                ".kitchen.IceResponse",
+                ".kitchen.LoadSummary",
+                ".kitchen.PreparedDrink",
+                ".kitchen.SauteRequest",
+                ".kitchen.SauteResponse",
+            ]
+        );
+        assert_eq!(
+            names(&mut diagnostics.warnings()),
+            [
+                ".kitchen.Sink.Brand.KRAUS",
+                ".kitchen.Sink.Material.Finish.NONE",
+                ".kitchen.Sink.Material.Type.STAINLESS_STEEL",
+                ".kitchen.Style.CONTEMPORARY",
+            ]
+        );
+        assert_eq!(diagnostics.len(), 12);
+    }
+}
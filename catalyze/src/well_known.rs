@@ -12,28 +12,59 @@ impl std::str::FromStr for WellKnownType {
     type Err = crate::error::Error;
 
     fn from_str(s: &str) -> ::std::result::Result<WellKnownType, Self::Err> {
+        WellKnownMessage::from_str(s)
+            .map(WellKnownType::Message)
+            .or_else(|_| WellKnownEnum::from_str(s).map(WellKnownType::Enum))
+    }
+}
+
+impl std::str::FromStr for WellKnownMessage {
+    type Err = crate::error::Error;
+
+    fn from_str(s: &str) -> ::std::result::Result<WellKnownMessage, Self::Err> {
+        match s {
+            "Any" => Ok(WellKnownMessage::Any),
+            "Api" => Ok(WellKnownMessage::Api),
+            "BoolValue" => Ok(WellKnownMessage::BoolValue),
+            "BytesValue" => Ok(WellKnownMessage::BytesValue),
+            "DoubleValue" => Ok(WellKnownMessage::DoubleValue),
+            "Duration" => Ok(WellKnownMessage::Duration),
+            "Empty" => Ok(WellKnownMessage::Empty),
+            "Enum" => Ok(WellKnownMessage::Enum),
+            "EnumValue" => Ok(WellKnownMessage::EnumValue),
+            "Field" => Ok(WellKnownMessage::Field),
+            "FieldKind" => Ok(WellKnownMessage::FieldKind),
+            "FieldMask" => Ok(WellKnownMessage::FieldMask),
+            "FloatValue" => Ok(WellKnownMessage::FloatValue),
+            "Int32Value" => Ok(WellKnownMessage::Int32Value),
+            "Int64Value" => Ok(WellKnownMessage::Int64Value),
+            "ListValue" => Ok(WellKnownMessage::ListValue),
+            "Method" => Ok(WellKnownMessage::Method),
+            "Mixin" => Ok(WellKnownMessage::Mixin),
+            "Option" => Ok(WellKnownMessage::Option),
+            "SourceContext" => Ok(WellKnownMessage::SourceContext),
+            "StringValue" => Ok(WellKnownMessage::StringValue),
+            "Struct" => Ok(WellKnownMessage::Struct),
+            "Timestamp" => Ok(WellKnownMessage::Timestamp),
+            "Type" => Ok(WellKnownMessage::Type),
+            "UInt32Value" => Ok(WellKnownMessage::UInt32Value),
+            "UInt64Value" => Ok(WellKnownMessage::UInt64Value),
+            "Value" => Ok(WellKnownMessage::Value),
+            value => Err(Error::not_well_known_type(value)),
+        }
+    }
+}
+
+impl std::str::FromStr for WellKnownEnum {
+    type Err = crate::error::Error;
+
+    fn from_str(s: &str) -> ::std::result::Result<WellKnownEnum, Self::Err> {
         match s {
-            "Any" => Ok(WellKnownType::Message(WellKnownMessage::Any)),
-            "Api" => Ok(WellKnownType::Message(WellKnownMessage::Api)),
-            "BoolValue" => Ok(WellKnownType::Message(WellKnownMessage::BoolValue)),
-            "BytesValue" => Ok(WellKnownType::Message(WellKnownMessage::BytesValue)),
-            "DoubleValue" => Ok(WellKnownType::Message(WellKnownMessage::DoubleValue)),
-            "Duration" => Ok(WellKnownType::Message(WellKnownMessage::Duration)),
-            "Empty" => Ok(WellKnownType::Message(WellKnownMessage::Empty)),
-            "Enum" => Ok(WellKnownType::Message(WellKnownMessage::Enum)),
-            "EnumValue" => Ok(WellKnownType::Message(WellKnownMessage::EnumValue)),
-            "Field" => Ok(WellKnownType::Message(WellKnownMessage::Field)),
-            "FieldKind" => Ok(WellKnownType::Message(WellKnownMessage::FieldKind)),
-            "FieldMask" => Ok(WellKnownType::Message(WellKnownMessage::FieldMask)),
-            "FloatValue" => Ok(WellKnownType::Message(WellKnownMessage::FloatValue)),
-            "Int32Value" => Ok(WellKnownType::Message(WellKnownMessage::Int32Value)),
-            "Int64Value" => Ok(WellKnownType::Message(WellKnownMessage::Int64Value)),
-            "ListValue" => Ok(WellKnownType::Message(WellKnownMessage::ListValue)),
-            "Method" => Ok(WellKnownType::Message(WellKnownMessage::Method)),
-            "FieldCardinality" => Ok(WellKnownType::Enum(WellKnownEnum::FieldCardinality)),
-            "NullValue" => Ok(WellKnownType::Enum(WellKnownEnum::NullValue)),
-            "Syntax" => Ok(WellKnownType::Enum(WellKnownEnum::Syntax)),
-            value => Error::not_well_known_type(value),
+            "FieldCardinality" => Ok(WellKnownEnum::FieldCardinality),
+            "FieldKind" => Ok(WellKnownEnum::FieldKind),
+            "NullValue" => Ok(WellKnownEnum::NullValue),
+            "Syntax" => Ok(WellKnownEnum::Syntax),
+            value => Err(Error::not_well_known_type(value)),
         }
     }
 }
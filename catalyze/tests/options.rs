@@ -0,0 +1,127 @@
+//! Reads the `(validate.rules)` options of `person/person.proto` and the
+//! repeated `(optionspb.tags)` option of `options/contact.proto` with
+//! `option::<T>()`, parsing the files without `protoc`.
+
+use std::{env, path::PathBuf};
+
+use catalyze::{Ast, CustomOption, Error, FromMessage, Node, Standalone};
+
+#[derive(Debug, FromMessage, CustomOption)]
+#[option(extension = "(validate.rules)")]
+struct FieldRules {
+    message: Option<MessageRules>,
+    uint64: Option<UInt64Rules>,
+    string: Option<StringRules>,
+    double: Option<DoubleRules>,
+}
+
+#[derive(Debug, FromMessage)]
+struct MessageRules {
+    required: bool,
+}
+
+#[derive(Debug, FromMessage)]
+struct UInt64Rules {
+    gt: Option<u64>,
+}
+
+#[derive(Debug, FromMessage)]
+struct StringRules {
+    email: bool,
+    pattern: Option<String>,
+    max_bytes: Option<u64>,
+}
+
+#[derive(Debug, FromMessage)]
+struct DoubleRules {
+    gte: Option<f64>,
+    lte: Option<f64>,
+}
+
+/// A repeated extension.
+#[derive(Debug, FromMessage, CustomOption)]
+#[option(extension = "(optionspb.tags)")]
+struct Tag {
+    name: String,
+}
+
+/// An extension which is not defined in the input.
+#[derive(Debug, FromMessage, CustomOption)]
+#[option(extension = "unknown.rules")]
+struct Unknown {}
+
+fn parse(file: &str) -> Ast {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/proto");
+    let input = Standalone::parse([&root], [root.join(file)], env::temp_dir(), ()).unwrap();
+    Ast::new(&input).unwrap()
+}
+
+fn rules(ast: &Ast, field: &str) -> FieldRules {
+    let Some(Node::Field(field)) = ast.node(field) else {
+        panic!("{field} is not a field");
+    };
+    field.option::<FieldRules>().unwrap().unwrap()
+}
+
+#[test]
+fn test_option() {
+    let ast = parse("person/person.proto");
+
+    let id = rules(&ast, ".examplepb.Person.id");
+    assert_eq!(id.uint64.unwrap().gt, Some(999));
+    assert!(id.string.is_none());
+
+    let email = rules(&ast, ".examplepb.Person.email").string.unwrap();
+    assert!(email.email);
+    assert_eq!(email.pattern, None);
+
+    let name = rules(&ast, ".examplepb.Person.name").string.unwrap();
+    assert!(!name.email);
+    assert_eq!(
+        name.pattern.as_deref(),
+        Some("^[^[0-9]A-Za-z]+( [^[0-9]A-Za-z]+)*$")
+    );
+    assert_eq!(name.max_bytes, Some(256));
+
+    assert!(
+        rules(&ast, ".examplepb.Person.home")
+            .message
+            .unwrap()
+            .required
+    );
+
+    let lng = rules(&ast, ".examplepb.Person.Location.lng")
+        .double
+        .unwrap();
+    assert_eq!((lng.gte, lng.lte), (Some(-180.0), Some(180.0)));
+}
+
+#[test]
+fn test_option_not_set() {
+    let ast = parse("person/person.proto");
+    let person = ast.node(".examplepb.Person").unwrap();
+    assert!(person.option::<FieldRules>().unwrap().is_none());
+}
+
+#[test]
+fn test_option_extension_not_found() {
+    let ast = parse("person/person.proto");
+    let id = ast.node(".examplepb.Person.id").unwrap();
+    assert!(matches!(
+        id.option::<Unknown>(),
+        Err(Error::NodeNotFound { fully_qualified_name }) if fully_qualified_name == ".unknown.rules"
+    ));
+}
+
+#[test]
+fn test_option_values() {
+    let ast = parse("options/contact.proto");
+    let contact = ast.node(".optionspb.Contact").unwrap();
+    let tags = contact.option_values::<Tag>().unwrap();
+    let names: Vec<_> = tags.iter().map(|tag| tag.name.as_str()).collect();
+    assert_eq!(names, ["pii", "crm"]);
+    assert_eq!(contact.option::<Tag>().unwrap().unwrap().name, "crm");
+
+    let id = ast.node(".optionspb.Contact.id").unwrap();
+    assert!(id.option_values::<Tag>().unwrap().is_empty());
+}